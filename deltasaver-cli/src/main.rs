@@ -0,0 +1,1162 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+use clap::{Parser, Subcommand};
+use deltasaver_core::branch::{self, Branches};
+use deltasaver_core::config::{Config, DirectoryArgs};
+use deltasaver_core::rooms::RoomNames;
+use deltasaver_core::stats::{self, ChapterStats};
+use deltasaver_core::{
+    Chapter, Locale, SaveFile, SaveStore, Slot, config, conflict, dump, duplicates, edit,
+    encryption, export, format, freeze, git, import, index, json, portable, profile, recovery,
+    retention, search, snapshot, transfer, undo,
+};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
+
+/// Manage DELTARUNE saves from the terminal, using the same backup directory
+/// as the DELTASAVER app.
+#[derive(Parser)]
+#[command(name = "deltasaver-cli", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Format dates for this locale, e.g. `en-GB`, instead of the system one.
+    #[arg(long, global = true, value_name = "TAG")]
+    locale: Option<String>,
+
+    /// Manage another game's saves, e.g. `UNDERTALE` or one from
+    /// `profiles.json`, instead of DELTARUNE's.
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
+    #[command(flatten)]
+    directories: DirectoryArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the game's save slots and every local backup.
+    List {
+        /// Only list backups with this tag.
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only list backups matching this search, as in the app's search
+        /// box, e.g. `spamton chapter:2 date:2025-06`.
+        #[arg(long)]
+        search: Option<String>,
+    },
+    /// Back up a game save slot.
+    Backup {
+        chapter: Chapter,
+        /// The slot as shown in game, from 1 to 3.
+        slot: Slot,
+        /// A note on the backup, also the commit message if backups are
+        /// committed to git.
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Set the note on a backup, or clear it with an empty note.
+    Note { id: String, note: String },
+    /// Give a backup a name to list it by, or with an empty name, go back to
+    /// its note or hash.
+    Rename { id: String, name: String },
+    /// Change the player name or dark dollars of a copy of a backup, saved
+    /// as a new backup of the same slot.
+    Edit {
+        id: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        gold: Option<u32>,
+    },
+    /// List a backup's flags that are set or known to mean something, with
+    /// what they mean.
+    Flags {
+        id: String,
+        /// List only flags whose index starts with this, or whose meaning
+        /// contains it.
+        #[arg(long)]
+        search: Option<String>,
+    },
+    /// Print a backup's raw contents, line by line, numbered.
+    Dump {
+        id: String,
+        /// Print the bytes in hex instead.
+        #[arg(long)]
+        hex: bool,
+    },
+    /// Print a backup's parsed fields and flags as JSON.
+    Json {
+        id: String,
+        /// Write the JSON here instead.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Tag a backup, e.g. with the route it is on.
+    Tag { id: String, tag: String },
+    /// Remove a tag from a backup.
+    Untag { id: String, tag: String },
+    /// Pin a backup, so it is never pruned or deleted in bulk.
+    Pin { id: String },
+    /// Unpin a backup.
+    Unpin { id: String },
+    /// Restore a backup into the slot it was taken from.
+    Restore {
+        id: String,
+        /// Restore into this slot instead, from 1 to 3, e.g. to experiment
+        /// on a copy.
+        #[arg(long)]
+        slot: Option<Slot>,
+        /// Restore into this chapter instead, after a warning, e.g. for a
+        /// glitch setup.
+        #[arg(long)]
+        chapter: Option<Chapter>,
+    },
+    /// Put back what was in the slot before the last restore.
+    UndoRestore,
+    /// Move a backup to the trash.
+    Delete { id: String },
+    /// Bundle backups into a `.dsave` archive with their notes and tags, e.g.
+    /// to send them to a friend.
+    Export {
+        /// Where to write the archive, given the `.dsave` extension if it has
+        /// none.
+        output: PathBuf,
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    /// Add the backups in a `.dsave` archive, or just a zip of `filech*`
+    /// files, skipping any already backed up.
+    Import { archive: PathBuf },
+    /// Print a backup as a share code, short enough to paste into a message.
+    Share { id: String },
+    /// Add the save in a share code to the slot it was shared from.
+    ImportCode { code: String },
+    /// List the backups in the trash, with when they were deleted.
+    Trash,
+    /// Put a backup in the trash back.
+    Untrash { id: String },
+    /// Permanently delete everything in the trash.
+    EmptyTrash,
+    /// Move the backups that the `[retention]` rules in config.toml no longer
+    /// keep, other than pinned ones, to the trash, as happens after every
+    /// backup, and purge what has been in the trash longer than `trash_days`.
+    Prune {
+        /// Only list what would be deleted.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Block restoring into a game save slot, and deleting its backups, until
+    /// a given time.
+    Freeze {
+        chapter: Chapter,
+        /// The slot as shown in game, from 1 to 3.
+        slot: Slot,
+        /// A local time, as `YYYY-MM-DD HH:MM` or `YYYY-MM-DD`.
+        #[arg(long)]
+        until: String,
+    },
+    /// Lift a freeze early.
+    Unfreeze {
+        chapter: Chapter,
+        /// The slot as shown in game, from 1 to 3.
+        slot: Slot,
+    },
+    /// Walk through what might have happened to a missing save, offering to
+    /// restore it from each copy found.
+    Recover {
+        chapter: Chapter,
+        /// The slot as shown in game, from 1 to 3.
+        slot: Slot,
+    },
+    /// Show play time, slots used, and backups taken for each chapter.
+    Stats {
+        /// Another profile to compare against, side by side.
+        #[arg(long, value_name = "NAME")]
+        against: Option<String>,
+    },
+    /// Show every directory checked for the game's saves and which is used.
+    Paths,
+    /// Copy every file in the game's save directory, every slot of every
+    /// chapter included, into a snapshot.
+    Snapshot { name: String },
+    /// List snapshots, newest first, with the IDs to restore them by.
+    Snapshots,
+    /// Put the game's save directory back exactly as it was in a snapshot,
+    /// snapshotting it as it is first.
+    RestoreSnapshot { id: String },
+    /// List playthroughs, each a branch of backups, marking the one new
+    /// backups go to.
+    Branches,
+    /// Send new backups to another playthrough, starting it if it is new.
+    SwitchBranch { name: String },
+    /// Pick a playthrough back up: restore the newest backup of every slot in
+    /// it and send new backups to it, backing up the slots first onto the
+    /// playthrough left.
+    RestoreBranch { name: String },
+    /// Push the git repository backups are committed to, set as `git` under
+    /// `[directories]` in config.toml, to its `origin` remote.
+    GitPush,
+    /// Show each migration made to the backup directory by a newer version.
+    History,
+    /// List sets of backups with the same contents.
+    Duplicates {
+        /// Move all but the newest or pinned backups of each set to the
+        /// trash, keeping one for each slot on each branch.
+        #[arg(long)]
+        collapse: bool,
+    },
+    /// Read back every backup to find any damaged by bit rot or truncation,
+    /// failing if there are any.
+    Check,
+    /// Rename backups from before backups had IDs, named by when they were
+    /// taken, to the current naming, listing any that cannot be read.
+    MigrateLegacy,
+    /// Encrypt the contents of every backup with a passphrase from now on,
+    /// asked for each time they are used unless DELTASAVER_PASSPHRASE is set.
+    /// Their filenames still say which chapter and slot each is of, and when
+    /// it was taken.
+    Encrypt,
+    /// Write a synthetic save file into a directory, for testing.
+    #[cfg(feature = "fixtures")]
+    GenerateFixture {
+        directory: PathBuf,
+        chapter: Chapter,
+        /// The slot as shown in game, from 1 to 3.
+        slot: Slot,
+        #[arg(long, default_value = "KRIS")]
+        name: String,
+        #[arg(long, default_value_t = 0)]
+        room: u32,
+        #[arg(long, default_value_t = 0)]
+        gold: u32,
+        /// Sets a flag, as `INDEX=VALUE`. May be repeated.
+        #[arg(long = "flag", value_parser = parse_flag)]
+        flags: Vec<(usize, i64)>,
+    },
+}
+
+#[cfg(feature = "fixtures")]
+fn parse_flag(flag: &str) -> Result<(usize, i64), String> {
+    let (index, value) = flag
+        .split_once('=')
+        .ok_or_else(|| "expected INDEX=VALUE".to_string())?;
+    let index = index.parse::<usize>().map_err(|error| error.to_string())?;
+    if index >= deltasaver_core::format::FLAG_COUNT {
+        return Err(format!(
+            "flag index must be below {}",
+            deltasaver_core::format::FLAG_COUNT
+        ));
+    }
+    let value = value.parse::<i64>().map_err(|error| error.to_string())?;
+    Ok((index, value))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let mut config = match config::load_config() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("error: could not read config: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let locale = cli
+        .locale
+        .as_deref()
+        .or(config.locale.as_deref())
+        .map(Locale::from_tag)
+        .unwrap_or_else(Locale::current);
+
+    let result = match cli.command {
+        #[cfg(feature = "fixtures")]
+        Command::GenerateFixture {
+            directory,
+            chapter,
+            slot,
+            name,
+            room,
+            gold,
+            flags,
+        } => generate_fixture(
+            &directory,
+            slot,
+            deltasaver_core::fixtures::SyntheticSave {
+                chapter,
+                name,
+                room,
+                gold,
+                flags: flags.into_iter().collect(),
+                ..Default::default()
+            },
+        ),
+        command => open_store(cli.profile.as_deref())
+            .and_then(|store| {
+                config::remember_directories(
+                    &mut config,
+                    &store.profile().name,
+                    cli.directories.saves_dir,
+                    cli.directories.backups_dir,
+                )
+                .map_err(|error| format!("could not save config: {}", error))?;
+                Ok(config
+                    .apply_directories(&store)
+                    .with_skip_identical(config.backups.skip_identical))
+            })
+            .and_then(|store| run(&store, &config, &locale, command)),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn open_store(profile_name: Option<&str>) -> Result<SaveStore, String> {
+    let Some(profile_name) = profile_name else {
+        return SaveStore::at_default_location().ok_or_else(|| {
+            "could not find the DELTARUNE save directory on this platform".to_string()
+        });
+    };
+    let profile = profile::find_profile(profile_name)
+        .map_err(|error| format!("could not read profiles: {}", error))?
+        .ok_or_else(|| format!("no profile named {}", profile_name))?;
+    SaveStore::for_profile(profile)
+        .ok_or_else(|| "could not find a local data directory on this platform".to_string())
+}
+
+#[cfg(feature = "fixtures")]
+fn generate_fixture(
+    directory: &std::path::Path,
+    slot: Slot,
+    save: deltasaver_core::fixtures::SyntheticSave,
+) -> Result<(), String> {
+    let slot = slot
+        .checked_sub(1)
+        .ok_or_else(|| "slots are numbered from 1".to_string())?;
+    let path = save
+        .write_to(directory, slot)
+        .map_err(|error| error.to_string())?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+/// The passphrase from DELTASAVER_PASSPHRASE, or else asked for on the
+/// terminal without echoing it.
+fn passphrase(prompt: &str) -> Result<String, String> {
+    match std::env::var(encryption::PASSPHRASE_VARIABLE) {
+        Ok(passphrase) => Ok(passphrase),
+        Err(_) => rpassword::prompt_password(prompt)
+            .map_err(|error| format!("could not read the passphrase: {}", error)),
+    }
+}
+
+/// Asks a yes-or-no question on the terminal, defaulting to no.
+fn confirm(question: &str) -> Result<bool, String> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush().map_err(|error| error.to_string())?;
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .map_err(|error| error.to_string())?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn print_stats(name: &str, ours: &[ChapterStats], theirs: Option<&(String, Vec<ChapterStats>)>) {
+    let cells = |stats: Option<&ChapterStats>| match stats {
+        Some(stats) => format!(
+            "{:>9}  {:>5}  {:>7}",
+            stats
+                .play_time
+                .map(stats::format_play_time)
+                .unwrap_or_else(|| "-".to_string()),
+            stats.slots_used,
+            stats.backups
+        ),
+        None => format!("{:>9}  {:>5}  {:>7}", "-", "-", "-"),
+    };
+    let header = format!("{:>9}  {:>5}  {:>7}", "Play time", "Slots", "Backups");
+
+    match theirs {
+        Some((their_name, _)) => {
+            println!("         {:<25}  {}", name, their_name);
+            println!("Chapter  {}  {}", header, header);
+        }
+        None => println!("Chapter  {}", header),
+    }
+    let chapter_count = ours.len().max(theirs.map_or(0, |(_, theirs)| theirs.len()));
+    for index in 0..chapter_count {
+        let mut line = format!("{:>7}  {}", index + 1, cells(ours.get(index)));
+        if let Some((_, theirs)) = theirs {
+            line.push_str(&format!("  {}", cells(theirs.get(index))));
+        }
+        println!("{}", line);
+    }
+}
+
+fn run(
+    store: &SaveStore,
+    config: &Config,
+    locale: &Locale,
+    command: Command,
+) -> Result<(), String> {
+    let unlocked;
+    let store = if store.is_locked() {
+        unlocked = store
+            .unlock(&passphrase("Passphrase: ")?)
+            .map_err(|error| format!("could not unlock backups: {}", error))?;
+        &unlocked
+    } else {
+        store
+    };
+    if store.is_encrypted() && store.keeps_unencrypted_copies() {
+        eprintln!(
+            "warning: backing up is off: the mirror and git keep unencrypted copies of encrypted backups; remove them from config.toml"
+        );
+    }
+    store
+        .ensure_local_directory()
+        .map_err(|error| error.to_string())?;
+    let saves = store.load().map_err(|error| error.to_string())?;
+
+    match command {
+        Command::List { tag, search } => {
+            let mut game_saves = saves.game.values().collect::<Vec<_>>();
+            game_saves.sort_by_key(|save| (save.chapter, save.slot));
+            println!("Game saves:");
+            for save in game_saves {
+                match store.frozen_until(save.chapter, save.slot) {
+                    Ok(Some(until)) => println!(
+                        "  {}  frozen until {}",
+                        save.display_name(),
+                        freeze::format_local_time(until)
+                    ),
+                    _ => println!("  {}", save.display_name()),
+                }
+            }
+
+            let mut extra_saves = saves
+                .extra
+                .values()
+                .filter(|save| config.extra_files.contains(&save.chapter))
+                .collect::<Vec<_>>();
+            if !extra_saves.is_empty() {
+                extra_saves.sort_by_key(|save| (save.chapter, save.slot));
+                println!("Extra files (read-only):");
+                for save in extra_saves {
+                    println!(
+                        "  {}",
+                        store.profile().save_filename(save.chapter, save.slot)
+                    );
+                }
+            }
+
+            let branches =
+                Branches::load(store.local_directory()).map_err(|error| error.to_string())?;
+            let entries = store
+                .index()
+                .and_then(|index| index.entries())
+                .map_err(|error| error.to_string())?;
+            let query = search::Query::parse(search.as_deref().unwrap_or_default());
+            let mut local_saves = saves
+                .local
+                .iter()
+                .filter(|save| {
+                    let entry = save.id().and_then(|id| entries.get(id));
+                    tag.as_ref()
+                        .is_none_or(|tag| entry.is_some_and(|entry| entry.tags.contains(tag)))
+                        && query.matches(save, entry)
+                })
+                .collect::<Vec<_>>();
+            local_saves.sort_by_key(|save| (save.chapter, save.slot, save.id()));
+            println!("Local saves:");
+            for save in local_saves {
+                let branch = branches.branch_of(save);
+                let entry = save.id().and_then(|id| entries.get(id));
+                println!(
+                    "  {}  {}  {}  {}{}{}",
+                    save.share_code().unwrap_or_default(),
+                    save.display_name_with_label(entry.and_then(index::Entry::label)),
+                    save.modified
+                        .map(|time| locale.format_time(time))
+                        .unwrap_or_default(),
+                    save.id().unwrap_or_default(),
+                    if entry.is_some_and(|entry| entry.pinned) {
+                        "  pinned"
+                    } else {
+                        ""
+                    },
+                    if branch == branch::DEFAULT_BRANCH {
+                        String::new()
+                    } else {
+                        format!("  [{}]", branch)
+                    }
+                );
+                let companions = store.companions(&save.path).unwrap_or_default();
+                if !companions.is_empty() {
+                    println!("    with {}", companions.join(", "));
+                }
+                if let Some(entry) = entry.filter(|entry| !entry.tags.is_empty()) {
+                    let tags = entry.tags.iter().cloned().collect::<Vec<_>>();
+                    println!("    tagged {}", tags.join(", "));
+                }
+            }
+        }
+        Command::Note { id, note } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            store
+                .index()
+                .and_then(|index| index.set_note(&id, &note))
+                .map_err(|error| error.to_string())?;
+            if note.trim().is_empty() {
+                println!("Cleared the note on {}", save.display_name());
+            } else {
+                println!("Noted {}", save.display_name());
+            }
+        }
+        Command::Edit { id, name, gold } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            let edits = edit::Edits { name, gold };
+            if edits.is_empty() {
+                return Err("nothing to edit: pass --name or --gold".to_string());
+            }
+            let path = edit::edit_backup(store, save, &edits).map_err(|error| error.to_string())?;
+            println!("Saved the edits as {}", path.display());
+        }
+        Command::Flags { id, search } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            let contents = store
+                .read_backup(&save.path)
+                .map_err(|error| error.to_string())?;
+            let flags = format::flags(&contents)
+                .ok_or_else(|| format!("{} is not a full DELTARUNE save", id))?;
+            let query = search.unwrap_or_default();
+            for (flag, value) in flags.into_iter().enumerate() {
+                if !format::flag_matches(flag, value, &query) {
+                    continue;
+                }
+                match format::flag_meaning(flag) {
+                    Some(meaning) => println!("{:>4} = {}  ({})", flag, value, meaning),
+                    None => println!("{:>4} = {}", flag, value),
+                }
+            }
+        }
+        Command::Dump { id, hex } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            let contents = store
+                .read_backup(&save.path)
+                .map_err(|error| error.to_string())?;
+            let rows = if hex {
+                dump::hex(&contents)
+            } else {
+                dump::lines(&contents)
+            };
+            for row in rows {
+                println!("{}", row);
+            }
+        }
+        Command::Json { id, output } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            let contents = store
+                .read_backup(&save.path)
+                .map_err(|error| error.to_string())?;
+            let rooms = RoomNames::load_default().map_err(|error| error.to_string())?;
+            let json = json::save_json(&contents, save.chapter, &rooms)
+                .ok_or_else(|| format!("{} is not a full DELTARUNE save", id))?;
+            match output {
+                Some(output) => {
+                    std::fs::write(&output, json).map_err(|error| error.to_string())?;
+                    println!("Wrote {}", output.display());
+                }
+                None => println!("{}", json),
+            }
+        }
+        Command::Rename { id, name } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            store
+                .index()
+                .and_then(|index| index.set_name(&id, &name))
+                .map_err(|error| error.to_string())?;
+            if name.trim().is_empty() {
+                println!("Cleared the name of {}", save.display_name());
+            } else {
+                println!("Renamed {} to {}", save.display_name(), name.trim());
+            }
+        }
+        Command::Tag { id, tag } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            if tag.trim().is_empty() {
+                return Err("the tag is empty".to_string());
+            }
+            store
+                .index()
+                .and_then(|index| index.tag(&id, &tag))
+                .map_err(|error| error.to_string())?;
+            println!("Tagged {} {}", save.display_name(), tag.trim());
+        }
+        Command::Untag { id, tag } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            let removed = store
+                .index()
+                .and_then(|index| index.untag(&id, &tag))
+                .map_err(|error| error.to_string())?;
+            if !removed {
+                return Err(format!(
+                    "{} is not tagged {}",
+                    save.display_name(),
+                    tag.trim()
+                ));
+            }
+            println!("Untagged {}", save.display_name());
+        }
+        Command::Pin { id } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            store
+                .index()
+                .and_then(|index| index.set_pinned(&id, true))
+                .map_err(|error| error.to_string())?;
+            println!("Pinned {}", save.display_name());
+        }
+        Command::Unpin { id } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            store
+                .index()
+                .and_then(|index| index.set_pinned(&id, false))
+                .map_err(|error| error.to_string())?;
+            println!("Unpinned {}", save.display_name());
+        }
+        Command::Backup {
+            chapter,
+            slot,
+            note,
+        } => {
+            let slot = slot
+                .checked_sub(1)
+                .ok_or_else(|| "slots are numbered from 1".to_string())?;
+            let Some(game_save) = saves.game.get(&(chapter, slot)) else {
+                return Err(format!("Chapter {} Slot {} is empty", chapter, slot + 1));
+            };
+            if let Ok(Err(problem)) = store
+                .read_save(game_save)
+                .map(|contents| format::validate(&contents))
+            {
+                eprintln!(
+                    "warning: Chapter {} Slot {} looks corrupted: {}; backing it up anyway",
+                    chapter,
+                    slot + 1,
+                    problem
+                );
+            }
+            let path = store
+                .backup_with_note(chapter, slot, note.as_deref())
+                .map_err(|error| error.to_string())?;
+            if saves.local.iter().any(|save| save.path == path) {
+                println!(
+                    "Chapter {} Slot {} is unchanged since its latest backup, {}",
+                    chapter,
+                    slot + 1,
+                    path.display()
+                );
+                return Ok(());
+            }
+            println!("Backed up to {}", path.display());
+            let pruned = retention::prune(store, &config.retention)
+                .map_err(|error| format!("could not prune old backups: {}", error))?;
+            for save in pruned {
+                println!("Pruned {}", save.path.display());
+            }
+        }
+        Command::Restore { id, slot, chapter } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            let slot = match slot {
+                Some(slot) => slot
+                    .checked_sub(1)
+                    .filter(|slot| *slot < store.profile().slot_count)
+                    .ok_or_else(|| {
+                        format!(
+                            "slots are numbered from 1 to {}",
+                            store.profile().slot_count
+                        )
+                    })?,
+                None => save.slot,
+            };
+            let chapter = chapter.unwrap_or(save.chapter);
+            if !(1..=store.profile().chapter_count).contains(&chapter) {
+                return Err(format!(
+                    "chapters are numbered from 1 to {}",
+                    store.profile().chapter_count
+                ));
+            }
+            if let Some(warning) = store
+                .restore_warning(&save.path, chapter)
+                .map_err(|error| error.to_string())?
+            {
+                println!("Warning: {}", warning);
+                if !confirm("Restore anyway?")? {
+                    return Ok(());
+                }
+            }
+            let watched = conflict::restore_watched(store, save.path.clone(), chapter, slot)
+                .map_err(|error| error.to_string())?;
+            if (chapter, slot) == (save.chapter, save.slot) {
+                println!("Restored and verified {}", save.display_name());
+            } else {
+                println!(
+                    "Restored and verified {} into Chapter {} Slot {}",
+                    save.display_name(),
+                    chapter,
+                    slot + 1
+                );
+            }
+            if watched.pre_restore.is_some() {
+                println!("Backed up what was there first; `undo-restore` puts it back");
+            }
+        }
+        Command::UndoRestore => {
+            let last = undo::undo(store).map_err(|error| error.to_string())?;
+            println!(
+                "Put back what was in Chapter {} Slot {} before the restore",
+                last.chapter,
+                last.slot + 1
+            );
+        }
+        Command::Export { output, ids } => {
+            let selected = ids
+                .iter()
+                .map(|id| {
+                    saves
+                        .find_backup(id)
+                        .cloned()
+                        .ok_or_else(|| format!("no backup with id {}", id))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let output = if output.extension().is_none() {
+                output.with_extension(export::ARCHIVE_EXTENSION)
+            } else {
+                output
+            };
+            export::write_backup_archive(&output, store, &selected)
+                .map_err(|error| error.to_string())?;
+            println!(
+                "Exported {} backups to {}",
+                selected.len(),
+                output.display()
+            );
+        }
+        Command::Share { id } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            let contents = store
+                .read_backup(&save.path)
+                .map_err(|error| error.to_string())?;
+            let filename = store.profile().save_filename(save.chapter, save.slot);
+            let code = transfer::encode_share_code(&filename, &contents)
+                .map_err(|error| error.to_string())?;
+            println!("{}", code);
+        }
+        Command::ImportCode { code } => {
+            let decoded = transfer::decode_share_code(store.profile(), &code)
+                .map_err(|error| error.to_string())?;
+            let (chapter, slot) = decoded.origin.unwrap_or_default();
+            let path = store
+                .import(chapter, slot, &decoded.contents)
+                .map_err(|error| error.to_string())?;
+            println!("Imported {}", path.display());
+        }
+        Command::Import { archive } => {
+            let summary =
+                import::import_archive(&archive, store).map_err(|error| error.to_string())?;
+            for path in &summary.imported {
+                println!("Imported {}", path.display());
+            }
+            for (name, reason) in &summary.rejected {
+                println!("Skipped {}: {}", name, reason);
+            }
+            if summary.unlabeled > 0 {
+                println!(
+                    "Dropped the names, notes, and tags of {} backups, which are not kept while backups are encrypted",
+                    summary.unlabeled
+                );
+            }
+            println!(
+                "Imported {} backups, {} already backed up",
+                summary.imported.len(),
+                summary.duplicates
+            );
+        }
+        Command::Delete { id } => {
+            let save = saves
+                .find_backup(&id)
+                .ok_or_else(|| format!("no backup with id {}", id))?;
+            store
+                .delete(&save.path)
+                .map_err(|error| error.to_string())?;
+            println!("Moved {} to the trash", save.display_name());
+        }
+        Command::Trash => {
+            let trash = store.trash().map_err(|error| error.to_string())?;
+            if trash.is_empty() {
+                println!("The trash is empty");
+            }
+            for save in trash {
+                println!(
+                    "  {}  deleted {}  {}",
+                    save.display_name(),
+                    save.modified
+                        .map(|time| locale.format_time(time))
+                        .unwrap_or_default(),
+                    save.id().unwrap_or_default()
+                );
+            }
+        }
+        Command::Untrash { id } => {
+            let save = store
+                .trash()
+                .map_err(|error| error.to_string())?
+                .into_iter()
+                .find(|save| save.id() == Some(id.as_str()))
+                .ok_or_else(|| format!("no backup in the trash with id {}", id))?;
+            store
+                .restore_from_trash(&save.path)
+                .map_err(|error| error.to_string())?;
+            println!("Put back {}", save.display_name());
+        }
+        Command::EmptyTrash => {
+            let purged = store
+                .purge_trash_older_than(Duration::ZERO)
+                .map_err(|error| error.to_string())?;
+            println!("Deleted {} backups for good", purged.len());
+        }
+        Command::Prune { dry_run } => {
+            if config.retention.is_empty() {
+                println!("No retention rules are set in config.toml, so nothing is pruned");
+            } else if dry_run {
+                let entries = store
+                    .index()
+                    .and_then(|index| index.entries())
+                    .map_err(|error| error.to_string())?;
+                let is_pinned = |save: &SaveFile| {
+                    save.id()
+                        .and_then(|id| entries.get(id))
+                        .is_some_and(|entry| entry.pinned)
+                };
+                let candidates = retention::prune_candidates(
+                    &saves.local,
+                    &config.retention,
+                    is_pinned,
+                    SystemTime::now(),
+                );
+                println!("Would move {} backups to the trash:", candidates.len());
+                for save in candidates {
+                    println!(
+                        "  {}  {}",
+                        save.display_name(),
+                        save.id().unwrap_or_default()
+                    );
+                }
+            } else {
+                let pruned = retention::prune(store, &config.retention)
+                    .map_err(|error| error.to_string())?;
+                println!("Moved {} backups to the trash", pruned.len());
+            }
+        }
+        Command::Freeze {
+            chapter,
+            slot,
+            until,
+        } => {
+            let slot = slot
+                .checked_sub(1)
+                .ok_or_else(|| "slots are numbered from 1".to_string())?;
+            let until = freeze::parse_local_time(&until)
+                .ok_or_else(|| format!("{} is not a time like 2025-06-01 18:30", until))?;
+            store
+                .freeze(chapter, slot, until)
+                .map_err(|error| error.to_string())?;
+            println!(
+                "Froze Chapter {} Slot {} until {}",
+                chapter,
+                slot + 1,
+                freeze::format_local_time(until)
+            );
+        }
+        Command::Unfreeze { chapter, slot } => {
+            let slot = slot
+                .checked_sub(1)
+                .ok_or_else(|| "slots are numbered from 1".to_string())?;
+            let was_frozen = store
+                .unfreeze(chapter, slot)
+                .map_err(|error| error.to_string())?;
+            if !was_frozen {
+                return Err(format!(
+                    "Chapter {} Slot {} is not frozen",
+                    chapter,
+                    slot + 1
+                ));
+            }
+            println!("Unfroze Chapter {} Slot {}", chapter, slot + 1);
+        }
+        Command::Recover { chapter, slot } => {
+            let slot = slot
+                .checked_sub(1)
+                .ok_or_else(|| "slots are numbered from 1".to_string())?;
+            let findings = recovery::diagnose(store, &saves, chapter, slot);
+            if findings.is_empty() {
+                println!(
+                    "Found no trace of Chapter {} Slot {} anywhere",
+                    chapter,
+                    slot + 1
+                );
+            }
+            for finding in findings {
+                println!("* {}", finding);
+                if !finding.is_fixable() || !confirm("  Restore from this?")? {
+                    continue;
+                }
+                recovery::fix(store, chapter, slot, &finding).map_err(|error| error.to_string())?;
+                println!("Restored Chapter {} Slot {}", chapter, slot + 1);
+                return Ok(());
+            }
+        }
+        Command::Stats { against } => {
+            let ours = stats::chapter_stats(store, &saves);
+            let theirs = match against {
+                Some(name) => {
+                    let other = config.apply_directories(&open_store(Some(&name))?);
+                    let other_saves = other.load().map_err(|error| error.to_string())?;
+                    Some((
+                        other.profile().name.clone(),
+                        stats::chapter_stats(&other, &other_saves),
+                    ))
+                }
+                None => None,
+            };
+            print_stats(&store.profile().name, &ours, theirs.as_ref());
+        }
+        Command::Snapshot { name } => {
+            let taken = snapshot::take(store, &name).map_err(|error| error.to_string())?;
+            println!(
+                "Snapshotted everything to {}",
+                taken.id().unwrap_or(&taken.name)
+            );
+        }
+        Command::Snapshots => {
+            for taken in snapshot::list(store).map_err(|error| error.to_string())? {
+                println!(
+                    "  {}  {}  {}",
+                    locale.format_time(taken.created),
+                    taken.name,
+                    taken.id().unwrap_or_default()
+                );
+            }
+        }
+        Command::RestoreSnapshot { id } => {
+            let taken = snapshot::find(store, &id).map_err(|error| error.to_string())?;
+            let before = snapshot::restore(store, &taken).map_err(|error| error.to_string())?;
+            println!(
+                "Restored {}; what was there is in {}",
+                taken.name,
+                before.id().unwrap_or(&before.name)
+            );
+        }
+        Command::Branches => {
+            let branches =
+                Branches::load(store.local_directory()).map_err(|error| error.to_string())?;
+            for name in branches.names() {
+                let count = saves
+                    .local
+                    .iter()
+                    .filter(|save| branches.branch_of(save) == name)
+                    .count();
+                let marker = if name == branches.active() { "*" } else { " " };
+                println!("{} {}  {} backups", marker, name, count);
+            }
+        }
+        Command::SwitchBranch { name } => {
+            let mut branches =
+                Branches::load(store.local_directory()).map_err(|error| error.to_string())?;
+            branches.switch(&name);
+            branches
+                .save(store.local_directory())
+                .map_err(|error| error.to_string())?;
+            println!("New backups now go to {}", branches.active());
+        }
+        Command::RestoreBranch { name } => {
+            for tip in branch::restore_tip(store, &name).map_err(|error| error.to_string())? {
+                println!("Restored {}", tip.display_name());
+            }
+            println!("New backups now go to {}", name);
+        }
+        Command::GitPush => {
+            let directory = store.git_directory().ok_or_else(|| {
+                "backups are not committed to git; set `git` under [directories] in config.toml"
+                    .to_string()
+            })?;
+            let url = git::push(directory).map_err(|error| error.to_string())?;
+            println!("Pushed {} to {}", directory.display(), url);
+        }
+        Command::History => {
+            let history = store.history().map_err(|error| error.to_string())?;
+            if history.is_empty() {
+                println!("The backup directory has never needed migrating");
+            }
+            for entry in history {
+                println!(
+                    "{}: {} ({} files)",
+                    locale.format_time(entry.time()),
+                    entry.migration,
+                    entry.count
+                );
+            }
+        }
+        Command::Duplicates { collapse } => {
+            let entries = store
+                .index()
+                .and_then(|index| index.entries())
+                .map_err(|error| error.to_string())?;
+            let is_pinned = |save: &SaveFile| {
+                save.id()
+                    .and_then(|id| entries.get(id))
+                    .is_some_and(|entry| entry.pinned)
+            };
+            let sets = duplicates::find(&saves.local);
+            for set in &sets {
+                println!("{} copies of {}:", set.saves.len(), set.hash);
+                for save in &set.saves {
+                    println!(
+                        "  {}  {}",
+                        save.display_name(),
+                        save.id().unwrap_or_default()
+                    );
+                }
+            }
+            if collapse {
+                let collapsed = duplicates::collapse(store, &sets, is_pinned)
+                    .map_err(|error| error.to_string())?;
+                println!(
+                    "Moved {} duplicate backups to the trash; emptying it frees {}",
+                    collapsed.trashed.len(),
+                    locale.format_size(collapsed.reclaimable)
+                );
+            } else if sets.is_empty() {
+                println!("No two backups are the same");
+            }
+        }
+        Command::Check => {
+            let damaged = store.check_integrity().map_err(|error| error.to_string())?;
+            for damaged in &damaged {
+                println!(
+                    "{}  {}: {}",
+                    damaged.save.display_name(),
+                    damaged.save.id().unwrap_or_default(),
+                    damaged.problem
+                );
+            }
+            if !damaged.is_empty() {
+                return Err(format!("{} backups are damaged", damaged.len()));
+            }
+            println!("Every backup is intact");
+        }
+        Command::MigrateLegacy => {
+            let migration = store
+                .migrate_legacy_backups()
+                .map_err(|error| error.to_string())?;
+            for path in &migration.migrated {
+                println!("Migrated {}", path.display());
+            }
+            for (filename, reason) in &migration.skipped {
+                println!("Skipped {}: {}", filename, reason);
+            }
+            println!(
+                "Migrated {} legacy backups, {} left as they were",
+                migration.migrated.len(),
+                migration.skipped.len()
+            );
+        }
+        Command::Encrypt => {
+            if store.is_encrypted() {
+                return Err("the backups are already encrypted".to_string());
+            }
+            let new_passphrase = passphrase("New passphrase: ")?;
+            if new_passphrase.is_empty() {
+                return Err("the passphrase cannot be empty".to_string());
+            }
+            if std::env::var(encryption::PASSPHRASE_VARIABLE).is_err()
+                && passphrase("Repeat it: ")? != new_passphrase
+            {
+                return Err("the passphrases did not match".to_string());
+            }
+            store
+                .encrypt(&new_passphrase)
+                .map_err(|error| error.to_string())?;
+            println!(
+                "Encrypted the contents of the backups; they cannot be recovered without the passphrase"
+            );
+        }
+        Command::Paths => {
+            match profile::builtin_location(&store.profile().name) {
+                Some(location) => {
+                    println!("Checked, in order:");
+                    for candidate in location.candidates() {
+                        let state = if candidate.is_dir() {
+                            "found"
+                        } else {
+                            "missing"
+                        };
+                        println!("  {:<7}  {}", state, candidate.display());
+                    }
+                }
+                None => println!("Save directory set in profiles.json"),
+            }
+            println!("Using {}", store.game_directory().display());
+            println!("Backups in {}", store.local_directory().display());
+            if let Some(directory) = store.mirror_directory() {
+                println!("Plain copies in {}", directory.display());
+            }
+            if let Some(directory) = store.git_directory() {
+                println!("Git history in {}", directory.display());
+            }
+            if let Some(directory) = portable::portable_directory() {
+                println!("Portable mode: settings in {}", directory.display());
+            }
+            if let Some(path) = config::config_path()
+                && config.directories_for(&store.profile().name).is_some()
+            {
+                println!("Overridden in {}", path.display());
+            }
+        }
+        #[cfg(feature = "fixtures")]
+        Command::GenerateFixture { .. } => unreachable!("handled above"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,310 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Named lines of backups, one per playthrough, e.g. "pacifist" and "weird
+//! route", so each playthrough's backups stay together and either can be
+//! picked back up where it was left.
+//!
+//! New backups join the active branch. Branches are kept in `branches.json`
+//! in the backup directory, mapping each backup's ID to its branch:
+//!
+//! ```json
+//! { "active": "pacifist", "backups": { "01JZ3K2N8Q4V6X0M5B7C9D1E2F": "pacifist" } }
+//! ```
+//!
+//! Backups not listed, such as every backup taken before branches existed,
+//! are on [`DEFAULT_BRANCH`].
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::conflict;
+use crate::profile::{backup_created_secs, backup_id};
+use crate::save::SaveFile;
+use crate::store::SaveStore;
+
+const BRANCHES_FILENAME: &str = "branches.json";
+
+/// The branch backups are on unless another is active.
+pub const DEFAULT_BRANCH: &str = "main";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Branches {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active: Option<String>,
+    /// Branches by backup ID.
+    #[serde(default)]
+    backups: BTreeMap<String, String>,
+    /// Branches started without any backups yet.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    empty: BTreeSet<String>,
+    /// The branch last switched away from, whose saves may still be in the
+    /// game's slots until [`restore_tip`] replaces them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    left: Option<String>,
+}
+
+impl Branches {
+    /// Reads the branches in `local_directory`, where everything is on
+    /// [`DEFAULT_BRANCH`] if the file does not exist yet.
+    pub fn load(local_directory: &Path) -> io::Result<Self> {
+        match fs::read(local_directory.join(BRANCHES_FILENAME)) {
+            Ok(contents) => serde_json::from_slice(&contents)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn save(&self, local_directory: &Path) -> io::Result<()> {
+        let contents = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        fs::write(local_directory.join(BRANCHES_FILENAME), contents)
+    }
+
+    /// The branch new backups join.
+    pub fn active(&self) -> &str {
+        self.active.as_deref().unwrap_or(DEFAULT_BRANCH)
+    }
+
+    /// Makes `name` the active branch, starting it if it is new.
+    pub fn switch(&mut self, name: &str) {
+        let name = match name.trim() {
+            "" => DEFAULT_BRANCH,
+            name => name,
+        };
+        if name != self.active() {
+            self.left = Some(self.active().to_string());
+            self.set_active(name);
+        }
+    }
+
+    fn set_active(&mut self, name: &str) {
+        if name == DEFAULT_BRANCH {
+            self.active = None;
+            return;
+        }
+        if !self.backups.values().any(|branch| branch == name) {
+            self.empty.insert(name.to_string());
+        }
+        self.active = Some(name.to_string());
+    }
+
+    /// Puts the backup named `filename` on the active branch.
+    pub fn add(&mut self, filename: &str) {
+        let Some(id) = backup_id(filename) else {
+            return;
+        };
+        if let Some(active) = self.active.clone() {
+            self.empty.remove(&active);
+            self.backups.insert(id.to_string(), active);
+        }
+    }
+
+    /// Moves the backup with the ID `old_id` to `new_id`, on the same branch.
+    pub fn rename_backup(&mut self, old_id: &str, new_id: &str) {
+        if let Some(branch) = self.backups.remove(old_id) {
+            self.backups.insert(new_id.to_string(), branch);
+        }
+    }
+
+    /// The branch `save` is on.
+    pub fn branch_of(&self, save: &SaveFile) -> &str {
+        save.id()
+            .and_then(backup_id)
+            .and_then(|id| self.backups.get(id))
+            .map_or(DEFAULT_BRANCH, String::as_str)
+    }
+
+    /// Every branch, [`DEFAULT_BRANCH`] first, then by name.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![DEFAULT_BRANCH.to_string()];
+        let others = self
+            .backups
+            .values()
+            .chain(&self.empty)
+            .chain(&self.active)
+            .filter(|name| *name != DEFAULT_BRANCH)
+            .collect::<BTreeSet<_>>();
+        names.extend(others.into_iter().cloned());
+        names
+    }
+}
+
+/// The newest backup of each slot on the branch `name`, among `saves`, by
+/// chapter and slot. Extra files are left out, as they cannot be restored.
+pub fn tips(
+    store: &SaveStore,
+    branches: &Branches,
+    name: &str,
+    saves: &[SaveFile],
+) -> Vec<SaveFile> {
+    let mut tips = BTreeMap::<_, &SaveFile>::new();
+    let created = |save: &SaveFile| {
+        let filename = save.id().unwrap_or_default();
+        (
+            backup_created_secs(filename),
+            backup_id(filename).map(str::to_string),
+        )
+    };
+    for save in saves {
+        if branches.branch_of(save) != name || store.profile().is_extra_slot(save.slot) {
+            continue;
+        }
+        let tip = tips.entry((save.chapter, save.slot)).or_insert(save);
+        if created(save) > created(tip) {
+            *tip = save;
+        }
+    }
+    tips.into_values().cloned().collect()
+}
+
+/// Picks the branch `name` back up: restores the newest backup of every slot
+/// on it, returning them, and makes it the active branch. Each slot is backed
+/// up first onto the branch being left, which is the active one, or the one
+/// last switched away from if `name` is already active.
+pub fn restore_tip(store: &SaveStore, name: &str) -> io::Result<Vec<SaveFile>> {
+    let saves = store
+        .load()
+        .map_err(|error| io::Error::other(error.to_string()))?;
+    let mut branches = Branches::load(store.local_directory())?;
+    let tips = tips(store, &branches, name, &saves.local);
+    if tips.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("the branch {} has no backups", name),
+        ));
+    }
+
+    let leaving = match (branches.active(), &branches.left) {
+        (active, Some(left)) if active == name => left.clone(),
+        (active, _) => active.to_string(),
+    };
+    branches.set_active(&leaving);
+    branches.save(store.local_directory())?;
+    let restored = tips.iter().try_for_each(|tip| {
+        conflict::restore_watched(store, tip.path.clone(), tip.chapter, tip.slot).map(|_| ())
+    });
+
+    // Backing up added to the branches on disk, so they are read again. Until
+    // every slot is restored, the branch left is remembered so a retry backs
+    // up onto it as well.
+    let mut branches = Branches::load(store.local_directory())?;
+    branches.switch(name);
+    if restored.is_ok() {
+        branches.left = None;
+    }
+    branches.save(store.local_directory())?;
+    restored.map(|()| tips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RetentionPolicy;
+    use crate::fixtures::{SyntheticSave, store_in};
+    use crate::retention;
+
+    #[test]
+    fn backups_renamed_on_collision_join_the_branch_by_their_own_name() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let id = ulid::Ulid::from_parts(1_000, 0).to_string();
+        let contents = SyntheticSave::default().to_bytes();
+        let first = store.import_with_id(1, 0, &contents, &id).unwrap();
+
+        let mut branches = Branches::load(store.local_directory()).unwrap();
+        branches.switch("weird route");
+        branches.save(store.local_directory()).unwrap();
+        let second = store.import_with_id(1, 0, &contents, &id).unwrap();
+        assert_ne!(first, second);
+
+        let branches = Branches::load(store.local_directory()).unwrap();
+        let saves = store.load().unwrap().local;
+        let branch_of = |path: &Path| {
+            let save = saves.iter().find(|save| save.path == path).unwrap();
+            branches.branch_of(save).to_string()
+        };
+        assert_eq!(branch_of(&first), DEFAULT_BRANCH);
+        assert_eq!(branch_of(&second), "weird route");
+    }
+
+    #[test]
+    fn each_playthrough_keeps_its_own_backups() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let game_path = store.game_directory().join("filech1_0");
+
+        let pacifist = SyntheticSave::default().to_bytes();
+        fs::write(&game_path, &pacifist).unwrap();
+        store.backup(1, 0).unwrap();
+
+        let mut branches = Branches::load(store.local_directory()).unwrap();
+        branches.switch("weird route");
+        branches.save(store.local_directory()).unwrap();
+        assert_eq!(branches.names(), vec!["main", "weird route"]);
+        let weird = SyntheticSave {
+            gold: 1234,
+            ..Default::default()
+        }
+        .to_bytes();
+        fs::write(&game_path, &weird).unwrap();
+        store.backup(1, 0).unwrap();
+
+        let branches = Branches::load(store.local_directory()).unwrap();
+        let saves = store.load().unwrap().local;
+        let tip = &tips(&store, &branches, "weird route", &saves)[0];
+        assert_eq!(store.read_backup(&tip.path).unwrap(), weird);
+
+        let keep_one = RetentionPolicy {
+            keep_last: Some(1),
+            ..Default::default()
+        };
+        assert!(retention::prune(&store, &keep_one).unwrap().is_empty());
+
+        restore_tip(&store, DEFAULT_BRANCH).unwrap();
+        assert_eq!(fs::read(&game_path).unwrap(), pacifist);
+        assert_eq!(
+            Branches::load(store.local_directory()).unwrap().active(),
+            DEFAULT_BRANCH
+        );
+        assert!(restore_tip(&store, "no such branch").is_err());
+    }
+
+    #[test]
+    fn switching_first_backs_up_onto_the_branch_left() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let game_path = store.game_directory().join("filech1_0");
+        let pacifist = SyntheticSave::default().to_bytes();
+        fs::write(&game_path, &pacifist).unwrap();
+        store.backup(1, 0).unwrap();
+
+        let mut branches = Branches::load(store.local_directory()).unwrap();
+        branches.switch("weird route");
+        branches.save(store.local_directory()).unwrap();
+        let weird = SyntheticSave {
+            gold: 1234,
+            ..Default::default()
+        }
+        .to_bytes();
+        // Played on without backing up, then switched back before restoring.
+        fs::write(&game_path, &weird).unwrap();
+        let mut branches = Branches::load(store.local_directory()).unwrap();
+        branches.switch(DEFAULT_BRANCH);
+        branches.save(store.local_directory()).unwrap();
+
+        restore_tip(&store, DEFAULT_BRANCH).unwrap();
+        assert_eq!(fs::read(&game_path).unwrap(), pacifist);
+        let branches = Branches::load(store.local_directory()).unwrap();
+        assert_eq!(branches.active(), DEFAULT_BRANCH);
+        let saves = store.load().unwrap().local;
+        let tip = |name| tips(&store, &branches, name, &saves).remove(0);
+        assert_eq!(store.read_backup(&tip("weird route").path).unwrap(), weird);
+        assert_eq!(
+            store.read_backup(&tip(DEFAULT_BRANCH).path).unwrap(),
+            pacifist
+        );
+    }
+}
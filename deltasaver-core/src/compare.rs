@@ -0,0 +1,128 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Comparing two saves field by field, to see what happened between them:
+//! the fields of their [`SaveSummary`], the story's progress, and every flag
+//! that changed.
+
+use crate::format;
+use crate::stats::format_play_time;
+use crate::summary::SaveSummary;
+
+/// A field that differs between two saves, written as it would be shown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// E.g. `LV` or `flag 915`.
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// What changed from the save `before` to the save `after`, or `None` if
+/// either is not a DELTARUNE save.
+pub fn compare(before: &[u8], after: &[u8]) -> Option<Vec<Change>> {
+    let before = Fields::read(before)?;
+    let after = Fields::read(after)?;
+    let mut changes = before
+        .named
+        .into_iter()
+        .zip(after.named)
+        .filter(|((_, before), (_, after))| before != after)
+        .map(|((field, before), (_, after))| Change {
+            field: field.to_string(),
+            before,
+            after,
+        })
+        .collect::<Vec<_>>();
+    changes.extend(
+        before
+            .flags
+            .into_iter()
+            .zip(after.flags)
+            .enumerate()
+            .filter(|(_, (before, after))| before != after)
+            .map(|(flag, (before, after))| Change {
+                field: format!("flag {}", flag),
+                before: before.to_string(),
+                after: after.to_string(),
+            }),
+    );
+    Some(changes)
+}
+
+/// The values compared, in the order changes are listed.
+struct Fields<'a> {
+    named: Vec<(&'static str, String)>,
+    flags: Vec<&'a str>,
+}
+
+impl<'a> Fields<'a> {
+    fn read(contents: &'a [u8]) -> Option<Fields<'a>> {
+        let summary = SaveSummary::parse(contents)?;
+        let lines = std::str::from_utf8(contents)
+            .ok()?
+            .lines()
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        let party = summary
+            .party
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+        let named = vec![
+            ("Name", summary.name),
+            ("Room", summary.room.to_string()),
+            ("Play time", format_play_time(summary.play_time)),
+            ("Plot", lines.get(format::PLOT_LINE)?.to_string()),
+            ("LV", summary.lv.to_string()),
+            ("HP", format!("{}/{}", summary.hp, summary.max_hp)),
+            ("D$", summary.gold.to_string()),
+            ("Route", summary.route.to_string()),
+            ("Party", party),
+        ];
+        Some(Fields {
+            named,
+            flags: format::flags(contents)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SyntheticSave;
+
+    #[test]
+    fn lists_changed_fields_then_flags() {
+        let before = SyntheticSave {
+            gold: 10,
+            flags: [(100, 1)].into(),
+            ..Default::default()
+        };
+        let after = SyntheticSave {
+            gold: 250,
+            flags: [(100, 1), (format::WEIRD_ROUTE_FLAG, 3)].into(),
+            ..before.clone()
+        };
+
+        let changes = compare(&before.to_bytes(), &after.to_bytes()).unwrap();
+        let change = |field: &str, before: &str, after: &str| Change {
+            field: field.to_string(),
+            before: before.to_string(),
+            after: after.to_string(),
+        };
+        assert_eq!(
+            changes,
+            vec![
+                change("D$", "10", "250"),
+                change("Route", "Normal", "Weird"),
+                change("flag 915", "0", "3"),
+            ]
+        );
+        assert_eq!(
+            compare(&before.to_bytes(), &before.to_bytes()),
+            Some(vec![])
+        );
+        assert_eq!(compare(b"KRIS\n", &before.to_bytes()), None);
+    }
+}
@@ -0,0 +1,500 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Choices the user has made that outlive a single run, kept in
+//! `config.toml` in the DELTASAVER config directory next to `profiles.json`.
+//! Every setting is optional:
+//!
+//! ```toml
+//! locale = "en-GB"
+//! theme = "Dracula"
+//! extra_files = [1, 2]
+//! backup_order = "by_note"
+//!
+//! [backups]
+//! auto_backup = false
+//! snapshot_on_game_update = true
+//! skip_identical = true
+//! pause_on_battery = false
+//!
+//! [retention]
+//! keep_last = 5
+//! daily_after_days = 7
+//! max_age_days = 90
+//! max_per_slot = 20
+//! trash_days = 30
+//!
+//! [schedule]
+//! interval_minutes = 15
+//! disabled_slots = { DELTARUNE = ["filech1_2"] }
+//!
+//! [directories.DELTARUNE]
+//! saves = 'D:\Games\DELTARUNE\saves'
+//! backups = 'D:\Backups\DELTASAVER'
+//! mirror = 'D:\Backups\DELTARUNE saves'
+//! git = 'D:\Backups\DELTARUNE history'
+//!
+//! [hooks.DELTARUNE]
+//! copy_to = 'E:\Offsite'
+//! command = 'rclone copy "%DELTASAVER_ARCHIVE%" remote:deltarune'
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::portable;
+use crate::save::Chapter;
+use crate::store::SaveStore;
+
+const CONFIG_FILENAME: &str = "config.toml";
+
+/// Where the directory overrides were kept before `config.toml`, as
+/// `{"directories": {"DELTARUNE": {"saves": "…", "backups": "…"}}}`.
+const LEGACY_SETTINGS_FILENAME: &str = "settings.json";
+
+#[derive(Deserialize)]
+struct LegacySettings {
+    #[serde(default)]
+    directories: BTreeMap<String, DirectoryOverride>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Formats dates and sizes for this locale tag instead of the system one,
+    /// as `--locale` does.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// The name of one of the app's built-in color themes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Chapters whose extra files, kept past the last slot, the app shows.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub extra_files: BTreeSet<Chapter>,
+    /// How the backups of each slot are listed.
+    pub backup_order: BackupOrder,
+    /// Offer to edit a backup's name and dark dollars in its details, see
+    /// [`crate::edit`].
+    pub save_editor: bool,
+    pub backups: BackupPolicy,
+    pub retention: RetentionPolicy,
+    pub schedule: SchedulePolicy,
+    /// Directory overrides by profile name.
+    pub directories: BTreeMap<String, DirectoryOverride>,
+    /// What to do with each snapshot once it is taken, by profile name.
+    pub hooks: BTreeMap<String, ExportHook>,
+}
+
+/// How the backups of each slot are listed, see [`crate::search::sort`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+    /// Alphabetically by name or note, with backups with neither last.
+    ByNote,
+    /// Largest first.
+    BySize,
+}
+
+impl BackupOrder {
+    pub const ALL: [BackupOrder; 4] = [
+        BackupOrder::NewestFirst,
+        BackupOrder::OldestFirst,
+        BackupOrder::ByNote,
+        BackupOrder::BySize,
+    ];
+}
+
+impl fmt::Display for BackupOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BackupOrder::NewestFirst => "Newest first",
+            BackupOrder::OldestFirst => "Oldest first",
+            BackupOrder::ByNote => "By note",
+            BackupOrder::BySize => "By size",
+        })
+    }
+}
+
+/// When DELTASAVER backs things up on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupPolicy {
+    /// Back up every slot the game writes while the app is open, as the
+    /// daemon does.
+    pub auto_backup: bool,
+    /// Snapshot every slot when Steam installs a game update.
+    pub snapshot_on_game_update: bool,
+    /// Don't back up a slot that has not changed since its latest backup.
+    pub skip_identical: bool,
+    /// Hold off on periodic checks while on battery, as `--low-power` does.
+    pub pause_on_battery: bool,
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self {
+            auto_backup: false,
+            snapshot_on_game_update: true,
+            skip_identical: true,
+            pause_on_battery: false,
+        }
+    }
+}
+
+/// Which old backups to delete after each backup, per slot. Every rule is
+/// optional, and with none set nothing is ever deleted; see
+/// [`crate::retention`] for how they combine.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Always keep this many of each slot's newest backups.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_last: Option<usize>,
+    /// Keep only the newest backup of each day once backups are this many
+    /// days old.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_after_days: Option<u64>,
+    /// Delete backups once they are this many days old.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u64>,
+    /// Never keep more than this many backups of a slot on each branch,
+    /// deleting the oldest unpinned ones past it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_per_slot: Option<usize>,
+    /// Days deleted backups stay in the trash before they are gone for good,
+    /// [`RetentionPolicy::DEFAULT_TRASH_DAYS`] if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trash_days: Option<u64>,
+}
+
+impl RetentionPolicy {
+    pub const DEFAULT_TRASH_DAYS: u64 = 30;
+
+    /// Whether no rule deletes any backup. The trash is emptied either way.
+    pub fn is_empty(&self) -> bool {
+        self.keep_last.is_none()
+            && self.daily_after_days.is_none()
+            && self.max_age_days.is_none()
+            && self.max_per_slot.is_none()
+    }
+
+    /// How long deleted backups stay in the trash.
+    pub fn trash_duration(&self) -> Duration {
+        Duration::from_secs(self.trash_days.unwrap_or(Self::DEFAULT_TRASH_DAYS) * 24 * 60 * 60)
+    }
+}
+
+/// How often to back up every occupied slot while the app is open.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulePolicy {
+    /// Minutes between scheduled backups, or 0 for none.
+    pub interval_minutes: u64,
+    /// Save filenames of the slots left out, by profile name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub disabled_slots: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl SchedulePolicy {
+    pub fn interval(&self) -> Option<Duration> {
+        (self.interval_minutes > 0).then(|| Duration::from_secs(self.interval_minutes * 60))
+    }
+
+    /// Whether the slot saved as `save_filename` by the profile named
+    /// `profile_name` is backed up on schedule.
+    pub fn is_enabled_for(&self, profile_name: &str, save_filename: &str) -> bool {
+        self.disabled_slots
+            .get(profile_name)
+            .is_none_or(|disabled| !disabled.contains(save_filename))
+    }
+
+    pub fn set_enabled_for(&mut self, profile_name: &str, save_filename: &str, enabled: bool) {
+        let disabled = self
+            .disabled_slots
+            .entry(profile_name.to_string())
+            .or_default();
+        if enabled {
+            disabled.remove(save_filename);
+        } else {
+            disabled.insert(save_filename.to_string());
+        }
+        if disabled.is_empty() {
+            self.disabled_slots.remove(profile_name);
+        }
+    }
+}
+
+/// Directories to use for a profile instead of the ones we would find, e.g.
+/// for a portable install or another Windows user's saves.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectoryOverride {
+    /// The game's save directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saves: Option<PathBuf>,
+    /// Where backups are kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backups: Option<PathBuf>,
+    /// Where to keep a plain, read-only copy of every backup as well, see
+    /// [`SaveStore::with_mirror_directory`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<PathBuf>,
+    /// A git repository to commit every backup to as well, see
+    /// [`SaveStore::with_git_directory`]. Like the mirror, it is a side copy
+    /// in the clear, not another place to keep backups in: `backups` is
+    /// still where they are kept, and neither can be set while they are
+    /// encrypted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<PathBuf>,
+}
+
+/// Where to send an archive of each snapshot, for offsite copies. Both are
+/// optional and run in this order.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportHook {
+    /// A directory to copy the archive into.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copy_to: Option<PathBuf>,
+    /// A shell command to run, with the archive's path in
+    /// `DELTASAVER_ARCHIVE`, e.g. to upload it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+impl DirectoryOverride {
+    fn is_empty(&self) -> bool {
+        self.saves.is_none()
+            && self.backups.is_none()
+            && self.mirror.is_none()
+            && self.git.is_none()
+    }
+}
+
+impl Config {
+    /// Reads the config at `path`. A missing file means nothing has been set
+    /// yet.
+    /// Reads the config at `path`. If there is none yet but there is a
+    /// `settings.json` beside it from an older DELTASAVER, its directory
+    /// overrides are carried over into a new config at `path`, leaving
+    /// `settings.json` as it was.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Self::migrate(path),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn migrate(path: &Path) -> io::Result<Self> {
+        let legacy_path = path.with_file_name(LEGACY_SETTINGS_FILENAME);
+        let contents = match fs::read(&legacy_path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+        let legacy: LegacySettings = serde_json::from_slice(&contents)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let config = Self {
+            directories: legacy.directories,
+            ..Default::default()
+        };
+        config.save(path)?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+
+    /// The directory overrides for the profile named `profile_name`, ignoring
+    /// case as [`crate::profile::find_profile`] does.
+    pub fn directories_for(&self, profile_name: &str) -> Option<&DirectoryOverride> {
+        self.directories
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(profile_name))
+            .map(|(_, directories)| directories)
+    }
+
+    /// The export hook for the profile named `profile_name`, ignoring case.
+    pub fn hook_for(&self, profile_name: &str) -> Option<&ExportHook> {
+        self.hooks
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(profile_name))
+            .map(|(_, hook)| hook)
+    }
+
+    /// Updates the overrides for the profile named `profile_name`, dropping
+    /// them once nothing is overridden.
+    pub fn set_directories(
+        &mut self,
+        profile_name: &str,
+        update: impl FnOnce(&mut DirectoryOverride),
+    ) {
+        let key = self
+            .directories
+            .keys()
+            .find(|name| name.eq_ignore_ascii_case(profile_name))
+            .cloned()
+            .unwrap_or_else(|| profile_name.to_string());
+        let directories = self.directories.entry(key.clone()).or_default();
+        update(directories);
+        if directories.is_empty() {
+            self.directories.remove(&key);
+        }
+    }
+
+    /// `store` with the directory overrides for its profile applied.
+    pub fn apply_directories(&self, store: &SaveStore) -> SaveStore {
+        match self.directories_for(&store.profile().name) {
+            Some(directories) => store.with_directory_override(directories),
+            None => store.clone(),
+        }
+    }
+}
+
+/// Where `config.toml` lives, or `None` if this platform has no config
+/// directory.
+pub fn config_path() -> Option<PathBuf> {
+    Some(portable::config_directory()?.join(CONFIG_FILENAME))
+}
+
+/// The user's config, or the defaults if this platform has no config
+/// directory.
+pub fn load_config() -> io::Result<Config> {
+    match config_path() {
+        Some(path) => Config::load(&path),
+        None => Ok(Config::default()),
+    }
+}
+
+/// Changes the user's config with `update` and saves it.
+pub fn update_config(update: impl FnOnce(&mut Config)) -> io::Result<()> {
+    let Some(path) = config_path() else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "this platform has no config directory",
+        ));
+    };
+    let mut config = Config::load(&path)?;
+    update(&mut config);
+    config.save(&path)
+}
+
+/// The `--saves-dir` and `--backups-dir` options of every frontend, which
+/// [`remember_directories`] saves.
+#[cfg(feature = "clap")]
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct DirectoryArgs {
+    /// Use this directory for the game's saves from now on. An empty path
+    /// goes back to finding it automatically.
+    #[arg(long, global = true, value_name = "DIR", value_parser = parse_directory)]
+    pub saves_dir: Option<PathBuf>,
+
+    /// Keep backups in this directory from now on. An empty path goes back to
+    /// the default.
+    #[arg(long, global = true, value_name = "DIR", value_parser = parse_directory)]
+    pub backups_dir: Option<PathBuf>,
+}
+
+/// Like clap's own path parser, but allowing an empty path to clear a saved
+/// directory.
+#[cfg(feature = "clap")]
+fn parse_directory(path: &str) -> Result<PathBuf, std::convert::Infallible> {
+    Ok(PathBuf::from(path))
+}
+
+/// Remembers directory overrides for the profile named `profile_name` given
+/// on the command line, saving them to `config.toml`. An empty path clears an
+/// override.
+pub fn remember_directories(
+    config: &mut Config,
+    profile_name: &str,
+    saves: Option<PathBuf>,
+    backups: Option<PathBuf>,
+) -> io::Result<()> {
+    if saves.is_none() && backups.is_none() {
+        return Ok(());
+    }
+    let non_empty = |path: PathBuf| (!path.as_os_str().is_empty()).then_some(path);
+    config.set_directories(profile_name, |directories| {
+        if let Some(saves) = saves {
+            directories.saves = non_empty(saves);
+        }
+        if let Some(backups) = backups {
+            directories.backups = non_empty(backups);
+        }
+    });
+    match config_path() {
+        Some(path) => config.save(&path),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directories_carry_over_from_settings_json() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join(CONFIG_FILENAME);
+        fs::write(
+            root.path().join(LEGACY_SETTINGS_FILENAME),
+            r#"{"directories": {"DELTARUNE": {"saves": "/games/DELTARUNE"}}}"#,
+        )
+        .unwrap();
+
+        let config = Config::load(&path).unwrap();
+        assert_eq!(
+            config.directories_for("DELTARUNE").unwrap().saves,
+            Some(PathBuf::from("/games/DELTARUNE"))
+        );
+        assert_eq!(Config::load(&path).unwrap(), config);
+        assert!(root.path().join(LEGACY_SETTINGS_FILENAME).exists());
+    }
+
+    #[test]
+    fn overrides_round_trip_and_clear() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("DELTASAVER").join(CONFIG_FILENAME);
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+        let mut config = Config {
+            locale: Some("en-GB".to_string()),
+            ..Default::default()
+        };
+        config.set_directories("DELTARUNE", |directories| {
+            directories.saves = Some(PathBuf::from("/games/DELTARUNE"));
+        });
+        config.save(&path).unwrap();
+        let mut loaded = Config::load(&path).unwrap();
+        assert_eq!(loaded, config);
+        assert_eq!(
+            loaded.directories_for("deltarune").unwrap().saves,
+            Some(PathBuf::from("/games/DELTARUNE"))
+        );
+
+        loaded.set_directories("DELTARUNE", |directories| directories.saves = None);
+        assert!(loaded.directories.is_empty());
+    }
+
+    #[test]
+    fn partial_configs_keep_the_defaults() {
+        let config: Config = toml::from_str("[backups]\npause_on_battery = true\n").unwrap();
+        assert!(config.backups.pause_on_battery);
+        assert!(config.backups.snapshot_on_game_update);
+        assert_eq!(config.theme, None);
+        assert!(toml::from_str::<Config>("locale = 3").is_err());
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Catching a cloud sync client, usually Steam Cloud, putting its own copy of
+//! a slot back right after we restored a backup into it, and letting the user
+//! choose which version wins.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::save::{Chapter, Slot, hash_contents};
+use crate::store::SaveStore;
+use crate::undo;
+
+/// How long after a restore a change to the slot counts as a conflict rather
+/// than the game saving.
+pub const WATCH_WINDOW: Duration = Duration::from_secs(2 * 60);
+
+/// A restore we are keeping an eye on.
+#[derive(Debug, Clone)]
+pub struct WatchedRestore {
+    pub chapter: Chapter,
+    pub slot: Slot,
+    /// The backup that was restored.
+    pub restored: PathBuf,
+    restored_hash: String,
+    /// The backup of what was in the slot before, if it had anything.
+    pub pre_restore: Option<PathBuf>,
+    restored_at: SystemTime,
+}
+
+impl WatchedRestore {
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        now.duration_since(self.restored_at)
+            .is_ok_and(|elapsed| elapsed > WATCH_WINDOW)
+    }
+}
+
+/// The slot changed to something else right after a restore.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub chapter: Chapter,
+    pub slot: Slot,
+    /// A backup of what replaced the restored save, presumably the cloud's.
+    pub cloud: PathBuf,
+    pub restored: PathBuf,
+    pub pre_restore: Option<PathBuf>,
+}
+
+/// Which version of a slot to keep after a [`Conflict`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    Cloud,
+    Restored,
+    PreRestore,
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Choice::Cloud => "the cloud version",
+            Choice::Restored => "the restored backup",
+            Choice::PreRestore => "the save from before the restore",
+        })
+    }
+}
+
+/// Backs up what is in `chapter`'s `slot`, if anything, then restores
+/// `backup_path` into it, returning the restore to watch. The restore is
+/// remembered so it can be undone with [`crate::undo::undo`].
+pub fn restore_watched(
+    store: &SaveStore,
+    backup_path: PathBuf,
+    chapter: Chapter,
+    slot: Slot,
+) -> io::Result<WatchedRestore> {
+    let game_path = store
+        .game_directory()
+        .join(store.profile().save_filename(chapter, slot));
+    let pre_restore = match fs::metadata(&game_path) {
+        Ok(metadata) if metadata.len() > 0 => Some(store.backup(chapter, slot)?),
+        _ => None,
+    };
+    store.restore(&backup_path, chapter, slot)?;
+    undo::remember(store, chapter, slot, pre_restore.as_deref())?;
+    Ok(WatchedRestore {
+        chapter,
+        slot,
+        restored_hash: hash_contents(&store.read_backup(&backup_path)?),
+        restored: backup_path,
+        pre_restore,
+        restored_at: SystemTime::now(),
+    })
+}
+
+/// Looks at the slot `watched` restored into, returning a [`Conflict`] if it
+/// no longer holds the restored save. Whatever replaced it is backed up
+/// first, so no choice loses it.
+pub fn check(store: &SaveStore, watched: &WatchedRestore) -> io::Result<Option<Conflict>> {
+    let game_path = store
+        .game_directory()
+        .join(store.profile().save_filename(watched.chapter, watched.slot));
+    let contents = match fs::read(&game_path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+    if contents.is_empty() || hash_contents(&contents) == watched.restored_hash {
+        return Ok(None);
+    }
+    Ok(Some(Conflict {
+        chapter: watched.chapter,
+        slot: watched.slot,
+        cloud: store.import(watched.chapter, watched.slot, &contents)?,
+        restored: watched.restored.clone(),
+        pre_restore: watched.pre_restore.clone(),
+    }))
+}
+
+/// Puts the chosen version of the slot in place.
+pub fn resolve(store: &SaveStore, conflict: &Conflict, choice: Choice) -> io::Result<()> {
+    let backup_path = match choice {
+        Choice::Cloud => &conflict.cloud,
+        Choice::Restored => &conflict.restored,
+        Choice::PreRestore => conflict.pre_restore.as_ref().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "the slot was empty before the restore",
+            )
+        })?,
+    };
+    store.restore(backup_path, conflict.chapter, conflict.slot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+
+    #[test]
+    fn an_overwritten_restore_is_a_conflict_to_resolve() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let game_path = store.game_directory().join("filech1_0");
+
+        let wanted = SyntheticSave::default().to_bytes();
+        let backup = store.import(1, 0, &wanted).unwrap();
+        let before = SyntheticSave {
+            gold: 1,
+            ..Default::default()
+        }
+        .to_bytes();
+        fs::write(&game_path, &before).unwrap();
+
+        let watched = restore_watched(&store, backup, 1, 0).unwrap();
+        assert!(watched.pre_restore.is_some());
+        assert!(check(&store, &watched).unwrap().is_none());
+
+        let cloud = SyntheticSave {
+            gold: 2,
+            ..Default::default()
+        }
+        .to_bytes();
+        fs::write(&game_path, &cloud).unwrap();
+        let conflict = check(&store, &watched).unwrap().unwrap();
+        assert_eq!(store.read_backup(&conflict.cloud).unwrap(), cloud);
+
+        resolve(&store, &conflict, Choice::PreRestore).unwrap();
+        assert_eq!(fs::read(&game_path).unwrap(), before);
+        resolve(&store, &conflict, Choice::Restored).unwrap();
+        assert_eq!(fs::read(&game_path).unwrap(), wanted);
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! The raw contents of a save, as numbered lines or as hex, for looking
+//! inside one without digging it out of the game's directory or the object
+//! store.
+
+/// How many bytes each row of [`hex`] shows.
+pub const HEX_ROW_BYTES: usize = 16;
+
+/// Each line of `contents`, numbered from 1 as in a text editor, with bytes
+/// that are not UTF-8 replaced.
+pub fn lines(contents: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(contents)
+        .lines()
+        .enumerate()
+        .map(|(index, line)| format!("{:>5}  {}", index + 1, line))
+        .collect()
+}
+
+/// `contents` as rows of an offset, [`HEX_ROW_BYTES`] bytes in hex, and
+/// those bytes as ASCII, with `.` for any that are not printable.
+pub fn hex(contents: &[u8]) -> Vec<String> {
+    contents
+        .chunks(HEX_ROW_BYTES)
+        .enumerate()
+        .map(|(row, bytes)| {
+            let hex = bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ascii = bytes
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            format!(
+                "{:08x}  {:<width$}  {}",
+                row * HEX_ROW_BYTES,
+                hex,
+                ascii,
+                width = HEX_ROW_BYTES * 3 - 1
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dumps_numbered_lines_and_hex_rows() {
+        let contents = b"KRIS\r\n\r\n12\r\n";
+        assert_eq!(lines(contents), ["    1  KRIS", "    2  ", "    3  12"]);
+
+        let rows = hex(b"KRIS\r\n0123456789abcdef");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[0],
+            "00000000  4b 52 49 53 0d 0a 30 31 32 33 34 35 36 37 38 39  KRIS..0123456789"
+        );
+        assert!(rows[1].starts_with("00000010  61 62 63 64 65 66 "));
+        assert!(rows[1].ends_with("  abcdef"));
+    }
+}
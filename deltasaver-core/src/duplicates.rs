@@ -0,0 +1,239 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Backups with identical contents, such as those taken of a slot the game
+//! had not written since, or of one slot copied into another.
+//!
+//! Stored contents are shared by hash already, so duplicates cost next to
+//! nothing on disk, and collapsing them tidies the list rather than saving
+//! space. What it will free once the trash is emptied is reported all the
+//! same: the objects no backup left refers to, like a file backed up with a
+//! duplicate that differs from the one kept with the copy left. Collapsing
+//! keeps, for each slot on each branch, its newest backup in a set, or its
+//! pinned ones, so no slot or branch loses its only copy, and never touches
+//! the backup the last restore would be undone with.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+
+use crate::branch::Branches;
+use crate::profile::{backup_created_secs, backup_id};
+use crate::save::SaveFile;
+use crate::store::SaveStore;
+use crate::undo;
+
+/// Backups sharing the contents hashing to `hash`, newest first.
+#[derive(Debug, Clone)]
+pub struct DuplicateSet {
+    pub hash: String,
+    pub saves: Vec<SaveFile>,
+}
+
+/// The sets of two or more backups among `saves` with the same contents, by
+/// hash.
+pub fn find(saves: &[SaveFile]) -> Vec<DuplicateSet> {
+    let mut by_hash = BTreeMap::<_, Vec<_>>::new();
+    for save in saves {
+        if let Some(hash) = &save.hash {
+            by_hash.entry(hash.clone()).or_default().push(save.clone());
+        }
+    }
+    by_hash
+        .into_iter()
+        .filter(|(_, saves)| saves.len() > 1)
+        .map(|(hash, mut saves)| {
+            saves.sort_by_key(|save| {
+                let filename = save.id().unwrap_or_default();
+                (
+                    backup_created_secs(filename),
+                    backup_id(filename).map(str::to_string),
+                )
+            });
+            saves.reverse();
+            DuplicateSet { hash, saves }
+        })
+        .collect()
+}
+
+/// What [`collapse`] did.
+#[derive(Debug, Clone, Default)]
+pub struct Collapsed {
+    /// The backups moved to the trash.
+    pub trashed: Vec<SaveFile>,
+    /// The bytes of stored objects freed once the trash is emptied.
+    pub reclaimable: u64,
+}
+
+/// Moves every backup in `sets` to the trash but, for each slot on each
+/// branch in a set, those `is_pinned`, or if none are, the newest. Backups of
+/// frozen slots and the backup the last restore would be undone with are left
+/// alone.
+pub fn collapse(
+    store: &SaveStore,
+    sets: &[DuplicateSet],
+    is_pinned: impl Fn(&SaveFile) -> bool,
+) -> io::Result<Collapsed> {
+    let undo_backup = undo::last_restore(store)?.and_then(|last| last.pre_restore);
+    let branches = Branches::load(store.local_directory())?;
+    let mut doomed = Vec::new();
+    for set in sets {
+        let mut by_slot = BTreeMap::<_, Vec<_>>::new();
+        for save in &set.saves {
+            by_slot
+                .entry((save.chapter, save.slot, branches.branch_of(save)))
+                .or_default()
+                .push(save);
+        }
+        for saves in by_slot.values() {
+            let any_pinned = saves.iter().any(|save| is_pinned(save));
+            for (rank, save) in saves.iter().enumerate() {
+                if is_pinned(save)
+                    || (rank == 0 && !any_pinned)
+                    || (undo_backup.is_some() && save.id() == undo_backup.as_deref())
+                    || store.frozen_until(save.chapter, save.slot)?.is_some()
+                {
+                    continue;
+                }
+                doomed.push((*save).clone());
+            }
+        }
+    }
+    let reclaimable = store.reclaimable_bytes(
+        &doomed
+            .iter()
+            .map(|save| save.path.clone())
+            .collect::<Vec<PathBuf>>(),
+    )?;
+    for save in &doomed {
+        store.delete(&save.path)?;
+    }
+    Ok(Collapsed {
+        trashed: doomed,
+        reclaimable,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+    use crate::save::{PERSISTENT_FILENAME, hash_contents};
+    use std::fs;
+
+    #[test]
+    fn identical_backups_collapse_to_the_newest_or_pinned_of_each_slot() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let ulid = |ms| ulid::Ulid::from_parts(ms, 0).to_string();
+        let same = SyntheticSave::default().to_bytes();
+        let oldest = store.import_with_id(1, 0, &same, &ulid(1_000)).unwrap();
+        let copy = store.import_with_id(1, 1, &same, &ulid(2_000)).unwrap();
+        let newest = store.import_with_id(1, 0, &same, &ulid(3_000)).unwrap();
+        store
+            .import_with_id(1, 2, b"something else", &ulid(4_000))
+            .unwrap();
+
+        let sets = find(&store.load().unwrap().local);
+        assert_eq!(sets.len(), 1);
+        assert_eq!(
+            sets[0]
+                .saves
+                .iter()
+                .map(|save| &save.path)
+                .collect::<Vec<_>>(),
+            vec![&newest, &copy, &oldest]
+        );
+
+        let collapsed = collapse(&store, &sets, |save| save.path == oldest).unwrap();
+        assert_eq!(
+            collapsed
+                .trashed
+                .iter()
+                .map(|save| &save.path)
+                .collect::<Vec<_>>(),
+            vec![&newest]
+        );
+        assert_eq!(collapsed.reclaimable, 0);
+        let sets = find(&store.load().unwrap().local);
+        assert_eq!(
+            sets[0]
+                .saves
+                .iter()
+                .map(|save| &save.path)
+                .collect::<Vec<_>>(),
+            vec![&copy, &oldest]
+        );
+        assert!(
+            collapse(&store, &sets, |_| false)
+                .unwrap()
+                .trashed
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn other_branches_and_the_undo_backup_are_kept() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let ulid = |ms| ulid::Ulid::from_parts(ms, 0).to_string();
+        let same = SyntheticSave::default().to_bytes();
+        let undo_backup = store.import_with_id(1, 0, &same, &ulid(1_000)).unwrap();
+        let trashed = store.import_with_id(1, 0, &same, &ulid(2_000)).unwrap();
+        let newest = store.import_with_id(1, 0, &same, &ulid(3_000)).unwrap();
+        let mut branches = Branches::load(store.local_directory()).unwrap();
+        branches.switch("weird route");
+        branches.save(store.local_directory()).unwrap();
+        let other_branch = store.import_with_id(1, 0, &same, &ulid(4_000)).unwrap();
+        undo::remember(&store, 1, 0, Some(&undo_backup)).unwrap();
+
+        let sets = find(&store.load().unwrap().local);
+        let collapsed = collapse(&store, &sets, |_| false).unwrap();
+        assert_eq!(
+            collapsed
+                .trashed
+                .iter()
+                .map(|save| &save.path)
+                .collect::<Vec<_>>(),
+            vec![&trashed]
+        );
+        let remaining = store.load().unwrap().local;
+        for kept in [&undo_backup, &newest, &other_branch] {
+            assert!(remaining.iter().any(|save| &save.path == kept));
+        }
+    }
+
+    #[test]
+    fn only_files_no_backup_left_refers_to_are_reclaimable() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let game = store.game_directory().to_path_buf();
+        SyntheticSave::default().write_to(&game, 0).unwrap();
+        let old_settings = b"[settings]\nvolume=1\n";
+        fs::write(game.join(PERSISTENT_FILENAME), old_settings).unwrap();
+        let older = store.backup(1, 0).unwrap();
+        fs::write(game.join(PERSISTENT_FILENAME), "[settings]\nvolume=0\n").unwrap();
+        let newer = store.backup(1, 0).unwrap();
+        assert_ne!(older, newer);
+
+        let freed = fs::metadata(
+            store
+                .local_directory()
+                .join("objects")
+                .join(hash_contents(old_settings)),
+        )
+        .unwrap()
+        .len();
+        let sets = find(&store.load().unwrap().local);
+        let collapsed = collapse(&store, &sets, |_| false).unwrap();
+        assert_eq!(
+            collapsed
+                .trashed
+                .iter()
+                .map(|save| &save.path)
+                .collect::<Vec<_>>(),
+            vec![&older]
+        );
+        assert!(freed > 0);
+        assert_eq!(collapsed.reclaimable, freed);
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Changing the few fields of a save that the game reads back without
+//! checking them against anything else, such as the name and dark dollars.
+//!
+//! Edits are always written as a new backup of the same slot; the backup
+//! edited is left as it was.
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::format;
+use crate::save::SaveFile;
+use crate::store::SaveStore;
+use crate::transfer::looks_like_save;
+
+/// The longest name the game's naming screen allows.
+pub const MAX_NAME_LENGTH: usize = 12;
+
+/// The fields to change, leaving those that are `None` as they are.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Edits {
+    pub name: Option<String>,
+    /// Dark dollars.
+    pub gold: Option<u32>,
+}
+
+impl Edits {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.gold.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    NotASave,
+    /// The name is empty, too long, or has characters that would break the
+    /// save apart.
+    InvalidName(String),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::NotASave => f.write_str("not a DELTARUNE save"),
+            EditError::InvalidName(name) => write!(
+                f,
+                "{:?} is not a valid name: it must be 1 to {} characters on one line",
+                name, MAX_NAME_LENGTH
+            ),
+        }
+    }
+}
+
+impl From<EditError> for io::Error {
+    fn from(error: EditError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+    }
+}
+
+/// `contents` with `edits` made, keeping every other line, and the line
+/// endings the game wrote, byte for byte.
+pub fn apply(contents: &[u8], edits: &Edits) -> Result<Vec<u8>, EditError> {
+    if !format::has_full_layout(contents) || !looks_like_save(contents) {
+        return Err(EditError::NotASave);
+    }
+    if let Some(name) = edits.name.as_ref().filter(|name| !is_valid_name(name)) {
+        return Err(EditError::InvalidName(name.clone()));
+    }
+
+    let text = std::str::from_utf8(contents).map_err(|_| EditError::NotASave)?;
+    let mut edited = String::with_capacity(text.len());
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        let value = line.trim_end_matches(['\r', '\n']);
+        let ending = &line[value.len()..];
+        let replacement = match index {
+            format::NAME_LINE => edits.name.clone(),
+            format::GOLD_LINE => edits.gold.map(|gold| gold.to_string()),
+            _ => None,
+        };
+        edited.push_str(replacement.as_deref().unwrap_or(value));
+        edited.push_str(ending);
+    }
+    Ok(edited.into_bytes())
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().count() <= MAX_NAME_LENGTH
+        && !name.chars().any(char::is_control)
+}
+
+/// Writes the backup `save` with `edits` made as a new backup of its slot,
+/// noting which backup it was edited from unless the backups are encrypted,
+/// and returns its path.
+pub fn edit_backup(store: &SaveStore, save: &SaveFile, edits: &Edits) -> io::Result<PathBuf> {
+    let edited = apply(&store.read_backup(&save.path)?, edits)?;
+    let backup_path = store.import(save.chapter, save.slot, &edited)?;
+    if let (Some(id), Some(original)) = (
+        backup_path.file_name().and_then(|name| name.to_str()),
+        save.share_code(),
+    ) && !store.is_encrypted()
+    {
+        store
+            .index()?
+            .set_note(id, &format!("Edited from {}", original))?;
+    }
+    Ok(backup_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+    use crate::summary::SaveSummary;
+
+    #[test]
+    fn edits_are_new_backups_differing_only_in_the_edited_lines() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let contents = SyntheticSave {
+            gold: 10,
+            ..Default::default()
+        }
+        .to_bytes();
+        let original = store.import(1, 0, &contents).unwrap();
+        let save = store.load().unwrap().local.remove(0);
+
+        let edits = Edits {
+            name: Some("NOELLE".to_string()),
+            gold: Some(9999),
+        };
+        let edited_path = edit_backup(&store, &save, &edits).unwrap();
+        assert_eq!(store.read_backup(&original).unwrap(), contents);
+
+        let edited = store.read_backup(&edited_path).unwrap();
+        let summary = SaveSummary::parse(&edited).unwrap();
+        assert_eq!((summary.name.as_str(), summary.gold), ("NOELLE", 9999));
+        let changed = String::from_utf8(contents)
+            .unwrap()
+            .split("\r\n")
+            .zip(String::from_utf8(edited).unwrap().split("\r\n"))
+            .filter(|(before, after)| before != after)
+            .count();
+        assert_eq!(changed, 2);
+
+        assert_eq!(
+            apply(
+                &store.read_backup(&original).unwrap(),
+                &Edits {
+                    name: Some("A\nB".to_string()),
+                    gold: None,
+                }
+            ),
+            Err(EditError::InvalidName("A\nB".to_string()))
+        );
+        assert_eq!(
+            apply(b"KRIS\n", &Edits::default()),
+            Err(EditError::NotASave)
+        );
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Optional encryption of backups at rest, for PCs shared with others.
+//!
+//! Once a backup directory is encrypted with a passphrase, the contents of
+//! every backup in it are sealed with AES-256-GCM under a key derived from
+//! the passphrase with Argon2. The directory's `encryption.json` holds the
+//! salt and a sealed check value, so a wrong passphrase is caught before
+//! anything is written with it. The passphrase itself is never stored.
+//!
+//! Only the contents are sealed. Backup filenames still say which chapter
+//! and slot each backup is of, when it was taken, and the SHA-256 hash of
+//! its unencrypted contents, which is enough to confirm a guess at them.
+//! Snapshots, chapter handoffs, and the mirror would keep unencrypted
+//! copies, and the index would keep names, notes, and tags in the clear, so
+//! they are all refused while the backups are encrypted, and encrypting
+//! refuses while any are left, other than backups from before the object
+//! store, which are moved into it and sealed with the rest.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::Argon2;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const ENCRYPTION_FILENAME: &str = "encryption.json";
+
+/// Read for the passphrase by the command line tools, so scripts and the
+/// background daemon can unlock encrypted backups without a prompt.
+pub const PASSPHRASE_VARIABLE: &str = "DELTASAVER_PASSPHRASE";
+
+/// Starts every sealed file, so sealed and plain files can be told apart.
+pub const SEALED_MAGIC: &[u8; 6] = b"DSENC1";
+
+const NONCE_LENGTH: usize = 12;
+
+const SALT_LENGTH: usize = 16;
+
+/// What the check value in `encryption.json` decrypts to.
+const CHECK_PLAINTEXT: &[u8] = b"deltasaver";
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    /// In base64.
+    salt: String,
+    /// [`CHECK_PLAINTEXT`] sealed with the key, in base64.
+    check: String,
+}
+
+/// The key to a backup directory, derived from its passphrase.
+#[derive(Clone)]
+pub struct Key([u8; 32]);
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+impl Key {
+    fn derive(passphrase: &str, salt: &[u8]) -> io::Result<Self> {
+        let mut key = [0; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|error| io::Error::other(error.to_string()))?;
+        Ok(Self(key))
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(&self.0.into())
+    }
+}
+
+/// Whether the backups in `local_directory` are encrypted.
+pub fn is_enabled(local_directory: &Path) -> bool {
+    local_directory.join(ENCRYPTION_FILENAME).is_file()
+}
+
+/// Starts encrypting `local_directory` with `passphrase`, returning its key.
+/// Fails if it already is encrypted.
+pub fn enable(local_directory: &Path, passphrase: &str) -> io::Result<Key> {
+    if is_enabled(local_directory) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "the backups are already encrypted",
+        ));
+    }
+    let mut salt = [0; SALT_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    let key = Key::derive(passphrase, &salt)?;
+    let header = Header {
+        salt: STANDARD.encode(salt),
+        check: STANDARD.encode(seal(&key, CHECK_PLAINTEXT)?),
+    };
+    let contents = serde_json::to_vec_pretty(&header).map_err(io::Error::other)?;
+    fs::write(local_directory.join(ENCRYPTION_FILENAME), contents)?;
+    Ok(key)
+}
+
+/// The key to `local_directory` if `passphrase` is right, or a
+/// `PermissionDenied` error if not.
+pub fn unlock(local_directory: &Path, passphrase: &str) -> io::Result<Key> {
+    let invalid = |error: String| io::Error::new(io::ErrorKind::InvalidData, error);
+    let header: Header =
+        serde_json::from_slice(&fs::read(local_directory.join(ENCRYPTION_FILENAME))?)
+            .map_err(|error| invalid(error.to_string()))?;
+    let salt = STANDARD
+        .decode(&header.salt)
+        .map_err(|error| invalid(error.to_string()))?;
+    let check = STANDARD
+        .decode(&header.check)
+        .map_err(|error| invalid(error.to_string()))?;
+    let key = Key::derive(passphrase, &salt)?;
+    match open(&key, &check) {
+        Ok(plaintext) if plaintext == CHECK_PLAINTEXT => Ok(key),
+        _ => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "wrong passphrase",
+        )),
+    }
+}
+
+/// Fails if the backups in `local_directory` are encrypted, before making
+/// `what`, which would be kept beside them unencrypted.
+pub(crate) fn check_unencrypted(local_directory: &Path, what: &str) -> io::Result<()> {
+    if is_enabled(local_directory) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "{} are not encrypted, so they are off while the backups are",
+                what
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `contents` were sealed by [`seal`].
+pub fn is_sealed(contents: &[u8]) -> bool {
+    contents.starts_with(SEALED_MAGIC)
+}
+
+/// Encrypts `plaintext` with `key` under a fresh nonce.
+pub fn seal(key: &Key, plaintext: &[u8]) -> io::Result<Vec<u8>> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = key
+        .cipher()
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| io::Error::other("could not encrypt"))?;
+    let mut sealed = SEALED_MAGIC.to_vec();
+    sealed.extend_from_slice(&nonce);
+    sealed.extend(ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypts what [`seal`] returned, failing if it was sealed with another key
+/// or has been tampered with.
+pub fn open(key: &Key, sealed: &[u8]) -> io::Result<Vec<u8>> {
+    let damaged = || io::Error::new(io::ErrorKind::InvalidData, "could not decrypt");
+    let rest = sealed.strip_prefix(SEALED_MAGIC).ok_or_else(damaged)?;
+    if rest.len() < NONCE_LENGTH {
+        return Err(damaged());
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+    key.cipher()
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| damaged())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_right_passphrase_unlocks_what_was_sealed() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(!is_enabled(root.path()));
+        let key = enable(root.path(), "hunter2").unwrap();
+        assert!(is_enabled(root.path()));
+        assert!(enable(root.path(), "again").is_err());
+
+        let sealed = seal(&key, b"save").unwrap();
+        assert!(is_sealed(&sealed));
+        assert_eq!(
+            unlock(root.path(), "wrong").unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+        let unlocked = unlock(root.path(), "hunter2").unwrap();
+        assert_eq!(open(&unlocked, &sealed).unwrap(), b"save");
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(open(&unlocked, &tampered).is_err());
+    }
+}
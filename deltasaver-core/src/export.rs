@@ -0,0 +1,272 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Writing backups into `.dsave` archives, the format for sharing saves along
+//! with what is known about them.
+//!
+//! A `.dsave` archive is a zip of the saves under `saves/`, named as they are
+//! in the backup directory, beside a `manifest.json` describing them:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "profile": "DELTARUNE",
+//!   "saves": [{
+//!     "file": "saves/filech2_0_ab12…_01JZ3K2N8Q4V6X0M5B7C9D1E2F",
+//!     "id": "01JZ3K2N8Q4V6X0M5B7C9D1E2F",
+//!     "chapter": 2,
+//!     "slot": 0,
+//!     "hash": "ab12…",
+//!     "created": 1767225600,
+//!     "note": "before Spamton NEO",
+//!     "tags": ["boss"]
+//!   }]
+//! }
+//! ```
+//!
+//! [`crate::import`] reads them back, along with plain zips of save files,
+//! refusing archives of another game's saves or of a newer
+//! [`FORMAT_VERSION`]. Archives written before the version was recorded are
+//! version 0, which differs from version 1 only in lacking it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+use crate::profile::{backup_created_secs, backup_id};
+use crate::{Chapter, SaveFile, SaveStore, Slot};
+
+/// The extension of archives written here, though any name imports.
+pub const ARCHIVE_EXTENSION: &str = "dsave";
+
+/// The version of the `.dsave` format written here, raised whenever a
+/// change would be misread by older versions of DELTASAVER.
+pub const FORMAT_VERSION: u32 = 1;
+
+pub(crate) const MANIFEST_NAME: &str = "manifest.json";
+const SAVES_DIRECTORY: &str = "saves";
+
+#[derive(Debug, Clone)]
+pub enum ExportError {
+    Io(String),
+    Zip(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io(message) | ExportError::Zip(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(error: io::Error) -> Self {
+        ExportError::Io(error.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for ExportError {
+    fn from(error: zip::result::ZipError) -> Self {
+        ExportError::Zip(error.to_string())
+    }
+}
+
+/// The mode of every entry, rather than that of the file it was read from.
+const FIXED_PERMISSIONS: u32 = 0o644;
+
+/// Every entry is written with a fixed timestamp and mode so that exporting
+/// the same backups always produces the same bytes, no matter when or where.
+fn archive_options() -> SimpleFileOptions {
+    SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(DateTime::default())
+        .unix_permissions(FIXED_PERMISSIONS)
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Manifest {
+    /// See [`FORMAT_VERSION`].
+    #[serde(default)]
+    pub(crate) version: u32,
+    /// The name of the profile the backups were taken with.
+    #[serde(default)]
+    pub(crate) profile: String,
+    /// The chapter whose history this is, if the archive is of one chapter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) chapter: Option<Chapter>,
+    pub(crate) saves: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ManifestEntry {
+    pub(crate) file: String,
+    /// The backup's ID, unique among the user's backups.
+    pub(crate) id: Option<String>,
+    pub(crate) chapter: Chapter,
+    pub(crate) slot: Slot,
+    pub(crate) hash: Option<String>,
+    /// Seconds since the epoch at which the backup was taken, as recorded in
+    /// its ID. File modification times are deliberately not used since
+    /// they differ between machines holding the same backup.
+    pub(crate) created: Option<u64>,
+    /// What the sender named the backup, see [`crate::index::Entry::name`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) note: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub(crate) tags: BTreeSet<String>,
+}
+
+/// Writes every backup of `chapter` among `saves`, as loaded from `store`,
+/// into a zip archive at `path`, alongside a manifest describing them.
+pub fn write_chapter_archive(
+    path: &Path,
+    chapter: Chapter,
+    store: &SaveStore,
+    saves: &[SaveFile],
+) -> Result<(), ExportError> {
+    let saves = saves
+        .iter()
+        .filter(|save| save.chapter == chapter)
+        .cloned()
+        .collect::<Vec<_>>();
+    write_archive(path, Some(chapter), store, &saves)
+}
+
+/// Writes `saves`, backups loaded from `store` of any chapters, into a zip
+/// archive at `path`, alongside a manifest describing them, e.g. to send them
+/// to a friend.
+pub fn write_backup_archive(
+    path: &Path,
+    store: &SaveStore,
+    saves: &[SaveFile],
+) -> Result<(), ExportError> {
+    write_archive(path, None, store, saves)
+}
+
+fn write_archive(
+    path: &Path,
+    chapter: Option<Chapter>,
+    store: &SaveStore,
+    saves: &[SaveFile],
+) -> Result<(), ExportError> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = archive_options();
+
+    let mut saves = saves
+        .iter()
+        .filter_map(|save| {
+            let filename = save.path.file_name()?.to_str()?;
+            Some((filename, save))
+        })
+        .collect::<Vec<_>>();
+    saves.sort_by_key(|(filename, _)| *filename);
+
+    let index_entries = store.index()?.entries()?;
+    let mut entries = Vec::new();
+    for (filename, save) in saves {
+        let index_entry = index_entries.get(filename).cloned().unwrap_or_default();
+        let archive_name = format!("{}/{}", SAVES_DIRECTORY, filename);
+
+        zip.start_file(archive_name.as_str(), options)?;
+        zip.write_all(&store.read_backup(&save.path)?)?;
+
+        entries.push(ManifestEntry {
+            file: archive_name,
+            id: backup_id(filename).map(str::to_string),
+            chapter: save.chapter,
+            slot: save.slot,
+            hash: save.hash.clone(),
+            created: backup_created_secs(filename),
+            name: index_entry.name,
+            note: index_entry.note,
+            tags: index_entry.tags,
+        });
+    }
+
+    let manifest = Manifest {
+        version: FORMAT_VERSION,
+        profile: store.profile().name.clone(),
+        chapter,
+        saves: entries,
+    };
+    zip.start_file(MANIFEST_NAME, options)?;
+    zip.write_all(
+        &serde_json::to_vec_pretty(&manifest).map_err(|e| ExportError::Io(e.to_string()))?,
+    )?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Writes every file directly inside `directory` into a zip archive at
+/// `path`, e.g. to send a snapshot offsite as one file.
+pub fn write_directory_archive(path: &Path, directory: &Path) -> Result<(), ExportError> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = archive_options();
+
+    let mut files = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    files.retain(|file| file.is_file());
+    files.sort();
+
+    for file in files {
+        let Some(filename) = file.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        zip.start_file(filename, options)?;
+        zip.write_all(&fs::read(&file)?)?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    #[test]
+    fn selected_backups_are_exported_with_a_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let contents = SyntheticSave::default().to_bytes();
+        store.import(1, 0, &contents).unwrap();
+        store.import(2, 1, &contents).unwrap();
+        let saves = store.load().unwrap().local;
+
+        let path = root.path().join("backups.zip");
+        write_backup_archive(&path, &store, &saves).unwrap();
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(archive.len(), 3);
+        let mut manifest = String::new();
+        archive
+            .by_name(MANIFEST_NAME)
+            .unwrap()
+            .read_to_string(&mut manifest)
+            .unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(manifest["profile"], "DELTARUNE");
+        assert_eq!(manifest["version"], FORMAT_VERSION);
+        assert!(manifest.get("chapter").is_none());
+        let entries = manifest["saves"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        let mut exported = Vec::new();
+        archive
+            .by_name(entries[0]["file"].as_str().unwrap())
+            .unwrap()
+            .read_to_end(&mut exported)
+            .unwrap();
+        assert_eq!(exported, contents);
+    }
+}
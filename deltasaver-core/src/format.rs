@@ -0,0 +1,244 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! The layout of a DELTARUNE save file.
+//!
+//! GameMaker writes saves as one value per line: strings verbatim and numbers
+//! in decimal. The game writes every value in a fixed order, so each field is
+//! identified purely by its line number.
+
+/// `global.truename`, the name typed at the start of the game.
+pub const NAME_LINE: usize = 0;
+
+pub const OTHER_NAME_COUNT: usize = 6;
+
+/// The three party members, as character IDs. See [`CHARACTER_NAMES`].
+pub const PARTY_LINE: usize = NAME_LINE + 1 + OTHER_NAME_COUNT;
+pub const PARTY_SIZE: usize = 3;
+
+/// Dark dollars.
+pub const GOLD_LINE: usize = PARTY_LINE + PARTY_SIZE;
+pub const XP_LINE: usize = GOLD_LINE + 1;
+pub const LV_LINE: usize = XP_LINE + 1;
+pub const INV_LINE: usize = LV_LINE + 1;
+pub const INVC_LINE: usize = INV_LINE + 1;
+pub const DARKZONE_LINE: usize = INVC_LINE + 1;
+
+/// Each character's block starts with these stats, in this order.
+pub const CHARACTERS_LINE: usize = DARKZONE_LINE + 1;
+pub const CHARACTER_COUNT: usize = 5;
+pub const HP_OFFSET: usize = 0;
+pub const MAX_HP_OFFSET: usize = 1;
+pub const WEAPON_OFFSET: usize = 6;
+pub const ARMOR1_OFFSET: usize = 7;
+pub const ARMOR2_OFFSET: usize = 8;
+pub const CHARACTER_STAT_COUNT: usize = 10;
+pub const CHARACTER_ITEM_SLOTS: usize = 4;
+pub const ITEM_STAT_COUNT: usize = 8;
+pub const SPELL_COUNT: usize = 12;
+pub const CHARACTER_BLOCK_LENGTH: usize =
+    CHARACTER_STAT_COUNT + CHARACTER_ITEM_SLOTS * ITEM_STAT_COUNT + SPELL_COUNT;
+
+/// Bolt speed, graze amount, and graze size.
+pub const BOLT_GRAZE_LINE: usize = CHARACTERS_LINE + CHARACTER_COUNT * CHARACTER_BLOCK_LENGTH;
+
+/// Items, key items, weapons, and armors, interleaved per inventory slot.
+pub const INVENTORY_LINE: usize = BOLT_GRAZE_LINE + 3;
+pub const INVENTORY_SLOTS: usize = 13;
+pub const INVENTORY_KINDS: usize = 4;
+/// Where key items come among the kinds of each inventory slot.
+pub const KEY_ITEM_KIND: usize = 1;
+
+pub const TENSION_LINE: usize = INVENTORY_LINE + INVENTORY_SLOTS * INVENTORY_KINDS;
+pub const MAX_TENSION_LINE: usize = TENSION_LINE + 1;
+
+/// Light world weapon, armor, XP, LV, gold, HP, max HP, attack, defense,
+/// weapon strength, and armor defense.
+pub const LIGHT_WORLD_LINE: usize = MAX_TENSION_LINE + 1;
+pub const LIGHT_WORLD_STAT_COUNT: usize = 11;
+
+/// Light world items and phone numbers, interleaved.
+pub const LIGHT_ITEMS_LINE: usize = LIGHT_WORLD_LINE + LIGHT_WORLD_STAT_COUNT;
+pub const LIGHT_ITEM_SLOTS: usize = 8;
+
+/// `global.flag`, the game's general-purpose progress array.
+pub const FLAGS_LINE: usize = LIGHT_ITEMS_LINE + LIGHT_ITEM_SLOTS * 2;
+pub const FLAG_COUNT: usize = 9999;
+
+/// The flag tracking progress along the weird route, which Chapter 2 starts
+/// and later chapters carry over. Anything but 0 means the save is on it.
+pub const WEIRD_ROUTE_FLAG: usize = 915;
+
+/// Recruit progress for the enemy with ID `id` is kept in flag
+/// `RECRUIT_FLAG_BASE + id`: positive once recruited, negative once lost.
+pub const RECRUIT_FLAG_BASE: usize = 600;
+
+/// The IDs of the enemies that can be recruited in each chapter, starting
+/// from Chapter 1. Later chapters are not listed yet.
+pub const RECRUIT_IDS: [&[usize]; 2] = [
+    &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+    &[20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31],
+];
+
+/// `global.plot`, the main story progress counter.
+pub const PLOT_LINE: usize = FLAGS_LINE + FLAG_COUNT;
+
+/// The index of the room the game was saved in.
+pub const ROOM_LINE: usize = PLOT_LINE + 1;
+
+/// Play time in frames, at [`FRAMES_PER_SECOND`].
+pub const TIME_LINE: usize = ROOM_LINE + 1;
+pub const FRAMES_PER_SECOND: u64 = 30;
+
+pub const LINE_COUNT: usize = TIME_LINE + 1;
+
+/// Names for the character IDs used in the party lines.
+pub const CHARACTER_NAMES: [&str; CHARACTER_COUNT] =
+    ["(none)", "Kris", "Susie", "Ralsei", "Noelle"];
+
+/// Whether `contents` has every line of the layout, as any save the game
+/// writes does. Saves record no chapter of their own, so this is as close as
+/// we can come to checking one before another chapter loads it.
+pub fn has_full_layout(contents: &[u8]) -> bool {
+    std::str::from_utf8(contents).is_ok_and(|text| text.lines().count() >= LINE_COUNT)
+}
+
+/// Checks that `contents` has the structure the game writes, describing the
+/// first thing wrong with it otherwise: that it is text with every line of
+/// the layout and that its numeric fields are numbers in range. Saves record
+/// no chapter, so which chapter one belongs to cannot be checked here.
+pub fn validate(contents: &[u8]) -> Result<(), String> {
+    let text = std::str::from_utf8(contents).map_err(|_| "it is not text".to_string())?;
+    let lines = text.lines().collect::<Vec<_>>();
+    if lines.len() < LINE_COUNT {
+        return Err(format!(
+            "it has {} lines of the {} a save has",
+            lines.len(),
+            LINE_COUNT
+        ));
+    }
+    let numbers = (0..PARTY_SIZE)
+        .map(|i| (PARTY_LINE + i, "party member", CHARACTER_COUNT as f64))
+        .chain([
+            (GOLD_LINE, "dark dollars", f64::MAX),
+            (XP_LINE, "XP", f64::MAX),
+            (LV_LINE, "LV", f64::MAX),
+            (PLOT_LINE, "plot", f64::MAX),
+            (ROOM_LINE, "room", f64::MAX),
+            (TIME_LINE, "play time", f64::MAX),
+        ]);
+    for (line, field, limit) in numbers {
+        let value = lines[line].trim();
+        match value.parse::<f64>() {
+            Ok(number) if number.is_finite() && (0.0..limit).contains(&number) => {}
+            _ => {
+                return Err(format!(
+                    "line {} ({}) is {:?}, not a number in range",
+                    line + 1,
+                    field,
+                    value
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The values of every flag in `contents`, as written, or `None` if it is not
+/// a full save.
+pub fn flags(contents: &[u8]) -> Option<Vec<&str>> {
+    let lines = std::str::from_utf8(contents)
+        .ok()?
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>();
+    lines
+        .get(FLAGS_LINE..FLAGS_LINE + FLAG_COUNT)
+        .map(<[&str]>::to_vec)
+}
+
+/// What `flag` is known to mean, if anything.
+pub fn flag_meaning(flag: usize) -> Option<String> {
+    if flag == WEIRD_ROUTE_FLAG {
+        return Some("Weird route progress".to_string());
+    }
+    let id = flag.checked_sub(RECRUIT_FLAG_BASE)?;
+    RECRUIT_IDS.iter().enumerate().find_map(|(index, ids)| {
+        ids.contains(&id)
+            .then(|| format!("Recruit progress of enemy {} (Chapter {})", id, index + 1))
+    })
+}
+
+/// Whether `flag`, set to `value`, is one searched for by `query`: with no
+/// query, those set or known to mean something, or else those whose index
+/// starts with the query, or whose meaning contains it.
+pub fn flag_matches(flag: usize, value: &str, query: &str) -> bool {
+    let query = query.trim().to_lowercase();
+    let meaning = flag_meaning(flag);
+    if query.is_empty() {
+        value.parse::<f64>() != Ok(0.0) || meaning.is_some()
+    } else if query.chars().all(|c| c.is_ascii_digit()) {
+        flag.to_string().starts_with(&query)
+    } else {
+        meaning.is_some_and(|meaning| meaning.to_lowercase().contains(&query))
+    }
+}
+
+/// The line holding `stat_offset` of `character`'s block.
+pub const fn character_line(character: usize, stat_offset: usize) -> usize {
+    CHARACTERS_LINE + character * CHARACTER_BLOCK_LENGTH + stat_offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SyntheticSave;
+
+    #[test]
+    fn validation_catches_truncated_and_garbled_saves() {
+        let mut lines = SyntheticSave::default().lines();
+        assert_eq!(validate(lines.join("\r\n").as_bytes()), Ok(()));
+
+        lines[ROOM_LINE] = "4x2".to_string();
+        assert_eq!(
+            validate(lines.join("\r\n").as_bytes()),
+            Err(format!(
+                "line {} (room) is \"4x2\", not a number in range",
+                ROOM_LINE + 1
+            ))
+        );
+        lines[ROOM_LINE] = "42".to_string();
+        lines[PARTY_LINE] = "9".to_string();
+        assert!(validate(lines.join("\r\n").as_bytes()).is_err());
+
+        lines.truncate(100);
+        assert_eq!(
+            validate(lines.join("\r\n").as_bytes()),
+            Err(format!("it has 100 lines of the {} a save has", LINE_COUNT))
+        );
+        assert_eq!(validate(&[0xff]), Err("it is not text".to_string()));
+    }
+
+    #[test]
+    fn reads_flags_and_what_some_mean() {
+        let contents = SyntheticSave {
+            flags: [(WEIRD_ROUTE_FLAG, 2)].into(),
+            ..Default::default()
+        }
+        .to_bytes();
+        let flags = flags(&contents).unwrap();
+        assert_eq!(flags.len(), FLAG_COUNT);
+        assert_eq!(flags[WEIRD_ROUTE_FLAG], "2");
+
+        assert!(flag_meaning(WEIRD_ROUTE_FLAG).is_some());
+        let recruit = RECRUIT_FLAG_BASE + RECRUIT_IDS[1][0];
+        assert!(flag_meaning(recruit).unwrap().ends_with("(Chapter 2)"));
+        assert_eq!(flag_meaning(0), None);
+
+        assert!(flag_matches(12, "1", ""));
+        assert!(!flag_matches(12, "0", ""));
+        assert!(flag_matches(WEIRD_ROUTE_FLAG, "0", ""));
+        assert!(flag_matches(123, "0", "12"));
+        assert!(!flag_matches(213, "0", "12"));
+        assert!(flag_matches(WEIRD_ROUTE_FLAG, "0", "weird"));
+    }
+}
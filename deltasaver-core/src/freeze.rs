@@ -0,0 +1,131 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Game slots frozen until a chosen time, so that an in-progress run cannot
+//! be overwritten by a restore, nor its backups deleted, by mistake.
+//!
+//! Freezes are kept in `freezes.json` in the backup directory, mapping each
+//! frozen slot's save filename to the Unix time the freeze ends:
+//!
+//! ```json
+//! { "filech2_0": 1767225600 }
+//! ```
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const FREEZES_FILENAME: &str = "freezes.json";
+
+/// The freeze end times by save filename.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Freezes(BTreeMap<String, u64>);
+
+impl Freezes {
+    /// Reads the freezes in `local_directory`, of which there are none if the
+    /// file does not exist yet.
+    pub fn load(local_directory: &Path) -> io::Result<Self> {
+        match fs::read(local_directory.join(FREEZES_FILENAME)) {
+            Ok(contents) => serde_json::from_slice(&contents)
+                .map(Self)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Writes the freezes back, dropping any that have ended.
+    pub fn save(&self, local_directory: &Path) -> io::Result<()> {
+        let now = unix_secs(SystemTime::now());
+        let active = self
+            .0
+            .iter()
+            .filter(|(_, until)| **until > now)
+            .collect::<BTreeMap<_, _>>();
+        let contents = serde_json::to_vec_pretty(&active).map_err(io::Error::other)?;
+        fs::write(local_directory.join(FREEZES_FILENAME), contents)
+    }
+
+    /// When the freeze on the slot saved as `save_filename` ends, or `None`
+    /// if it is not frozen.
+    pub fn frozen_until(&self, save_filename: &str) -> Option<SystemTime> {
+        let until = *self.0.get(save_filename)?;
+        (until > unix_secs(SystemTime::now()))
+            .then(|| SystemTime::UNIX_EPOCH + Duration::from_secs(until))
+    }
+
+    pub fn freeze(&mut self, save_filename: &str, until: SystemTime) {
+        self.0.insert(save_filename.to_string(), unix_secs(until));
+    }
+
+    /// Returns whether the slot was frozen.
+    pub fn unfreeze(&mut self, save_filename: &str) -> bool {
+        self.0.remove(save_filename).is_some()
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Parses a local time written as `YYYY-MM-DD HH:MM`, or as `YYYY-MM-DD` for
+/// the start of that day.
+pub fn parse_local_time(text: &str) -> Option<SystemTime> {
+    let text = text.trim();
+    let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(SystemTime::from)
+}
+
+/// Writes `time` the way [`parse_local_time`] reads it.
+pub fn format_local_time(time: SystemTime) -> String {
+    DateTime::<Local>::from(time)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freezes_end_and_round_trip() {
+        let root = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let mut freezes = Freezes::default();
+        freezes.freeze("filech1_0", now + Duration::from_secs(3600));
+        freezes.freeze("filech1_1", now - Duration::from_secs(3600));
+        assert!(freezes.frozen_until("filech1_0").is_some());
+        assert_eq!(freezes.frozen_until("filech1_1"), None);
+        assert_eq!(freezes.frozen_until("filech1_2"), None);
+
+        freezes.save(root.path()).unwrap();
+        let mut loaded = Freezes::load(root.path()).unwrap();
+        assert!(loaded.frozen_until("filech1_0").is_some());
+        assert!(!loaded.unfreeze("filech1_1"));
+        assert!(loaded.unfreeze("filech1_0"));
+    }
+
+    #[test]
+    fn parses_dates_with_and_without_times() {
+        let day = parse_local_time("2025-06-01").unwrap();
+        let evening = parse_local_time("2025-06-01 18:30").unwrap();
+        assert_eq!(
+            evening.duration_since(day).unwrap(),
+            Duration::from_secs(18 * 3600 + 30 * 60)
+        );
+        assert_eq!(format_local_time(evening), "2025-06-01 18:30");
+        assert_eq!(parse_local_time("June 1st"), None);
+    }
+}
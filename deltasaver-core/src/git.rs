@@ -0,0 +1,175 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Keeping every backup as a commit in a git repository as well, see
+//! [`crate::store::SaveStore::with_git_directory`], so the full history can be
+//! browsed with any git tool and pushed to a private remote.
+//!
+//! The repository holds each slot's file, and the files backed up with it,
+//! under the names the game gives them, so checking out any commit and
+//! copying its files into the game's save directory restores that backup.
+//! Like the mirror, the files are neither compressed nor encrypted, so
+//! backing up fails while the backups are encrypted and a repository is set.
+
+use git2::{Commit, Cred, CredentialType, ErrorCode, PushOptions, RemoteCallbacks};
+use git2::{Repository, Signature};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Who commits are by when git has no `user.name` and `user.email` set.
+const FALLBACK_NAME: &str = "DELTASAVER";
+const FALLBACK_EMAIL: &str = "deltasaver@localhost";
+
+/// The remote [`push`] pushes to.
+pub const REMOTE: &str = "origin";
+
+fn git_error(error: git2::Error) -> io::Error {
+    io::Error::other(error.message().to_string())
+}
+
+/// The repository at `directory`, creating it if there is none.
+fn open_or_init(directory: &Path) -> io::Result<Repository> {
+    match Repository::open(directory) {
+        Ok(repository) => Ok(repository),
+        Err(error) if error.code() == ErrorCode::NotFound => {
+            fs::create_dir_all(directory)?;
+            Repository::init(directory).map_err(git_error)
+        }
+        Err(error) => Err(git_error(error)),
+    }
+}
+
+/// Writes `files`, as `(filename, contents)`, into the repository at
+/// `directory` and commits them with `message`, creating the repository if
+/// need be.
+pub fn commit(directory: &Path, files: &[(String, Vec<u8>)], message: &str) -> io::Result<()> {
+    let repository = open_or_init(directory)?;
+    let workdir = repository.workdir().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is a bare repository", directory.display()),
+        )
+    })?;
+    let mut index = repository.index().map_err(git_error)?;
+    for (filename, contents) in files {
+        fs::write(workdir.join(filename), contents)?;
+        index.add_path(Path::new(filename)).map_err(git_error)?;
+    }
+    index.write().map_err(git_error)?;
+    let tree = repository
+        .find_tree(index.write_tree().map_err(git_error)?)
+        .map_err(git_error)?;
+
+    let signature = match repository.signature() {
+        Ok(signature) => signature,
+        Err(_) => Signature::now(FALLBACK_NAME, FALLBACK_EMAIL).map_err(git_error)?,
+    };
+    let parent = match repository.head() {
+        Ok(head) => Some(head.peel_to_commit().map_err(git_error)?),
+        Err(error) if matches!(error.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => None,
+        Err(error) => return Err(git_error(error)),
+    };
+    let parents = parent.iter().collect::<Vec<&Commit>>();
+    repository
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .map_err(git_error)?;
+    Ok(())
+}
+
+/// Pushes the current branch of the repository at `directory` to its
+/// [`REMOTE`], authenticating with the SSH agent or git's credential helper,
+/// and returns the remote's URL.
+pub fn push(directory: &Path) -> io::Result<String> {
+    let repository = Repository::open(directory).map_err(git_error)?;
+    let head = repository.head().map_err(git_error)?;
+    let branch = head.name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "the branch name is not UTF-8")
+    })?;
+    let mut remote = repository.find_remote(REMOTE).map_err(|error| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "{}; add one with `git remote add {} <url>` in {}",
+                error.message(),
+                REMOTE,
+                directory.display()
+            ),
+        )
+    })?;
+    let url = remote.url().unwrap_or_default().to_string();
+
+    let config = repository.config().map_err(git_error)?;
+    let mut tried_agent = false;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| {
+        // libgit2 asks again whenever credentials are rejected, so the agent
+        // is only tried once.
+        if allowed.contains(CredentialType::SSH_KEY) && !tried_agent {
+            tried_agent = true;
+            return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            return Cred::credential_helper(&config, url, username);
+        }
+        Err(git2::Error::from_str("no credentials were accepted"))
+    });
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+    remote
+        .push(&[format!("{0}:{0}", branch)], Some(&mut options))
+        .map_err(git_error)?;
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+
+    #[test]
+    fn backups_are_committed_and_pushed() {
+        let root = tempfile::tempdir().unwrap();
+        let history = root.path().join("history");
+        let store = store_in(root.path())
+            .unwrap()
+            .with_git_directory(Some(history.clone()));
+
+        SyntheticSave::default()
+            .write_to(store.game_directory(), 0)
+            .unwrap();
+        store.backup(1, 0).unwrap();
+        SyntheticSave {
+            gold: 5,
+            ..Default::default()
+        }
+        .write_to(store.game_directory(), 0)
+        .unwrap();
+        store
+            .backup_with_note(1, 0, Some("before the fountain"))
+            .unwrap();
+
+        let repository = Repository::open(&history).unwrap();
+        let latest = repository.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(latest.summary(), Some("before the fountain"));
+        assert_eq!(latest.parent_count(), 1);
+        assert_eq!(
+            fs::read(history.join("filech1_0")).unwrap(),
+            fs::read(store.game_directory().join("filech1_0")).unwrap()
+        );
+
+        let remote = root.path().join("remote.git");
+        Repository::init_bare(&remote).unwrap();
+        repository.remote(REMOTE, remote.to_str().unwrap()).unwrap();
+        push(&history).unwrap();
+        let pushed = Repository::open_bare(&remote).unwrap();
+        let branch = repository.head().unwrap().name().unwrap().to_string();
+        assert_eq!(pushed.refname_to_id(&branch).unwrap(), latest.id());
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! A record of every automatic migration of a backup directory, so users can
+//! tell why its layout changed between versions.
+//!
+//! The record is kept in `history.json` in the backup directory, oldest
+//! first:
+//!
+//! ```json
+//! [{ "time": 1767225600, "migration": "object_store", "count": 12 }]
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const HISTORY_FILENAME: &str = "history.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Migration {
+    /// Backups' contents moved into the `objects` directory, shared by hash.
+    ObjectStore,
+    /// Objects stored uncompressed were compressed.
+    Recompression,
+    /// Objects were encrypted with a passphrase.
+    Encryption,
+    /// Backups named by the time they were taken were renamed by ULID.
+    LegacyNames,
+}
+
+impl fmt::Display for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Migration::ObjectStore => "moved backup contents into the shared object store",
+            Migration::Recompression => "compressed stored backups with zstd",
+            Migration::Encryption => "encrypted stored backups with a passphrase",
+            Migration::LegacyNames => "renamed backups from the old timestamp naming",
+        })
+    }
+}
+
+/// One migration that happened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub migration: Migration,
+    /// How many files it changed.
+    pub count: usize,
+}
+
+impl Entry {
+    pub fn time(&self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(self.time)
+    }
+}
+
+/// The migrations recorded in `local_directory`, oldest first.
+pub fn load(local_directory: &Path) -> io::Result<Vec<Entry>> {
+    match fs::read(local_directory.join(HISTORY_FILENAME)) {
+        Ok(contents) => serde_json::from_slice(&contents)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error),
+    }
+}
+
+/// Records that `migration` just changed `count` files in `local_directory`.
+pub fn record(local_directory: &Path, migration: Migration, count: usize) -> io::Result<()> {
+    let mut entries = load(local_directory)?;
+    entries.push(Entry {
+        time: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        migration,
+        count,
+    });
+    let contents = serde_json::to_vec_pretty(&entries).map_err(io::Error::other)?;
+    fs::write(local_directory.join(HISTORY_FILENAME), contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_are_appended_in_order() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(load(root.path()).unwrap().is_empty());
+        record(root.path(), Migration::ObjectStore, 3).unwrap();
+        record(root.path(), Migration::Recompression, 1).unwrap();
+        let entries = load(root.path()).unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.migration, entry.count))
+                .collect::<Vec<_>>(),
+            vec![(Migration::ObjectStore, 3), (Migration::Recompression, 1)]
+        );
+    }
+}
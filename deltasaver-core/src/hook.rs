@@ -0,0 +1,97 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Hands each finished snapshot to the user's own offsite pipeline, as
+//! configured per profile under `[hooks]` in `config.toml`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::ExportHook;
+use crate::export::{self, ExportError};
+
+/// Set to the exported archive's path when running a hook's command.
+pub const ARCHIVE_VARIABLE: &str = "DELTASAVER_ARCHIVE";
+
+/// Archives `snapshot_directory` next to itself and hands the archive to
+/// `hook`: first copying it into [`ExportHook::copy_to`], then running
+/// [`ExportHook::command`]. Returns the archive's path.
+pub fn export_snapshot(
+    hook: &ExportHook,
+    snapshot_directory: &Path,
+) -> Result<PathBuf, ExportError> {
+    let mut archive_name = snapshot_directory.as_os_str().to_owned();
+    archive_name.push(".zip");
+    let archive_path = PathBuf::from(archive_name);
+    export::write_directory_archive(&archive_path, snapshot_directory)?;
+
+    if let Some(destination) = &hook.copy_to {
+        fs::create_dir_all(destination)?;
+        if let Some(filename) = archive_path.file_name() {
+            fs::copy(&archive_path, destination.join(filename))?;
+        }
+    }
+
+    if let Some(command) = &hook.command {
+        let status = shell(command)
+            .env(ARCHIVE_VARIABLE, &archive_path)
+            .status()?;
+        if !status.success() {
+            return Err(ExportError::Io(format!(
+                "export command `{}` failed: {}",
+                command, status
+            )));
+        }
+    }
+
+    Ok(archive_path)
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_are_archived_copied_and_handed_to_the_command() {
+        let root = tempfile::tempdir().unwrap();
+        let snapshot_directory = root.path().join("update_1_to_2_0");
+        fs::create_dir_all(&snapshot_directory).unwrap();
+        fs::write(snapshot_directory.join("filech1_0"), "save").unwrap();
+
+        let received = root.path().join("received");
+        let hook = ExportHook {
+            copy_to: Some(root.path().join("offsite")),
+            command: cfg!(unix)
+                .then(|| format!("echo \"$DELTASAVER_ARCHIVE\" > '{}'", received.display())),
+        };
+        let archive_path = export_snapshot(&hook, &snapshot_directory).unwrap();
+        assert_eq!(archive_path, root.path().join("update_1_to_2_0.zip"));
+        assert!(root.path().join("offsite/update_1_to_2_0.zip").is_file());
+        if cfg!(unix) {
+            assert_eq!(
+                fs::read_to_string(received).unwrap().trim(),
+                archive_path.display().to_string()
+            );
+        }
+
+        let failing = ExportHook {
+            copy_to: None,
+            command: Some("exit 3".to_string()),
+        };
+        assert!(export_snapshot(&failing, &snapshot_directory).is_err());
+    }
+}
@@ -0,0 +1,586 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Merging backups into the backup directory from a zip archive, whether the
+//! archive is a `.dsave` written by [`crate::export`] or a plain zip of
+//! `filech*` files from someone's save directory, or from save files and
+//! directories of them, as dropped onto the window.
+//!
+//! Each file in an archive is taken to be the slot its manifest entry says,
+//! with the name, note, and tags it gives, or failing that, the slot its name
+//! says. Files that are neither are ignored, so a zipped save directory with
+//! `dr.ini` in it imports fine, and files said to be of a slot the game does
+//! not have are rejected. Backups already in the store, by slot and
+//! contents, are not added again.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use ulid::Ulid;
+use zip::ZipArchive;
+
+use crate::export::{ARCHIVE_EXTENSION, FORMAT_VERSION, MANIFEST_NAME, Manifest};
+use crate::profile::backup_id;
+use crate::save::{Chapter, Slot, hash_contents};
+use crate::store::SaveStore;
+use crate::transfer::{MAX_SAVE_SIZE, looks_like_save};
+
+#[derive(Debug, Clone)]
+pub enum ImportError {
+    Io(String),
+    Zip(String),
+    /// The manifest could not be read.
+    Manifest(String),
+    /// The archive is of the saves of the profile named here, not the
+    /// store's.
+    OtherProfile(String),
+    /// The archive was written in this newer version of the format, see
+    /// [`crate::export::FORMAT_VERSION`].
+    NewerVersion(u32),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(message) | ImportError::Zip(message) => f.write_str(message),
+            ImportError::Manifest(message) => write!(f, "the manifest is invalid: {}", message),
+            ImportError::OtherProfile(profile) => {
+                write!(f, "the archive holds saves of {}, not this game", profile)
+            }
+            ImportError::NewerVersion(version) => write!(
+                f,
+                "the archive was written by a newer DELTASAVER (format version {}); update to import it",
+                version
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for ImportError {
+    fn from(error: io::Error) -> Self {
+        ImportError::Io(error.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for ImportError {
+    fn from(error: zip::result::ZipError) -> Self {
+        ImportError::Zip(error.to_string())
+    }
+}
+
+/// What importing an archive did.
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    /// The paths of the new backups.
+    pub imported: Vec<PathBuf>,
+    /// How many saves in the archive were already backed up.
+    pub duplicates: usize,
+    /// Files in the archive that looked like saves but were not imported,
+    /// with why.
+    pub rejected: Vec<(String, String)>,
+    /// How many backups were imported without the name, note, or tags the
+    /// archive gave them, because the backups are encrypted and the index
+    /// that would keep them is not.
+    pub unlabeled: usize,
+    /// Files whose name does not say which slot they are, to be imported with
+    /// [`import_file`] once the user does.
+    pub unrecognized: Vec<PathBuf>,
+}
+
+/// Adds saves to a store, checking each one and skipping those already
+/// backed up.
+struct Importer<'a> {
+    store: &'a SaveStore,
+    known: HashSet<(Chapter, Slot, String)>,
+    summary: ImportSummary,
+}
+
+impl<'a> Importer<'a> {
+    fn new(store: &'a SaveStore) -> Result<Self, ImportError> {
+        let known = store
+            .load()
+            .map_err(|error| ImportError::Io(error.to_string()))?
+            .local
+            .into_iter()
+            .filter_map(|save| Some((save.chapter, save.slot, save.hash?)))
+            .collect();
+        Ok(Self {
+            store,
+            known,
+            summary: ImportSummary::default(),
+        })
+    }
+
+    /// Imports `contents`, read from the file named `name`, from `origin`.
+    /// `contents` may be one byte longer than any save, to tell that it was
+    /// cut off.
+    fn add(&mut self, name: String, contents: &[u8], origin: Origin) -> io::Result<()> {
+        // Manifests are trusted no more than filenames, which are only
+        // parsed into slots the game has.
+        let profile = self.store.profile();
+        if !(1..=profile.chapter_count).contains(&origin.chapter)
+            || profile.is_extra_slot(origin.slot)
+        {
+            self.summary.rejected.push((
+                name,
+                format!(
+                    "is for Chapter {} Slot {}, which {} does not have",
+                    origin.chapter,
+                    origin.slot + 1,
+                    profile.name
+                ),
+            ));
+            return Ok(());
+        }
+        if contents.len() > MAX_SAVE_SIZE {
+            self.summary
+                .rejected
+                .push((name, "too large to be a save file".to_string()));
+            return Ok(());
+        }
+        if !looks_like_save(contents) {
+            self.summary
+                .rejected
+                .push((name, "does not look like a save file".to_string()));
+            return Ok(());
+        }
+        let hash = hash_contents(contents);
+        if origin
+            .hash
+            .as_ref()
+            .is_some_and(|expected| *expected != hash)
+        {
+            self.summary.rejected.push((
+                name,
+                "is damaged: its contents do not match its hash".to_string(),
+            ));
+            return Ok(());
+        }
+        if !self.known.insert((origin.chapter, origin.slot, hash)) {
+            self.summary.duplicates += 1;
+            return Ok(());
+        }
+
+        let backup_path = match &origin.id {
+            Some(id) => self
+                .store
+                .import_with_id(origin.chapter, origin.slot, contents, id)?,
+            None => self.store.import(origin.chapter, origin.slot, contents)?,
+        };
+        let labeled = origin.name.is_some() || origin.note.is_some() || !origin.tags.is_empty();
+        if labeled && self.store.is_encrypted() {
+            self.summary.unlabeled += 1;
+        } else if let Some(id) = backup_path.file_name().and_then(|name| name.to_str()) {
+            let index = self.store.index()?;
+            if let Some(name) = &origin.name {
+                index.set_name(id, name)?;
+            }
+            if let Some(note) = &origin.note {
+                index.set_note(id, note)?;
+            }
+            for tag in &origin.tags {
+                index.tag(id, tag)?;
+            }
+        }
+        self.summary.imported.push(backup_path);
+        Ok(())
+    }
+
+    /// Imports the file at `path` from `origin`.
+    fn add_file(&mut self, path: &Path, origin: Origin) -> io::Result<()> {
+        let mut contents = Vec::new();
+        File::open(path)?
+            .take(MAX_SAVE_SIZE as u64 + 1)
+            .read_to_end(&mut contents)?;
+        self.add(path.display().to_string(), &contents, origin)
+    }
+}
+
+/// Imports every save in the zip archive at `path` into `store`.
+pub fn import_archive(path: &Path, store: &SaveStore) -> Result<ImportSummary, ImportError> {
+    let mut zip = ZipArchive::new(File::open(path)?)?;
+
+    let manifest = match zip.by_name(MANIFEST_NAME) {
+        Ok(mut file) => {
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents)?;
+            Some(
+                serde_json::from_slice::<Manifest>(&contents)
+                    .map_err(|error| ImportError::Manifest(error.to_string()))?,
+            )
+        }
+        Err(zip::result::ZipError::FileNotFound) => None,
+        Err(error) => return Err(error.into()),
+    };
+    if let Some(manifest) = &manifest {
+        if manifest.version > FORMAT_VERSION {
+            return Err(ImportError::NewerVersion(manifest.version));
+        }
+        if !manifest.profile.is_empty()
+            && !manifest.profile.eq_ignore_ascii_case(&store.profile().name)
+        {
+            return Err(ImportError::OtherProfile(manifest.profile.clone()));
+        }
+    }
+
+    let mut importer = Importer::new(store)?;
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index)?;
+        let name = file.name().to_string();
+        if file.is_dir() || name == MANIFEST_NAME {
+            continue;
+        }
+        let Some(origin) = origin(store, manifest.as_ref(), &name) else {
+            continue;
+        };
+
+        let mut contents = Vec::new();
+        file.by_ref()
+            .take(MAX_SAVE_SIZE as u64 + 1)
+            .read_to_end(&mut contents)?;
+        importer.add(name, &contents, origin)?;
+    }
+    Ok(importer.summary)
+}
+
+/// Imports whatever is at `path`: a zip archive, as [`import_archive`] does,
+/// a save file, or every save file directly inside a directory. A lone file
+/// whose name does not say its slot is left in
+/// [`ImportSummary::unrecognized`].
+pub fn import_path(path: &Path, store: &SaveStore) -> Result<ImportSummary, ImportError> {
+    if path.extension().is_some_and(|extension| {
+        extension.eq_ignore_ascii_case("zip") || extension.eq_ignore_ascii_case(ARCHIVE_EXTENSION)
+    }) {
+        return import_archive(path, store);
+    }
+
+    let mut importer = Importer::new(store)?;
+    if path.is_dir() {
+        let mut files = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        files.sort();
+        for file in files {
+            let Some(name) = file.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if file.is_file()
+                && let Some(origin) = origin(store, None, name)
+            {
+                importer.add_file(&file, origin)?;
+            }
+        }
+    } else {
+        let origin = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| origin(store, None, name));
+        match origin {
+            Some(origin) => importer.add_file(path, origin)?,
+            None => importer.summary.unrecognized.push(path.to_path_buf()),
+        }
+    }
+    Ok(importer.summary)
+}
+
+/// Imports the save file at `path` as a backup of `chapter`'s `slot`, whatever
+/// it is named.
+pub fn import_file(
+    path: &Path,
+    chapter: Chapter,
+    slot: Slot,
+    store: &SaveStore,
+) -> Result<ImportSummary, ImportError> {
+    let mut importer = Importer::new(store)?;
+    importer.add_file(
+        path,
+        Origin {
+            chapter,
+            slot,
+            ..Default::default()
+        },
+    )?;
+    Ok(importer.summary)
+}
+
+/// Where a file in an archive came from.
+#[derive(Default)]
+struct Origin {
+    chapter: Chapter,
+    slot: Slot,
+    hash: Option<String>,
+    /// Its backup ID, if it has one worth keeping.
+    id: Option<String>,
+    name: Option<String>,
+    note: Option<String>,
+    tags: BTreeSet<String>,
+}
+
+/// Where the file named `name` in an archive came from, or `None` if it is
+/// not a save of `store`'s game.
+fn origin(store: &SaveStore, manifest: Option<&Manifest>, name: &str) -> Option<Origin> {
+    let filename = name.rsplit('/').next().unwrap_or(name);
+    let id = backup_id(filename)
+        .filter(|id| Ulid::from_string(id).is_ok())
+        .map(str::to_string);
+    let profile = store.profile();
+
+    if let Some(entry) =
+        manifest.and_then(|manifest| manifest.saves.iter().find(|entry| entry.file == name))
+    {
+        return Some(Origin {
+            chapter: entry.chapter,
+            slot: entry.slot,
+            hash: entry.hash.clone(),
+            id: entry
+                .id
+                .clone()
+                .filter(|id| Ulid::from_string(id).is_ok())
+                .or(id),
+            name: entry.name.clone(),
+            note: entry.note.clone(),
+            tags: entry.tags.clone(),
+        });
+    }
+    if let Some((chapter, slot, hash)) = profile.parse_backup_filename(filename) {
+        return Some(Origin {
+            chapter,
+            slot,
+            hash: Some(hash),
+            id,
+            ..Default::default()
+        });
+    }
+    let (chapter, slot) = profile.parse_save_filename(filename)?;
+    Some(Origin {
+        chapter,
+        slot,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export;
+    use crate::fixtures::{SyntheticSave, store_in};
+    use std::fs;
+    use std::io::Write;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    #[test]
+    fn exported_and_plain_archives_merge_without_duplicates() {
+        let root = tempfile::tempdir().unwrap();
+        let mine = store_in(&root.path().join("mine")).unwrap();
+        let theirs = store_in(&root.path().join("theirs")).unwrap();
+        let shared = SyntheticSave::default().to_bytes();
+        let only_theirs = SyntheticSave {
+            gold: 99,
+            ..Default::default()
+        }
+        .to_bytes();
+        mine.import(1, 0, &shared).unwrap();
+        theirs.import(1, 0, &shared).unwrap();
+        let kept = theirs.import(2, 1, &only_theirs).unwrap();
+        let kept_id = kept.file_name().unwrap().to_str().unwrap();
+        let index = theirs.index().unwrap();
+        index.set_note(kept_id, "before Spamton NEO").unwrap();
+        index.tag(kept_id, "boss").unwrap();
+
+        let exported = root.path().join("theirs.dsave");
+        export::write_backup_archive(&exported, &theirs, &theirs.load().unwrap().local).unwrap();
+        let summary = import_path(&exported, &mine).unwrap();
+        assert_eq!(summary.imported.len(), 1);
+        assert_eq!(summary.duplicates, 1);
+        assert_eq!(
+            summary.imported[0].file_name(),
+            kept.file_name(),
+            "the backup keeps its ID"
+        );
+        assert_eq!(mine.read_backup(&summary.imported[0]).unwrap(), only_theirs);
+        let entry = mine.index().unwrap().get(kept_id).unwrap().unwrap();
+        assert_eq!(entry.note.as_deref(), Some("before Spamton NEO"));
+        assert_eq!(entry.tags, BTreeSet::from(["boss".to_string()]));
+
+        let plain = root.path().join("plain.zip");
+        let mut zip = ZipWriter::new(File::create(&plain).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.start_file("filech1_2", options).unwrap();
+        zip.write_all(&only_theirs).unwrap();
+        zip.start_file("filech1_1", options).unwrap();
+        zip.write_all(&[0xff, 0xfe]).unwrap();
+        zip.start_file("dr.ini", options).unwrap();
+        zip.write_all(b"[settings]\n").unwrap();
+        zip.finish().unwrap();
+        let summary = import_archive(&plain, &mine).unwrap();
+        assert_eq!(summary.imported.len(), 1);
+        assert_eq!(summary.rejected.len(), 1);
+        assert_eq!(summary.rejected[0].0, "filech1_1");
+        assert_eq!(mine.load().unwrap().local.len(), 3);
+    }
+
+    /// Writes a `.dsave` at `path` with `manifest` and `files`.
+    fn write_dsave(path: &Path, manifest: serde_json::Value, files: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.start_file(MANIFEST_NAME, options).unwrap();
+        zip.write_all(manifest.to_string().as_bytes()).unwrap();
+        for (name, contents) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn manifest_entries_for_slots_the_game_lacks_are_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(&root.path().join("mine")).unwrap();
+        let contents = SyntheticSave::default().to_bytes();
+        let entry = |file: &str, chapter: Chapter, slot: Slot| serde_json::json!({ "file": file, "chapter": chapter, "slot": slot });
+        let archive = root.path().join("crafted.dsave");
+        write_dsave(
+            &archive,
+            serde_json::json!({
+                "profile": "DELTARUNE",
+                "saves": [
+                    entry("saves/a", 0, 0),
+                    entry("saves/b", 99, 0),
+                    entry("saves/c", 1, 3),
+                    entry("saves/d", 1, 0),
+                ],
+            }),
+            &[
+                ("saves/a", &contents),
+                ("saves/b", &contents),
+                ("saves/c", &contents),
+                ("saves/d", &contents),
+            ],
+        );
+
+        let summary = import_archive(&archive, &store).unwrap();
+        assert_eq!(summary.imported.len(), 1);
+        assert_eq!(
+            summary
+                .rejected
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            ["saves/a", "saves/b", "saves/c"]
+        );
+    }
+
+    #[test]
+    fn archives_of_other_games_or_newer_versions_are_refused() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(&root.path().join("mine")).unwrap();
+        let contents = SyntheticSave::default().to_bytes();
+        let archive = root.path().join("other.dsave");
+        let manifest = |version: u32, profile: &str| {
+            serde_json::json!({
+                "version": version,
+                "profile": profile,
+                "saves": [{ "file": "saves/a", "chapter": 1, "slot": 0 }],
+            })
+        };
+
+        write_dsave(
+            &archive,
+            manifest(1, "UNDERTALE"),
+            &[("saves/a", &contents)],
+        );
+        assert!(matches!(
+            import_archive(&archive, &store),
+            Err(ImportError::OtherProfile(profile)) if profile == "UNDERTALE"
+        ));
+        write_dsave(
+            &archive,
+            manifest(99, "DELTARUNE"),
+            &[("saves/a", &contents)],
+        );
+        assert!(matches!(
+            import_archive(&archive, &store),
+            Err(ImportError::NewerVersion(99))
+        ));
+        write_dsave(
+            &archive,
+            manifest(0, "deltarune"),
+            &[("saves/a", &contents)],
+        );
+        assert_eq!(import_archive(&archive, &store).unwrap().imported.len(), 1);
+        assert_eq!(store.load().unwrap().local.len(), 1);
+    }
+
+    #[test]
+    fn labels_dropped_for_encrypted_backups_are_counted() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(&root.path().join("mine"))
+            .unwrap()
+            .encrypt("hunter2")
+            .unwrap();
+        let archive = root.path().join("labeled.dsave");
+        write_dsave(
+            &archive,
+            serde_json::json!({
+                "profile": "DELTARUNE",
+                "saves": [
+                    { "file": "saves/a", "chapter": 1, "slot": 0, "note": "before Spamton NEO" },
+                    { "file": "saves/b", "chapter": 1, "slot": 1 },
+                ],
+            }),
+            &[
+                ("saves/a", &SyntheticSave::default().to_bytes()),
+                ("saves/b", &SyntheticSave::default().to_bytes()),
+            ],
+        );
+
+        let summary = import_archive(&archive, &store).unwrap();
+        assert_eq!(summary.imported.len(), 2);
+        assert_eq!(summary.unlabeled, 1);
+        assert!(
+            store
+                .index()
+                .unwrap()
+                .entries()
+                .unwrap()
+                .values()
+                .all(|entry| entry.note.is_none())
+        );
+    }
+
+    #[test]
+    fn dropped_files_and_directories_are_imported() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(&root.path().join("mine")).unwrap();
+        let dropped = root.path().join("dropped");
+        fs::create_dir_all(&dropped).unwrap();
+        SyntheticSave::default().write_to(&dropped, 0).unwrap();
+        SyntheticSave {
+            gold: 5,
+            ..Default::default()
+        }
+        .write_to(&dropped, 1)
+        .unwrap();
+        fs::write(dropped.join("dr.ini"), "[settings]\n").unwrap();
+
+        let summary = import_path(&dropped, &store).unwrap();
+        assert_eq!(summary.imported.len(), 2);
+        assert!(summary.rejected.is_empty());
+
+        let renamed = root.path().join("my save.txt");
+        let contents = SyntheticSave {
+            gold: 7,
+            ..Default::default()
+        }
+        .to_bytes();
+        fs::write(&renamed, &contents).unwrap();
+        let summary = import_path(&renamed, &store).unwrap();
+        assert!(summary.imported.is_empty());
+        assert_eq!(summary.unrecognized, vec![renamed.clone()]);
+        let summary = import_file(&renamed, 2, 2, &store).unwrap();
+        assert_eq!(store.read_backup(&summary.imported[0]).unwrap(), contents);
+        assert_eq!(import_file(&renamed, 2, 2, &store).unwrap().duplicates, 1);
+    }
+}
@@ -0,0 +1,349 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! An SQLite index of the backups, in `index.sqlite` in the backup directory,
+//! holding what their filenames cannot: names, notes, tags, pins, and sizes,
+//! the last of which would otherwise mean reading every object to list the
+//! backups.
+//!
+//! The backups themselves remain the record of which backups exist, so the
+//! index can be deleted at any time and is rebuilt, less names, notes, tags,
+//! and pins, the next time the backups are listed. Entries are keyed by
+//! [`SaveFile::id`] and outlive a trip to the trash.
+//!
+//! The index is not encrypted, so names, notes, and tags cannot be set while
+//! the backups are, see [`crate::encryption`].
+//!
+//! [`SaveFile::id`]: crate::save::SaveFile::id
+
+use rusqlite::{Connection, OptionalExtension, params};
+use std::collections::{BTreeSet, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::encryption;
+use crate::save::{Chapter, Slot};
+
+const INDEX_FILENAME: &str = "index.sqlite";
+
+/// The schema, one migration per version, applied in order to bring an
+/// older index up to date.
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE backups (
+        id TEXT PRIMARY KEY,
+        chapter INTEGER NOT NULL,
+        slot INTEGER NOT NULL,
+        hash TEXT NOT NULL,
+        created INTEGER,
+        size INTEGER,
+        note TEXT
+    );
+    CREATE TABLE tags (
+        id TEXT NOT NULL REFERENCES backups (id) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        PRIMARY KEY (id, tag)
+    );
+",
+    "
+    ALTER TABLE backups ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+",
+    "
+    ALTER TABLE backups ADD COLUMN name TEXT;
+",
+];
+
+/// What the index holds about one backup.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Entry {
+    pub chapter: Chapter,
+    pub slot: Slot,
+    pub hash: String,
+    /// Seconds since the epoch at which the backup was taken.
+    pub created: Option<u64>,
+    /// In bytes, before compression.
+    pub size: Option<u64>,
+    /// What the user renamed the backup to, shown in place of its note or
+    /// hash.
+    pub name: Option<String>,
+    pub note: Option<String>,
+    pub tags: BTreeSet<String>,
+    /// Pinned backups are never pruned or deleted in bulk.
+    pub pinned: bool,
+}
+
+impl Entry {
+    /// What to call the backup by: its name, or failing that, its note.
+    pub fn label(&self) -> Option<&str> {
+        self.name.as_deref().or(self.note.as_deref())
+    }
+}
+
+pub struct Index {
+    connection: Connection,
+    /// The backup directory, which may be encrypted.
+    local_directory: PathBuf,
+}
+
+fn sql_error(error: rusqlite::Error) -> io::Error {
+    io::Error::other(error.to_string())
+}
+
+impl Index {
+    /// Opens the index in `local_directory`, creating it or bringing its
+    /// schema up to date as needed.
+    pub fn open(local_directory: &Path) -> io::Result<Self> {
+        let connection =
+            Connection::open(local_directory.join(INDEX_FILENAME)).map_err(sql_error)?;
+        connection
+            .pragma_update(None, "foreign_keys", true)
+            .map_err(sql_error)?;
+        let version: usize = connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(sql_error)?;
+        for (applied, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            connection.execute_batch(migration).map_err(sql_error)?;
+            connection
+                .pragma_update(None, "user_version", applied + 1)
+                .map_err(sql_error)?;
+        }
+        Ok(Self {
+            connection,
+            local_directory: local_directory.to_path_buf(),
+        })
+    }
+
+    /// Adds the backup `id`, or updates its size if it is already indexed,
+    /// keeping its name, note, tags, and pin. Fails if `entry` has a name,
+    /// note, or tags and the backups are encrypted.
+    pub fn record(&self, id: &str, entry: &Entry) -> io::Result<()> {
+        if entry.name.is_some() || entry.note.is_some() || !entry.tags.is_empty() {
+            encryption::check_unencrypted(&self.local_directory, "names, notes, and tags")?;
+        }
+        self.connection
+            .execute(
+                "INSERT INTO backups (id, chapter, slot, hash, created, size, note, pinned, name)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT (id) DO UPDATE SET size = excluded.size",
+                params![
+                    id,
+                    entry.chapter,
+                    entry.slot,
+                    entry.hash,
+                    entry.created,
+                    entry.size,
+                    entry.note,
+                    entry.pinned,
+                    entry.name
+                ],
+            )
+            .map_err(sql_error)?;
+        for tag in &entry.tags {
+            self.tag(id, tag)?;
+        }
+        Ok(())
+    }
+
+    /// Forgets the backup `id`, along with its name, note, tags, and pin.
+    pub fn remove(&self, id: &str) -> io::Result<()> {
+        self.connection
+            .execute("DELETE FROM backups WHERE id = ?1", [id])
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> io::Result<Option<Entry>> {
+        let entry = self
+            .connection
+            .query_row(
+                "SELECT chapter, slot, hash, created, size, note, pinned, name FROM backups
+                 WHERE id = ?1",
+                [id],
+                |row| entry_from_row(row, 0),
+            )
+            .optional()
+            .map_err(sql_error)?;
+        let Some(mut entry) = entry else {
+            return Ok(None);
+        };
+        let mut statement = self
+            .connection
+            .prepare("SELECT tag FROM tags WHERE id = ?1")
+            .map_err(sql_error)?;
+        entry.tags = statement
+            .query_map([id], |row| row.get(0))
+            .and_then(Iterator::collect)
+            .map_err(sql_error)?;
+        Ok(Some(entry))
+    }
+
+    /// Every indexed backup, by ID.
+    pub fn entries(&self) -> io::Result<HashMap<String, Entry>> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT id, chapter, slot, hash, created, size, note, pinned, name FROM backups",
+            )
+            .map_err(sql_error)?;
+        let mut entries = statement
+            .query_map([], |row| Ok((row.get(0)?, entry_from_row(row, 1)?)))
+            .and_then(Iterator::collect::<rusqlite::Result<HashMap<String, Entry>>>)
+            .map_err(sql_error)?;
+        let mut statement = self
+            .connection
+            .prepare("SELECT id, tag FROM tags")
+            .map_err(sql_error)?;
+        let tags = statement
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))
+            .and_then(Iterator::collect::<rusqlite::Result<Vec<_>>>)
+            .map_err(sql_error)?;
+        for (id, tag) in tags {
+            if let Some(entry) = entries.get_mut(&id) {
+                entry.tags.insert(tag);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Sets or, if `note` is empty, clears the note on the backup `id`,
+    /// returning whether it is indexed. Only clearing works while the
+    /// backups are encrypted.
+    pub fn set_note(&self, id: &str, note: &str) -> io::Result<bool> {
+        let note = Some(note.trim()).filter(|note| !note.is_empty());
+        if note.is_some() {
+            encryption::check_unencrypted(&self.local_directory, "notes")?;
+        }
+        let updated = self
+            .connection
+            .execute(
+                "UPDATE backups SET note = ?2 WHERE id = ?1",
+                params![id, note],
+            )
+            .map_err(sql_error)?;
+        Ok(updated > 0)
+    }
+
+    /// Renames or, if `name` is empty, unnames the backup `id`, returning
+    /// whether it is indexed. Only unnaming works while the backups are
+    /// encrypted.
+    pub fn set_name(&self, id: &str, name: &str) -> io::Result<bool> {
+        let name = Some(name.trim()).filter(|name| !name.is_empty());
+        if name.is_some() {
+            encryption::check_unencrypted(&self.local_directory, "names")?;
+        }
+        let updated = self
+            .connection
+            .execute(
+                "UPDATE backups SET name = ?2 WHERE id = ?1",
+                params![id, name],
+            )
+            .map_err(sql_error)?;
+        Ok(updated > 0)
+    }
+
+    /// Pins or unpins the backup `id`, returning whether it is indexed.
+    pub fn set_pinned(&self, id: &str, pinned: bool) -> io::Result<bool> {
+        let updated = self
+            .connection
+            .execute(
+                "UPDATE backups SET pinned = ?2 WHERE id = ?1",
+                params![id, pinned],
+            )
+            .map_err(sql_error)?;
+        Ok(updated > 0)
+    }
+
+    /// Tags the backup `id` with `tag`, which must be indexed. Fails while
+    /// the backups are encrypted.
+    pub fn tag(&self, id: &str, tag: &str) -> io::Result<()> {
+        encryption::check_unencrypted(&self.local_directory, "tags")?;
+        self.connection
+            .execute(
+                "INSERT OR IGNORE INTO tags (id, tag) VALUES (?1, ?2)",
+                [id, tag.trim()],
+            )
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    /// Removes `tag` from the backup `id`, returning whether it had it.
+    pub fn untag(&self, id: &str, tag: &str) -> io::Result<bool> {
+        let removed = self
+            .connection
+            .execute(
+                "DELETE FROM tags WHERE id = ?1 AND tag = ?2",
+                [id, tag.trim()],
+            )
+            .map_err(sql_error)?;
+        Ok(removed > 0)
+    }
+}
+
+/// The [`Entry`] in the columns of `row` from `start` on, without its tags.
+fn entry_from_row(row: &rusqlite::Row, start: usize) -> rusqlite::Result<Entry> {
+    Ok(Entry {
+        chapter: row.get(start)?,
+        slot: row.get(start + 1)?,
+        hash: row.get(start + 2)?,
+        created: row.get(start + 3)?,
+        size: row.get(start + 4)?,
+        note: row.get(start + 5)?,
+        tags: BTreeSet::new(),
+        pinned: row.get(start + 6)?,
+        name: row.get(start + 7)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+
+    use std::fs;
+
+    #[test]
+    fn notes_and_tags_follow_backups_and_the_index_rebuilds() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        SyntheticSave::default()
+            .write_to(store.game_directory(), 0)
+            .unwrap();
+        let backup_path = store
+            .backup_with_note(1, 0, Some("before the fountain"))
+            .unwrap();
+        let id = backup_path.file_name().unwrap().to_str().unwrap();
+
+        let index = store.index().unwrap();
+        assert!(index.set_note(id, "  ").unwrap());
+        assert_eq!(index.get(id).unwrap().unwrap().note, None);
+        assert!(index.set_note(id, "after the fountain").unwrap());
+        assert!(index.set_name(id, "Queen's mansion").unwrap());
+        index.tag(id, "pacifist").unwrap();
+        index.tag(id, "pacifist").unwrap();
+        let entry = index.get(id).unwrap().unwrap();
+        assert_eq!(entry.note.as_deref(), Some("after the fountain"));
+        assert_eq!(entry.label(), Some("Queen's mansion"));
+        assert!(index.set_name(id, "").unwrap());
+        assert_eq!(
+            index.get(id).unwrap().unwrap().label(),
+            Some("after the fountain")
+        );
+        assert_eq!(entry.tags, BTreeSet::from(["pacifist".to_string()]));
+        assert!(!entry.pinned);
+        assert!(index.set_pinned(id, true).unwrap());
+        assert!(index.get(id).unwrap().unwrap().pinned);
+
+        store.delete(&backup_path).unwrap();
+        let trashed = &store.trash().unwrap()[0];
+        assert!(store.index().unwrap().get(id).unwrap().is_some());
+        store.purge(&trashed.path).unwrap();
+        assert!(store.index().unwrap().entries().unwrap().is_empty());
+
+        let backup_path = store.backup(1, 0).unwrap();
+        let size = store.load().unwrap().local[0].size;
+        fs::remove_file(root.path().join("local").join(INDEX_FILENAME)).unwrap();
+        assert_eq!(store.load().unwrap().local[0].size, size);
+        let id = backup_path.file_name().unwrap().to_str().unwrap();
+        assert_eq!(store.index().unwrap().get(id).unwrap().unwrap().size, size);
+        assert!(!store.index().unwrap().untag(id, "pacifist").unwrap());
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! A save's parsed fields and flags as a JSON document, for tools and
+//! spreadsheets that would rather not know the save format.
+//!
+//! The document looks like:
+//!
+//! ```json
+//! {
+//!   "chapter": 2,
+//!   "name": "KRIS",
+//!   "room": 42,
+//!   "room_name": null,
+//!   "play_time_seconds": 5405.0,
+//!   "plot": 0,
+//!   "gold": 250,
+//!   "lv": 3,
+//!   "hp": 120,
+//!   "max_hp": 160,
+//!   "route": "Normal",
+//!   "party": [{ "character": 1, "name": "Kris", "weapon": 2, "armor": [0, 5] }],
+//!   "recruited": [3, 7],
+//!   "recruits": { "recruited": 2, "total": 12 },
+//!   "key_items": 4,
+//!   "completion": 0.2,
+//!   "flags": [0, 1, 0.5, ...]
+//! }
+//! ```
+//!
+//! `recruits` is `null` for chapters whose recruits are not known. Flags are
+//! numbers, except any the game wrote that are not, which are kept as
+//! strings.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::format;
+use crate::rooms::RoomNames;
+use crate::save::Chapter;
+use crate::summary::SaveSummary;
+
+#[derive(Serialize)]
+struct Document<'a> {
+    chapter: Chapter,
+    name: &'a str,
+    room: u32,
+    room_name: Option<&'a str>,
+    play_time_seconds: f64,
+    plot: Value,
+    gold: u32,
+    lv: u32,
+    hp: u32,
+    max_hp: u32,
+    route: String,
+    party: Vec<Member>,
+    recruited: Vec<usize>,
+    recruits: Option<Recruits>,
+    key_items: usize,
+    completion: f32,
+    flags: Vec<Value>,
+}
+
+#[derive(Serialize)]
+struct Member {
+    character: usize,
+    name: &'static str,
+    weapon: u32,
+    armor: [u32; 2],
+}
+
+#[derive(Serialize)]
+struct Recruits {
+    recruited: usize,
+    total: usize,
+}
+
+/// The JSON document for the save `contents` of `chapter`, naming its room
+/// if `rooms` knows it, or `None` if `contents` is not a full DELTARUNE save.
+pub fn save_json(contents: &[u8], chapter: Chapter, rooms: &RoomNames) -> Option<String> {
+    let summary = SaveSummary::parse(contents)?;
+    let flags = format::flags(contents)?;
+    let plot = std::str::from_utf8(contents)
+        .ok()?
+        .lines()
+        .nth(format::PLOT_LINE)?
+        .trim();
+    let document = Document {
+        chapter,
+        name: &summary.name,
+        room: summary.room,
+        room_name: rooms.name(chapter, summary.room),
+        play_time_seconds: summary.play_time.as_secs_f64(),
+        plot: number(plot),
+        gold: summary.gold,
+        lv: summary.lv,
+        hp: summary.hp,
+        max_hp: summary.max_hp,
+        route: summary.route.to_string(),
+        party: summary
+            .party
+            .iter()
+            .map(|member| Member {
+                character: member.character,
+                name: member.name(),
+                weapon: member.weapon,
+                armor: member.armor,
+            })
+            .collect(),
+        recruited: summary.recruited.iter().copied().collect(),
+        recruits: summary
+            .recruits(chapter)
+            .map(|(recruited, total)| Recruits { recruited, total }),
+        key_items: summary.key_items,
+        completion: summary.completion(chapter),
+        flags: flags.into_iter().map(number).collect(),
+    };
+    serde_json::to_string_pretty(&document).ok()
+}
+
+/// `value` as a JSON number, whole if it is one, or as a string if it is not
+/// a number at all.
+fn number(value: &str) -> Value {
+    if let Ok(whole) = value.parse::<i64>() {
+        return whole.into();
+    }
+    value
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map_or_else(|| value.into(), Value::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SyntheticSave;
+
+    #[test]
+    fn writes_every_parsed_field_and_flag() {
+        let contents = SyntheticSave {
+            chapter: 2,
+            name: "SUSIE".to_string(),
+            room: 42,
+            gold: 250,
+            flags: [(format::WEIRD_ROUTE_FLAG, 3)].into(),
+            ..Default::default()
+        }
+        .to_bytes();
+        let rooms = RoomNames::default();
+
+        let document: Value =
+            serde_json::from_str(&save_json(&contents, 2, &rooms).unwrap()).unwrap();
+        assert_eq!(document["chapter"], 2);
+        assert_eq!(document["name"], "SUSIE");
+        assert_eq!(document["room"], 42);
+        assert_eq!(document["room_name"], Value::Null);
+        assert_eq!(document["gold"], 250);
+        assert_eq!(document["route"], "Weird");
+        assert_eq!(document["party"][0]["name"], "Kris");
+        assert_eq!(
+            document["flags"].as_array().unwrap().len(),
+            format::FLAG_COUNT
+        );
+        assert_eq!(document["flags"][format::WEIRD_ROUTE_FLAG], 3);
+        assert_eq!(save_json(b"KRIS\n", 2, &rooms), None);
+
+        assert_eq!(number("0.5"), 0.5);
+        assert_eq!(number("\"text\""), "\"text\"");
+    }
+}
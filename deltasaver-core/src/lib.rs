@@ -0,0 +1,52 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Save discovery, backup, and restore for DELTARUNE, independent of any
+//! particular frontend.
+//!
+//! Tools other than DELTASAVER should use [`vault`], which unlike the rest of
+//! the crate is kept stable.
+
+pub mod branch;
+pub mod compare;
+pub mod config;
+pub mod conflict;
+pub mod dump;
+pub mod duplicates;
+pub mod edit;
+pub mod encryption;
+pub mod export;
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
+pub mod format;
+pub mod freeze;
+pub mod git;
+pub mod history;
+pub mod hook;
+pub mod import;
+pub mod index;
+pub mod json;
+pub mod locale;
+pub mod platform;
+pub mod portable;
+pub mod power;
+pub mod profile;
+pub mod recovery;
+pub mod retention;
+pub mod rooms;
+pub mod save;
+pub mod schedule;
+pub mod search;
+pub mod snapshot;
+pub mod stats;
+pub mod steam;
+pub mod store;
+pub mod summary;
+pub mod transfer;
+pub mod undo;
+pub mod vault;
+pub mod watch;
+
+pub use locale::Locale;
+pub use profile::GameProfile;
+pub use save::{BUILTIN_SLOT_MAX_INDEX, CHAPTER_COUNT, Chapter, SaveFile, Slot};
+pub use store::{GameUpdate, LoadError, SaveStore, Saves};
@@ -0,0 +1,197 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Locale-aware formatting for the dates, times, and sizes shown to the user.
+//!
+//! We only need a handful of conventions, so rather than pulling in full CLDR
+//! data we derive them from the language and region of a locale tag.
+
+use chrono::{DateTime, Local, NaiveDateTime, Timelike};
+use std::env;
+use std::time::SystemTime;
+
+/// Overrides the OS locale, e.g. `DELTASAVER_LOCALE=de-DE`.
+pub const LOCALE_VARIABLE: &str = "DELTASAVER_LOCALE";
+
+const FALLBACK_TAG: &str = "en-US";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    MonthDayYear,
+    DayMonthYear,
+    YearMonthDay,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale {
+    pub date_order: DateOrder,
+    pub date_separator: char,
+    pub twelve_hour: bool,
+    pub decimal_separator: char,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::from_tag(FALLBACK_TAG)
+    }
+}
+
+impl Locale {
+    /// The locale from [`LOCALE_VARIABLE`] if set, otherwise the OS locale,
+    /// otherwise US English.
+    pub fn current() -> Self {
+        let tag = env::var(LOCALE_VARIABLE)
+            .ok()
+            .filter(|tag| !tag.is_empty())
+            .or_else(sys_locale::get_locale)
+            .unwrap_or_else(|| FALLBACK_TAG.to_string());
+        Self::from_tag(&tag)
+    }
+
+    /// Accepts both BCP 47 tags like `en-GB` and POSIX names like
+    /// `de_DE.UTF-8`.
+    pub fn from_tag(tag: &str) -> Self {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default();
+        let mut parts = tag.split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        // Skip a script subtag such as the `Hans` in `zh-Hans-CN`.
+        let region = parts
+            .find(|part| {
+                part.len() == 2 || (part.len() == 3 && part.bytes().all(|b| b.is_ascii_digit()))
+            })
+            .map(|region| region.to_ascii_uppercase());
+        let language = language.as_str();
+        let region = region.as_deref();
+
+        let date_order = match (language, region) {
+            (_, Some("US" | "PH")) | ("en", None) => DateOrder::MonthDayYear,
+            ("zh" | "ja" | "ko" | "hu" | "lt" | "sv", _) | (_, Some("CA")) => {
+                DateOrder::YearMonthDay
+            }
+            _ => DateOrder::DayMonthYear,
+        };
+        let date_separator = match (date_order, language) {
+            (DateOrder::YearMonthDay, "zh" | "ja") => '/',
+            (DateOrder::YearMonthDay, _) => '-',
+            (
+                DateOrder::DayMonthYear,
+                "de" | "ru" | "pl" | "fi" | "nb" | "no" | "da" | "cs" | "tr" | "uk",
+            ) => '.',
+            (DateOrder::DayMonthYear, "nl") => '-',
+            _ => '/',
+        };
+        let twelve_hour = matches!(
+            region,
+            Some("US" | "CA" | "AU" | "NZ" | "IN" | "PH" | "PK" | "EG")
+        ) || (language == "en" && region.is_none());
+        let decimal_separator = match language {
+            "de" | "fr" | "es" | "it" | "pt" | "ru" | "pl" | "nl" | "sv" | "fi" | "nb" | "no"
+            | "da" | "cs" | "tr" | "uk" | "id" => ',',
+            _ => '.',
+        };
+
+        Self {
+            date_order,
+            date_separator,
+            twelve_hour,
+            decimal_separator,
+        }
+    }
+
+    /// `time` in the local time zone, to the minute.
+    pub fn format_time(&self, time: SystemTime) -> String {
+        self.format_datetime(DateTime::<Local>::from(time).naive_local())
+    }
+
+    fn format_datetime(&self, datetime: NaiveDateTime) -> String {
+        let date = datetime.date();
+        let separator = self.date_separator;
+        let date = match self.date_order {
+            DateOrder::MonthDayYear => {
+                format!(
+                    "{}{separator}{}{separator}{}",
+                    date.format("%-m"),
+                    date.format("%-d"),
+                    date.format("%Y")
+                )
+            }
+            DateOrder::DayMonthYear => date
+                .format(&format!("%d{separator}%m{separator}%Y"))
+                .to_string(),
+            DateOrder::YearMonthDay => date
+                .format(&format!("%Y{separator}%m{separator}%d"))
+                .to_string(),
+        };
+        let time = if self.twelve_hour {
+            let (is_pm, hour) = datetime.hour12();
+            format!(
+                "{}:{:02} {}",
+                hour,
+                datetime.minute(),
+                if is_pm { "PM" } else { "AM" }
+            )
+        } else {
+            format!("{:02}:{:02}", datetime.hour(), datetime.minute())
+        };
+        format!("{} {}", date, time)
+    }
+
+    /// `bytes` in binary units with one decimal place, e.g. `10,1 KB`.
+    pub fn format_size(&self, bytes: u64) -> String {
+        const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+        if bytes < 1024 {
+            return format!("{} B", bytes);
+        }
+        let mut size = bytes as f64 / 1024.0;
+        let mut unit = 0;
+        while size >= 1024.0 && unit + 1 < UNITS.len() {
+            size /= 1024.0;
+            unit += 1;
+        }
+        format!("{:.1} {}", size, UNITS[unit]).replace('.', &self.decimal_separator.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn afternoon() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, 31)
+            .unwrap()
+            .and_hms_opt(14, 5, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn dates_follow_the_region() {
+        assert_eq!(
+            Locale::from_tag("en-US").format_datetime(afternoon()),
+            "1/31/2025 2:05 PM"
+        );
+        assert_eq!(
+            Locale::from_tag("en_GB.UTF-8").format_datetime(afternoon()),
+            "31/01/2025 14:05"
+        );
+        assert_eq!(
+            Locale::from_tag("de-DE").format_datetime(afternoon()),
+            "31.01.2025 14:05"
+        );
+        assert_eq!(
+            Locale::from_tag("ja-JP").format_datetime(afternoon()),
+            "2025/01/31 14:05"
+        );
+    }
+
+    #[test]
+    fn sizes_use_the_decimal_separator() {
+        assert_eq!(Locale::from_tag("en-US").format_size(512), "512 B");
+        assert_eq!(Locale::from_tag("en-US").format_size(10_342), "10.1 KB");
+        assert_eq!(Locale::from_tag("fr-FR").format_size(10_342), "10,1 KB");
+        assert_eq!(
+            Locale::from_tag("fr-FR").format_size(3 * 1024 * 1024),
+            "3,0 MB"
+        );
+    }
+}
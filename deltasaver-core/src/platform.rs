@@ -0,0 +1,218 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Where GameMaker games keep their saves on each platform.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::steam;
+
+/// Flatpak Steam runs games with their own XDG directories under this one.
+const FLATPAK_STEAM_DIRECTORY: &str = ".var/app/com.valvesoftware.Steam";
+
+/// What we need to know to find a game's save directory on any platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameLocation {
+    /// The save directory's name on Windows and Linux.
+    pub directory_name: &'static str,
+    /// The save directory's name on macOS.
+    pub macos_identifier: &'static str,
+    /// Lets us look inside Proton prefixes for the Windows build.
+    pub steam_app_id: Option<u32>,
+}
+
+impl GameLocation {
+    /// Every directory the game might keep its saves in on this platform, in
+    /// the order we prefer them.
+    ///
+    /// On Linux these are the native XDG directories, then the same inside
+    /// Flatpak Steam's sandbox, then the game's Proton prefix in each Steam
+    /// library, as on a Steam Deck.
+    pub fn candidates(&self) -> Vec<PathBuf> {
+        if cfg!(target_os = "windows") {
+            dirs::data_local_dir()
+                .map(|directory| directory.join(self.directory_name))
+                .into_iter()
+                .collect()
+        } else if cfg!(target_os = "macos") {
+            dirs::data_local_dir()
+                .map(|directory| directory.join(self.macos_identifier))
+                .into_iter()
+                .collect()
+        } else if cfg!(target_os = "linux") {
+            let Some(home) = dirs::home_dir() else {
+                return Vec::new();
+            };
+            let mut candidates = LinuxDirectories::from_env(&home).candidates(self.directory_name);
+            // Flatpak points the XDG variables into the app's own directory.
+            let flatpak_directory = home.join(FLATPAK_STEAM_DIRECTORY);
+            candidates.extend([
+                flatpak_directory.join("config").join(self.directory_name),
+                flatpak_directory.join("data").join(self.directory_name),
+            ]);
+            if let Some(app_id) = self.steam_app_id {
+                candidates.extend(
+                    steam::proton_local_app_data_candidates(app_id)
+                        .into_iter()
+                        .map(|local_app_data| local_app_data.join(self.directory_name)),
+                );
+            }
+            candidates
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// The first of the [`Self::candidates`] that exists, or else the first
+    /// one, where the game will create its saves. `None` if this platform is
+    /// not supported.
+    pub fn save_directory(&self) -> Option<PathBuf> {
+        choose_directory(self.candidates())
+    }
+
+    /// Every one of the [`Self::candidates`] that exists, since the Steam and
+    /// itch.io builds, or a native and a Proton install, keep separate saves.
+    pub fn existing_directories(&self) -> Vec<PathBuf> {
+        existing_directories(self.candidates())
+    }
+}
+
+/// The directories that exist, skipping any that are links to one we already
+/// have.
+fn existing_directories(candidates: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+    let mut canonical_directories = Vec::new();
+    for candidate in candidates {
+        if let Ok(canonical_directory) = candidate.canonicalize()
+            && candidate.is_dir()
+            && !canonical_directories.contains(&canonical_directory)
+        {
+            canonical_directories.push(canonical_directory);
+            directories.push(candidate);
+        }
+    }
+    directories
+}
+
+fn choose_directory(candidates: Vec<PathBuf>) -> Option<PathBuf> {
+    let existing = candidates.iter().position(|directory| directory.is_dir());
+    candidates.into_iter().nth(existing.unwrap_or(0))
+}
+
+/// The XDG base directories, resolved against the environment.
+#[derive(Debug, Clone)]
+struct LinuxDirectories {
+    config_home: PathBuf,
+    data_home: PathBuf,
+    data_dirs: Vec<PathBuf>,
+}
+
+impl LinuxDirectories {
+    fn from_env(home: &Path) -> Self {
+        Self::new(
+            home,
+            env::var_os("XDG_CONFIG_HOME").map(PathBuf::from),
+            env::var_os("XDG_DATA_HOME").map(PathBuf::from),
+            env::var("XDG_DATA_DIRS").ok(),
+        )
+    }
+
+    /// Applies the XDG defaults for anything unset. The specification says
+    /// relative paths are invalid and must be ignored.
+    fn new(
+        home: &Path,
+        config_home: Option<PathBuf>,
+        data_home: Option<PathBuf>,
+        data_dirs: Option<String>,
+    ) -> Self {
+        let config_home = config_home
+            .filter(|path| path.is_absolute())
+            .unwrap_or_else(|| home.join(".config"));
+        let data_home = data_home
+            .filter(|path| path.is_absolute())
+            .unwrap_or_else(|| home.join(".local/share"));
+        let data_dirs = data_dirs
+            .map(|data_dirs| {
+                data_dirs
+                    .split(':')
+                    .map(PathBuf::from)
+                    .filter(|path| path.is_absolute())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|data_dirs| !data_dirs.is_empty())
+            .unwrap_or_else(|| {
+                vec![
+                    PathBuf::from("/usr/local/share"),
+                    PathBuf::from("/usr/share"),
+                ]
+            });
+        Self {
+            config_home,
+            data_home,
+            data_dirs,
+        }
+    }
+
+    /// GameMaker's Linux runner writes to `$XDG_CONFIG_HOME/{name}`, but
+    /// repackaged builds sometimes use the data directories instead.
+    fn candidates(&self, name: &str) -> Vec<PathBuf> {
+        [self.config_home.join(name), self.data_home.join(name)]
+            .into_iter()
+            .chain(self.data_dirs.iter().map(|directory| directory.join(name)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn linux_defaults_follow_the_xdg_specification() {
+        let directories = LinuxDirectories::new(
+            Path::new("/home/kris"),
+            Some(PathBuf::from("relative/config")),
+            None,
+            Some(String::new()),
+        );
+        assert_eq!(directories.config_home, Path::new("/home/kris/.config"));
+        assert_eq!(directories.data_home, Path::new("/home/kris/.local/share"));
+        assert_eq!(
+            directories.data_dirs,
+            vec![
+                PathBuf::from("/usr/local/share"),
+                PathBuf::from("/usr/share")
+            ]
+        );
+    }
+
+    #[test]
+    fn linux_save_directory_prefers_config_then_existing_data_directories() {
+        let root = tempfile::tempdir().unwrap();
+        let directories = LinuxDirectories::new(
+            root.path(),
+            Some(root.path().join("config")),
+            Some(root.path().join("data")),
+            Some(format!("{}", root.path().join("shared").display())),
+        );
+        let save_directory = || choose_directory(directories.candidates("DELTARUNE")).unwrap();
+
+        // Nothing exists yet, so the game will create the config directory.
+        assert_eq!(save_directory(), root.path().join("config/DELTARUNE"));
+
+        fs::create_dir_all(root.path().join("shared/DELTARUNE")).unwrap();
+        assert_eq!(save_directory(), root.path().join("shared/DELTARUNE"));
+
+        fs::create_dir_all(root.path().join("config/DELTARUNE")).unwrap();
+        assert_eq!(save_directory(), root.path().join("config/DELTARUNE"));
+
+        assert_eq!(
+            existing_directories(directories.candidates("DELTARUNE")),
+            vec![
+                root.path().join("config/DELTARUNE"),
+                root.path().join("shared/DELTARUNE")
+            ]
+        );
+    }
+}
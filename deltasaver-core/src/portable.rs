@@ -0,0 +1,80 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Where DELTASAVER keeps its own files: backups, `profiles.json`, and
+//! `config.toml`.
+//!
+//! Normally these live in the platform's data and config directories. In
+//! portable mode they all live together in one directory instead, so the
+//! whole thing can be carried between machines on a USB stick. Portable mode
+//! is on when [`PORTABLE_VARIABLE`] names a directory, or when a directory
+//! named `DELTASAVER` sits next to the executable.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Turns on portable mode with the given directory, e.g.
+/// `DELTASAVER_PORTABLE_DIR=E:\DELTASAVER`.
+pub const PORTABLE_VARIABLE: &str = "DELTASAVER_PORTABLE_DIR";
+
+const DIRECTORY_NAME: &str = "DELTASAVER";
+
+/// The directory everything is kept in if portable mode is on.
+pub fn portable_directory() -> Option<PathBuf> {
+    let executable_directory = env::current_exe()
+        .ok()
+        .and_then(|executable| executable.parent().map(Path::to_path_buf));
+    choose_portable_directory(
+        env::var_os(PORTABLE_VARIABLE).map(PathBuf::from),
+        executable_directory.as_deref(),
+    )
+}
+
+fn choose_portable_directory(
+    variable: Option<PathBuf>,
+    executable_directory: Option<&Path>,
+) -> Option<PathBuf> {
+    variable
+        .filter(|directory| !directory.as_os_str().is_empty())
+        .or_else(|| {
+            executable_directory
+                .map(|directory| directory.join(DIRECTORY_NAME))
+                .filter(|directory| directory.is_dir())
+        })
+}
+
+/// Where DELTARUNE's backups are kept, with other profiles' in
+/// subdirectories.
+pub fn data_directory() -> Option<PathBuf> {
+    portable_directory().or_else(|| Some(dirs::data_local_dir()?.join(DIRECTORY_NAME)))
+}
+
+/// Where `profiles.json` and `config.toml` are kept.
+pub fn config_directory() -> Option<PathBuf> {
+    portable_directory().or_else(|| Some(dirs::config_dir()?.join(DIRECTORY_NAME)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn portable_mode_needs_the_variable_or_a_directory_beside_the_executable() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(choose_portable_directory(None, Some(root.path())), None);
+        assert_eq!(
+            choose_portable_directory(Some(PathBuf::new()), Some(root.path())),
+            None
+        );
+
+        fs::create_dir(root.path().join(DIRECTORY_NAME)).unwrap();
+        assert_eq!(
+            choose_portable_directory(None, Some(root.path())),
+            Some(root.path().join(DIRECTORY_NAME))
+        );
+        assert_eq!(
+            choose_portable_directory(Some(PathBuf::from("/media/usb")), Some(root.path())),
+            Some(PathBuf::from("/media/usb"))
+        );
+    }
+}
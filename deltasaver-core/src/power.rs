@@ -0,0 +1,96 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Whether the machine is running on battery, so that background work can
+//! wait for AC power.
+
+use std::fs;
+use std::path::Path;
+#[cfg(target_os = "macos")]
+use std::process::Command;
+
+/// Whether the machine is running on battery. Desktops, and anything we cannot
+/// tell, count as being on AC power.
+pub fn on_battery() -> bool {
+    platform_on_battery().unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+fn platform_on_battery() -> Option<bool> {
+    on_battery_in(Path::new("/sys/class/power_supply"))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_on_battery() -> Option<bool> {
+    let output = Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let output = String::from_utf8(output.stdout).ok()?;
+    Some(output.contains("'Battery Power'"))
+}
+
+#[cfg(target_os = "windows")]
+fn platform_on_battery() -> Option<bool> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // SAFETY: `SYSTEM_POWER_STATUS` is plain data, so all zeroes is valid, and
+    // `status` is writable.
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    match status.ACLineStatus {
+        0 => Some(true),
+        1 => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn platform_on_battery() -> Option<bool> {
+    None
+}
+
+/// Reads Linux's power supply class: we are on battery if there is a battery
+/// and no mains or USB supply is online.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn on_battery_in(power_supply_directory: &Path) -> Option<bool> {
+    let mut has_battery = false;
+    for entry in fs::read_dir(power_supply_directory).ok()? {
+        let supply = entry.ok()?.path();
+        let kind = fs::read_to_string(supply.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Battery" => has_battery = true,
+            "Mains" | "USB" => {
+                let online = fs::read_to_string(supply.join("online")).unwrap_or_default();
+                if online.trim() == "1" {
+                    return Some(false);
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(has_battery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_supply(directory: &Path, name: &str, kind: &str, online: &str) {
+        let supply = directory.join(name);
+        fs::create_dir_all(&supply).unwrap();
+        fs::write(supply.join("type"), format!("{}\n", kind)).unwrap();
+        fs::write(supply.join("online"), format!("{}\n", online)).unwrap();
+    }
+
+    #[test]
+    fn linux_laptops_are_on_battery_only_when_unplugged() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(on_battery_in(root.path()), Some(false));
+
+        add_supply(root.path(), "BAT0", "Battery", "1");
+        add_supply(root.path(), "AC", "Mains", "0");
+        assert_eq!(on_battery_in(root.path()), Some(true));
+
+        add_supply(root.path(), "AC", "Mains", "1");
+        assert_eq!(on_battery_in(root.path()), Some(false));
+    }
+}
@@ -0,0 +1,519 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Describes where a game keeps its saves and how they are named, so the
+//! backup engine is not tied to DELTARUNE.
+//!
+//! Besides the built-in DELTARUNE and UNDERTALE profiles, users can describe
+//! other games in `profiles.json` in the DELTASAVER config directory:
+//!
+//! ```json
+//! [
+//!   {
+//!     "name": "Some Game",
+//!     "save_directory": "C:\\Users\\me\\AppData\\Local\\SomeGame",
+//!     "filename_pattern": "save{chapter}-{slot}.dat",
+//!     "chapter_count": 2,
+//!     "slot_count": 3
+//!   }
+//! ]
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use ulid::Ulid;
+
+use crate::platform::GameLocation;
+use crate::portable;
+use crate::save::{
+    BUILTIN_SLOT_MAX_INDEX, CHAPTER_COUNT, COMPLETION_SLOT_OFFSET, Chapter, PERSISTENT_FILENAME,
+    Slot,
+};
+use crate::steam;
+
+pub const DELTARUNE_PROFILE_NAME: &str = "DELTARUNE";
+pub const UNDERTALE_PROFILE_NAME: &str = "UNDERTALE";
+pub const SURVEY_PROGRAM_PROFILE_NAME: &str = "SURVEY_PROGRAM";
+pub const DELTARUNE_DEMO_PROFILE_NAME: &str = "DELTARUNE Chapter 1&2 demo";
+
+const PROFILES_FILENAME: &str = "profiles.json";
+
+const DELTARUNE_LOCATION: GameLocation = GameLocation {
+    directory_name: "DELTARUNE",
+    macos_identifier: "com.tobyfox.deltarune",
+    steam_app_id: Some(steam::DELTARUNE_APP_ID),
+};
+const UNDERTALE_LOCATION: GameLocation = GameLocation {
+    directory_name: "UNDERTALE",
+    macos_identifier: "com.tobyfox.undertale",
+    steam_app_id: Some(steam::UNDERTALE_APP_ID),
+};
+const SURVEY_PROGRAM_LOCATION: GameLocation = GameLocation {
+    directory_name: "SURVEY_PROGRAM",
+    macos_identifier: "com.tobyfox.survey_program",
+    steam_app_id: None,
+};
+const DELTARUNE_DEMO_LOCATION: GameLocation = GameLocation {
+    directory_name: "DELTARUNEdemo",
+    macos_identifier: "com.tobyfox.deltarunedemo",
+    steam_app_id: None,
+};
+
+const CHAPTER_PLACEHOLDER: &str = "{chapter}";
+const SLOT_PLACEHOLDER: &str = "{slot}";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameProfile {
+    pub name: String,
+    pub save_directory: PathBuf,
+    /// The name of a save file, where `{chapter}` and `{slot}` stand for the
+    /// chapter and the zero-based slot. Games without chapters leave out
+    /// `{chapter}` and are treated as having a single chapter 1.
+    pub filename_pattern: String,
+    #[serde(default = "default_chapter_count")]
+    pub chapter_count: Chapter,
+    pub slot_count: Slot,
+    /// Whether a finished chapter writes completion data to slot
+    /// `slot + completion_slot_offset`, as DELTARUNE does.
+    #[serde(default)]
+    pub completion_slot_offset: Option<Slot>,
+    /// Files shared by every slot, such as DELTARUNE's `dr.ini`.
+    #[serde(default)]
+    pub shared_files: Vec<String>,
+    /// Lets us notice game updates through Steam.
+    #[serde(default)]
+    pub steam_app_id: Option<u32>,
+}
+
+fn default_chapter_count() -> Chapter {
+    1
+}
+
+impl GameProfile {
+    /// DELTARUNE, with its saves in `save_directory`.
+    pub fn deltarune(save_directory: PathBuf) -> Self {
+        Self {
+            name: DELTARUNE_PROFILE_NAME.to_string(),
+            save_directory,
+            filename_pattern: format!("filech{}_{}", CHAPTER_PLACEHOLDER, SLOT_PLACEHOLDER),
+            chapter_count: CHAPTER_COUNT,
+            slot_count: BUILTIN_SLOT_MAX_INDEX + 1,
+            completion_slot_offset: Some(COMPLETION_SLOT_OFFSET),
+            shared_files: vec![PERSISTENT_FILENAME.to_string()],
+            steam_app_id: Some(steam::DELTARUNE_APP_ID),
+        }
+    }
+
+    /// DELTARUNE at the platform's usual location, or `None` if this platform
+    /// has no local data directory or is not supported.
+    pub fn deltarune_at_default_location() -> Option<Self> {
+        DELTARUNE_LOCATION.save_directory().map(Self::deltarune)
+    }
+
+    /// A DELTARUNE demo with saves in `save_directory`. The demos name their
+    /// files like the full game but only contain the first `chapter_count`
+    /// chapters.
+    pub fn deltarune_demo(name: &str, save_directory: PathBuf, chapter_count: Chapter) -> Self {
+        Self {
+            name: name.to_string(),
+            chapter_count,
+            // SURVEY_PROGRAM had a single chapter and so nothing to hand off.
+            completion_slot_offset: (chapter_count > 1).then_some(COMPLETION_SLOT_OFFSET),
+            ..Self::deltarune(save_directory)
+        }
+    }
+
+    /// UNDERTALE, with its saves in `save_directory`. It has a single save,
+    /// `file0`; `file9` holds what survives a reset, and `undertale.ini` the
+    /// settings and ending state.
+    pub fn undertale(save_directory: PathBuf) -> Self {
+        Self {
+            name: UNDERTALE_PROFILE_NAME.to_string(),
+            save_directory,
+            filename_pattern: format!("file{}", SLOT_PLACEHOLDER),
+            chapter_count: 1,
+            slot_count: 1,
+            completion_slot_offset: None,
+            shared_files: vec!["file9".to_string(), "undertale.ini".to_string()],
+            steam_app_id: Some(steam::UNDERTALE_APP_ID),
+        }
+    }
+
+    pub fn undertale_at_default_location() -> Option<Self> {
+        UNDERTALE_LOCATION.save_directory().map(Self::undertale)
+    }
+
+    /// Every save directory this game has on this machine, starting with the
+    /// one the profile uses. Only built-in profiles know where else to look.
+    pub fn installations(&self) -> Vec<PathBuf> {
+        let mut installations = vec![self.save_directory.clone()];
+        if let Some(location) = builtin_location(&self.name) {
+            for directory in location.existing_directories() {
+                if !installations.contains(&directory) {
+                    installations.push(directory);
+                }
+            }
+        }
+        installations
+    }
+
+    pub fn has_chapters(&self) -> bool {
+        self.filename_pattern.contains(CHAPTER_PLACEHOLDER)
+    }
+
+    /// Every `(chapter, slot)` pair the game can write, in display order.
+    pub fn slots(&self) -> impl Iterator<Item = (Chapter, Slot)> + '_ {
+        (1..=self.chapter_count)
+            .flat_map(|chapter| (0..self.slot_count).map(move |slot| (chapter, slot)))
+    }
+
+    /// The name the game gives the file for `chapter`'s `slot`.
+    pub fn save_filename(&self, chapter: Chapter, slot: Slot) -> String {
+        self.filename_pattern
+            .replace(CHAPTER_PLACEHOLDER, &chapter.to_string())
+            .replace(SLOT_PLACEHOLDER, &slot.to_string())
+    }
+
+    /// Parses a game save filename, accepting only slots the game shows, so
+    /// e.g. DELTARUNE's completion files are skipped.
+    pub fn parse_save_filename(&self, filename: &str) -> Option<(Chapter, Slot)> {
+        self.parse_save_filename_of_any_chapter(filename)
+            .filter(|(chapter, _)| (1..=self.chapter_count).contains(chapter))
+    }
+
+    /// Parses the name of a file the game keeps past its last slot, such as
+    /// DELTARUNE's completion files, which are shown read-only if at all.
+    pub fn parse_extra_filename(&self, filename: &str) -> Option<(Chapter, Slot)> {
+        self.parse_slot_filename(filename)
+            .filter(|&(chapter, slot)| {
+                (1..=self.chapter_count).contains(&chapter) && self.is_extra_slot(slot)
+            })
+    }
+
+    /// Whether `slot` is past the slots the game shows, so holds an extra
+    /// file rather than a save.
+    pub fn is_extra_slot(&self, slot: Slot) -> bool {
+        slot >= self.slot_count
+    }
+
+    /// Like [`Self::parse_save_filename`], but accepting chapters past
+    /// `chapter_count`, as mods add.
+    fn parse_save_filename_of_any_chapter(&self, filename: &str) -> Option<(Chapter, Slot)> {
+        self.parse_slot_filename(filename)
+            .filter(|&(_, slot)| !self.is_extra_slot(slot))
+    }
+
+    /// Parses any filename matching the pattern, whatever its slot.
+    fn parse_slot_filename(&self, filename: &str) -> Option<(Chapter, Slot)> {
+        let mut chapter = if self.has_chapters() { None } else { Some(1) };
+        let mut slot = None;
+
+        let mut rest = filename;
+        let mut pattern = self.filename_pattern.as_str();
+        while !pattern.is_empty() {
+            if let Some(after) = pattern.strip_prefix(CHAPTER_PLACEHOLDER) {
+                let (digits, after_digits) = split_digits(rest);
+                chapter = Some(digits.parse().ok()?);
+                (rest, pattern) = (after_digits, after);
+            } else if let Some(after) = pattern.strip_prefix(SLOT_PLACEHOLDER) {
+                let (digits, after_digits) = split_digits(rest);
+                slot = Some(digits.parse().ok()?);
+                (rest, pattern) = (after_digits, after);
+            } else {
+                let literal = pattern.chars().next()?;
+                rest = rest.strip_prefix(literal)?;
+                pattern = &pattern[literal.len_utf8()..];
+            }
+        }
+
+        let (chapter, slot) = (chapter?, slot?);
+        (rest.is_empty() && chapter >= 1).then_some((chapter, slot))
+    }
+
+    /// The highest chapter with a save or backup in `directory`, counting
+    /// chapters past `chapter_count`, or `None` if there are none.
+    pub fn last_chapter_in(&self, directory: &Path) -> Option<Chapter> {
+        fs::read_dir(directory)
+            .ok()?
+            .filter_map(|entry| {
+                let filename = entry.ok()?.file_name().into_string().ok()?;
+                let save_filename = split_backup_filename(&filename)
+                    .map_or(filename.as_str(), |(save_filename, _, _)| save_filename);
+                self.parse_save_filename_of_any_chapter(save_filename)
+                    .map(|(chapter, _)| chapter)
+            })
+            .max()
+    }
+
+    /// The name of a backup of `chapter`'s `slot` with the given contents hash
+    /// and backup ID, e.g. `filech2_1_{hash}_{ulid}`.
+    pub fn backup_filename(&self, chapter: Chapter, slot: Slot, hash: &str, id: &str) -> String {
+        format!("{}_{}_{}", self.save_filename(chapter, slot), hash, id)
+    }
+
+    /// Parses a name made by [`Self::backup_filename`], returning the chapter,
+    /// slot, and hash.
+    pub fn parse_backup_filename(&self, filename: &str) -> Option<(Chapter, Slot, String)> {
+        let (save_filename, hash, _id) = split_backup_filename(filename)?;
+        let (chapter, slot) = self.parse_save_filename(save_filename)?;
+        Some((chapter, slot, hash.to_string()))
+    }
+
+    /// Like [`Self::parse_backup_filename`], but for backups of extra files.
+    pub fn parse_extra_backup_filename(&self, filename: &str) -> Option<(Chapter, Slot, String)> {
+        let (save_filename, hash, _id) = split_backup_filename(filename)?;
+        let (chapter, slot) = self.parse_extra_filename(save_filename)?;
+        Some((chapter, slot, hash.to_string()))
+    }
+}
+
+/// Splits a backup filename into the save filename, hash, and backup ID.
+///
+/// Backups are identified by a ULID, followed by `-{n}` in the unlikely event
+/// that a backup with that ULID already exists. Older backups were instead
+/// identified by `{secs}_{nanos}`, which is kept as their ID.
+fn split_backup_filename(filename: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = filename.rsplitn(3, '_');
+    let (id, hash, save_filename) = (parts.next()?, parts.next()?, parts.next()?);
+    if parse_backup_ulid(id).is_some() {
+        return Some((save_filename, hash, id));
+    }
+
+    let mut parts = filename.rsplitn(4, '_');
+    let (nanos, secs, hash, save_filename) =
+        (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    if !is_digits(secs) || !is_digits(nanos) {
+        return None;
+    }
+    let id = &filename[save_filename.len() + hash.len() + 2..];
+    Some((save_filename, hash, id))
+}
+
+fn parse_backup_ulid(id: &str) -> Option<Ulid> {
+    let ulid = id.split_once('-').map_or(id, |(ulid, _suffix)| ulid);
+    Ulid::from_string(ulid).ok()
+}
+
+fn is_digits(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// The ID of the backup named `filename`, unique within its backup directory.
+pub fn backup_id(filename: &str) -> Option<&str> {
+    let (_, _, id) = split_backup_filename(filename)?;
+    Some(id)
+}
+
+/// Seconds since the epoch at which the backup named `filename` was taken.
+pub fn backup_created_secs(filename: &str) -> Option<u64> {
+    let id = backup_id(filename)?;
+    match parse_backup_ulid(id) {
+        Some(ulid) => Some(ulid.timestamp_ms() / 1000),
+        None => id.split('_').next()?.parse().ok(),
+    }
+}
+
+/// A new ULID for the backup named `filename` if it has a legacy
+/// `{secs}_{nanos}` ID, with the time it was taken as its timestamp, so it
+/// sorts where it did.
+pub fn ulid_for_legacy_backup(filename: &str) -> Option<Ulid> {
+    let id = backup_id(filename)?;
+    if parse_backup_ulid(id).is_some() {
+        return None;
+    }
+    let (secs, nanos) = id.split_once('_')?;
+    let (secs, nanos) = (secs.parse::<u64>().ok()?, nanos.parse::<u64>().ok()?);
+    let timestamp_ms = secs.checked_mul(1000)?.checked_add(nanos / 1_000_000)?;
+    Some(Ulid::from_parts(timestamp_ms, Ulid::new().random()))
+}
+
+/// The DELTARUNE demos whose save directories exist on this machine: the 2018
+/// SURVEY_PROGRAM and the 2021 Chapter 1&2 demo. They were separate games, so
+/// GameMaker gave each its own save directory apart from the full game's.
+pub fn detected_deltarune_demos() -> Vec<GameProfile> {
+    [
+        (SURVEY_PROGRAM_PROFILE_NAME, SURVEY_PROGRAM_LOCATION, 1),
+        (DELTARUNE_DEMO_PROFILE_NAME, DELTARUNE_DEMO_LOCATION, 2),
+    ]
+    .into_iter()
+    .filter_map(|(name, location, chapter_count)| {
+        let save_directory = location.save_directory()?;
+        Some(GameProfile::deltarune_demo(
+            name,
+            save_directory,
+            chapter_count,
+        ))
+    })
+    .filter(|profile| profile.save_directory.is_dir())
+    .collect()
+}
+
+/// How to find the save directory of the built-in profile named `name`, so
+/// that we can show where we looked.
+pub fn builtin_location(name: &str) -> Option<GameLocation> {
+    [
+        (DELTARUNE_PROFILE_NAME, DELTARUNE_LOCATION),
+        (UNDERTALE_PROFILE_NAME, UNDERTALE_LOCATION),
+        (SURVEY_PROGRAM_PROFILE_NAME, SURVEY_PROGRAM_LOCATION),
+        (DELTARUNE_DEMO_PROFILE_NAME, DELTARUNE_DEMO_LOCATION),
+    ]
+    .into_iter()
+    .find(|(builtin_name, _)| builtin_name.eq_ignore_ascii_case(name))
+    .map(|(_, location)| location)
+}
+
+/// The profiles DELTASAVER knows about without any configuration, for the
+/// games installed at their usual locations on this platform.
+pub fn builtin_profiles() -> Vec<GameProfile> {
+    [
+        GameProfile::deltarune_at_default_location(),
+        GameProfile::undertale_at_default_location(),
+    ]
+    .into_iter()
+    .flatten()
+    .chain(detected_deltarune_demos())
+    .collect()
+}
+
+fn split_digits(text: &str) -> (&str, &str) {
+    let end = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    text.split_at(end)
+}
+
+/// Where `profiles.json` lives, or `None` if this platform has no config
+/// directory.
+pub fn profiles_path() -> Option<PathBuf> {
+    Some(portable::config_directory()?.join(PROFILES_FILENAME))
+}
+
+/// Reads user-defined profiles from `path`. A missing file means there are
+/// none.
+pub fn load_profiles(path: &Path) -> io::Result<Vec<GameProfile>> {
+    match fs::read(path) {
+        Ok(contents) => serde_json::from_slice(&contents).map_err(io::Error::other),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error),
+    }
+}
+
+/// The profile named `name`, ignoring case, looking first at the user's
+/// profiles so that they can override the built-in ones.
+pub fn find_profile(name: &str) -> io::Result<Option<GameProfile>> {
+    let user_profiles = match profiles_path() {
+        Some(path) => load_profiles(&path)?,
+        None => Vec::new(),
+    };
+    Ok(user_profiles
+        .into_iter()
+        .chain(builtin_profiles())
+        .find(|profile| profile.name.eq_ignore_ascii_case(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom_undertale() -> GameProfile {
+        GameProfile {
+            name: "Undertale".to_string(),
+            save_directory: PathBuf::new(),
+            filename_pattern: "file{slot}".to_string(),
+            chapter_count: 1,
+            slot_count: 1,
+            completion_slot_offset: None,
+            shared_files: Vec::new(),
+            steam_app_id: None,
+        }
+    }
+
+    #[test]
+    fn deltarune_profile_matches_game_filenames() {
+        let profile = GameProfile::deltarune(PathBuf::new());
+        assert_eq!(profile.save_filename(2, 1), "filech2_1");
+        assert_eq!(profile.parse_save_filename("filech4_2"), Some((4, 2)));
+        assert_eq!(profile.parse_save_filename("filech1_3"), None);
+        assert_eq!(profile.parse_save_filename("filech1_0_extra"), None);
+        assert_eq!(profile.parse_save_filename("dr.ini"), None);
+        assert_eq!(
+            profile.parse_backup_filename("filech2_1_abcd_01ARZ3NDEKTSV4RRFFQ69G5FAV"),
+            Some((2, 1, "abcd".to_string()))
+        );
+    }
+
+    #[test]
+    fn demo_profiles_only_cover_their_chapters() {
+        let survey_program =
+            GameProfile::deltarune_demo(SURVEY_PROGRAM_PROFILE_NAME, PathBuf::new(), 1);
+        assert_eq!(
+            survey_program.parse_save_filename("filech1_2"),
+            Some((1, 2))
+        );
+        assert_eq!(survey_program.parse_save_filename("filech2_0"), None);
+        assert_eq!(survey_program.completion_slot_offset, None);
+
+        let demo = GameProfile::deltarune_demo(DELTARUNE_DEMO_PROFILE_NAME, PathBuf::new(), 2);
+        assert_eq!(demo.slots().count(), 6);
+        assert_eq!(demo.completion_slot_offset, Some(COMPLETION_SLOT_OFFSET));
+    }
+
+    #[test]
+    fn profiles_without_chapters_use_chapter_one() {
+        let profile = GameProfile::undertale(PathBuf::new());
+        assert!(!profile.has_chapters());
+        assert_eq!(profile.slots().collect::<Vec<_>>(), vec![(1, 0)]);
+        assert_eq!(profile.parse_save_filename("file0"), Some((1, 0)));
+        assert_eq!(profile.parse_save_filename("file9"), None);
+        assert_eq!(
+            profile.parse_backup_filename(&profile.backup_filename(
+                1,
+                0,
+                "abcd",
+                "01ARZ3NDEKTSV4RRFFQ69G5FAV"
+            )),
+            Some((1, 0, "abcd".to_string()))
+        );
+    }
+
+    #[test]
+    fn backup_ids_cover_ulids_suffixes_and_legacy_timestamps() {
+        let profile = GameProfile::deltarune(PathBuf::new());
+        let ulid = Ulid::from_parts(1_700_000_000_123, 42).to_string();
+
+        let filename = profile.backup_filename(2, 1, "abcd", &ulid);
+        assert_eq!(backup_id(&filename), Some(ulid.as_str()));
+        assert_eq!(backup_created_secs(&filename), Some(1_700_000_000));
+
+        let suffixed = format!("{}-1", filename);
+        assert_eq!(backup_id(&suffixed), Some(format!("{}-1", ulid).as_str()));
+        assert_eq!(
+            profile.parse_backup_filename(&suffixed),
+            Some((2, 1, "abcd".to_string()))
+        );
+
+        let legacy = "filech2_1_abcd_1700000000_12";
+        assert_eq!(backup_id(legacy), Some("1700000000_12"));
+        assert_eq!(backup_created_secs(legacy), Some(1_700_000_000));
+        assert_eq!(
+            profile.parse_backup_filename(legacy),
+            Some((2, 1, "abcd".to_string()))
+        );
+        assert_eq!(profile.parse_backup_filename("filech2_1"), None);
+    }
+
+    #[test]
+    fn profiles_round_trip_through_json() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join(PROFILES_FILENAME);
+        assert!(load_profiles(&path).unwrap().is_empty());
+
+        fs::write(
+            &path,
+            r#"[{"name": "Undertale", "save_directory": "", "filename_pattern": "file{slot}", "slot_count": 1}]"#,
+        )
+        .unwrap();
+        assert_eq!(load_profiles(&path).unwrap(), vec![custom_undertale()]);
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Works out where a save that seems to have disappeared might have gone, by
+//! checking the usual culprits in order, and puts it back.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::save::{Chapter, SaveFile, Slot};
+use crate::steam;
+use crate::store::{SaveStore, Saves};
+
+/// Something that explains a missing save, or a copy of it we could restore.
+#[derive(Debug, Clone)]
+pub enum Finding {
+    /// The slot's file exists but is empty, as when a write was cut short.
+    EmptySave(PathBuf),
+    /// Steam Cloud has its own copy of the slot.
+    SteamCloudCopy(PathBuf),
+    /// Another installation of the game has the slot.
+    OtherInstallation(PathBuf),
+    /// The saves are in a OneDrive folder, which may keep files online only.
+    OneDriveFolder(PathBuf),
+    /// A renamed copy of the slot's file sits next to it, e.g. one the game
+    /// or an antivirus set aside.
+    SetAside(PathBuf),
+    /// The latest backup of the slot.
+    Backup(SaveFile),
+}
+
+impl Finding {
+    /// Whether [`fix`] can do anything about this.
+    pub fn is_fixable(&self) -> bool {
+        !matches!(self, Finding::EmptySave(_) | Finding::OneDriveFolder(_))
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Finding::EmptySave(path) => {
+                write!(f, "{} is empty, so the game sees no save", path.display())
+            }
+            Finding::SteamCloudCopy(path) => {
+                write!(f, "Steam Cloud has a copy at {}", path.display())
+            }
+            Finding::OtherInstallation(path) => {
+                write!(f, "another installation has a copy at {}", path.display())
+            }
+            Finding::OneDriveFolder(path) => write!(
+                f,
+                "the saves are in a OneDrive folder, {}, which may keep them online only",
+                path.display()
+            ),
+            Finding::SetAside(path) => {
+                write!(f, "a renamed copy was set aside at {}", path.display())
+            }
+            Finding::Backup(save) => write!(f, "there is a backup, {}", save.display_name()),
+        }
+    }
+}
+
+/// Checks, in order, everything that commonly makes `chapter`'s `slot` go
+/// missing, given `saves` as loaded from `store`.
+pub fn diagnose(store: &SaveStore, saves: &Saves, chapter: Chapter, slot: Slot) -> Vec<Finding> {
+    let profile = store.profile();
+    let filename = profile.save_filename(chapter, slot);
+    let game_path = store.game_directory().join(&filename);
+    let mut findings = Vec::new();
+
+    if fs::metadata(&game_path).is_ok_and(|metadata| metadata.len() == 0) {
+        findings.push(Finding::EmptySave(game_path));
+    }
+
+    if let Some(app_id) = profile.steam_app_id {
+        findings.extend(
+            steam::cloud_copies(app_id, &filename)
+                .into_iter()
+                .filter(|path| is_non_empty(path))
+                .map(Finding::SteamCloudCopy),
+        );
+    }
+
+    findings.extend(
+        profile
+            .installations()
+            .into_iter()
+            .filter(|directory| directory != store.game_directory())
+            .map(|directory| directory.join(&filename))
+            .filter(|path| is_non_empty(path))
+            .map(Finding::OtherInstallation),
+    );
+
+    let in_onedrive = store.game_directory().components().any(|component| {
+        component
+            .as_os_str()
+            .to_string_lossy()
+            .starts_with("OneDrive")
+    });
+    if in_onedrive {
+        findings.push(Finding::OneDriveFolder(
+            store.game_directory().to_path_buf(),
+        ));
+    }
+
+    findings.extend(
+        set_aside_copies(store.game_directory(), &filename)
+            .into_iter()
+            .map(Finding::SetAside),
+    );
+
+    let latest_backup = saves
+        .local
+        .iter()
+        .filter(|save| save.chapter == chapter && save.slot == slot)
+        .max_by_key(|save| save.id());
+    if let Some(backup) = latest_backup {
+        findings.push(Finding::Backup(backup.clone()));
+    }
+
+    findings
+}
+
+fn is_non_empty(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+}
+
+/// Files in `directory` named like `filename` plus a suffix, such as
+/// `filech1_0.bak` or `filech1_0 (1)`, but not another slot's file.
+fn set_aside_copies(directory: &Path, filename: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut copies = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(filename))
+                .and_then(|suffix| suffix.chars().next())
+                .is_some_and(|first| !first.is_ascii_digit())
+        })
+        .filter(|path| is_non_empty(path))
+        .collect::<Vec<_>>();
+    copies.sort();
+    copies
+}
+
+/// Restores `chapter`'s `slot` from what `finding` found. Whatever is in the
+/// slot now is backed up first, and copies from outside the backup directory
+/// are added to it, so nothing is lost either way.
+pub fn fix(store: &SaveStore, chapter: Chapter, slot: Slot, finding: &Finding) -> io::Result<()> {
+    let source = match finding {
+        Finding::SteamCloudCopy(path)
+        | Finding::OtherInstallation(path)
+        | Finding::SetAside(path) => path,
+        Finding::Backup(save) => &save.path,
+        Finding::EmptySave(_) | Finding::OneDriveFolder(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "there is nothing to restore from here",
+            ));
+        }
+    };
+
+    let game_path = store
+        .game_directory()
+        .join(store.profile().save_filename(chapter, slot));
+    if is_non_empty(&game_path) {
+        store.backup(chapter, slot)?;
+    }
+    let backup_path = match finding {
+        Finding::Backup(_) => source.clone(),
+        _ => store.import(chapter, slot, &fs::read(source)?)?,
+    };
+    store.restore(&backup_path, chapter, slot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+
+    #[test]
+    fn finds_set_aside_copies_and_backups_then_fixes() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+
+        let contents = SyntheticSave::default().to_bytes();
+        fs::write(store.game_directory().join("filech1_0"), &contents).unwrap();
+        store.backup(1, 0).unwrap();
+        fs::write(store.game_directory().join("filech1_0"), "").unwrap();
+        fs::write(store.game_directory().join("filech1_0.bak"), &contents).unwrap();
+        // Another slot's file is not a copy of this one.
+        fs::write(store.game_directory().join("filech1_01"), &contents).unwrap();
+
+        let findings = diagnose(&store, &store.load().unwrap(), 1, 0);
+        let kinds = findings
+            .iter()
+            .map(|finding| match finding {
+                Finding::EmptySave(_) => "empty",
+                Finding::SetAside(_) => "set aside",
+                Finding::Backup(_) => "backup",
+                _ => "other",
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec!["empty", "set aside", "backup"]);
+        assert!(!findings[0].is_fixable());
+
+        fix(&store, 1, 0, &findings[1]).unwrap();
+        assert_eq!(
+            fs::read(store.game_directory().join("filech1_0")).unwrap(),
+            contents
+        );
+        assert_eq!(store.load().unwrap().local.len(), 2);
+    }
+}
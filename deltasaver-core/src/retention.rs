@@ -0,0 +1,295 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Deciding which backups are old enough to delete, so the backup directory
+//! does not grow forever, following the rules under `[retention]` in
+//! `config.toml`.
+//!
+//! Each slot's backups are considered on their own, newest first, and those
+//! of each [`crate::branch`] apart from the others'. A backup is
+//! kept if it is one of the slot's newest [`RetentionPolicy::keep_last`], and
+//! otherwise deleted once it is older than [`RetentionPolicy::max_age_days`].
+//! Past [`RetentionPolicy::daily_after_days`], only the newest backup of each
+//! day is kept. With only `keep_last` set, everything else is deleted.
+//! Whatever these rules keep, a slot never has more than
+//! [`RetentionPolicy::max_per_slot`] backups on each branch, its oldest going
+//! first, so that one playthrough's backups never push out another's.
+//! Pinned backups, see [`crate::index::Entry::pinned`], are never deleted,
+//! though they count towards that cap.
+//!
+//! Deleted backups go to the trash, which is emptied of anything older than
+//! [`RetentionPolicy::trash_days`] on every prune.
+
+use chrono::{DateTime, Local, NaiveDate};
+use std::collections::BTreeMap;
+use std::io;
+use std::time::{Duration, SystemTime};
+
+use crate::branch::Branches;
+use crate::config::RetentionPolicy;
+use crate::profile::backup_created_secs;
+use crate::save::SaveFile;
+use crate::store::SaveStore;
+use crate::undo;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The backups among `saves` that `policy` would delete at `now`, oldest
+/// first, never including those `is_pinned`. Backups whose filename does not
+/// record when they were taken are always kept.
+pub fn prune_candidates(
+    saves: &[SaveFile],
+    policy: &RetentionPolicy,
+    is_pinned: impl Fn(&SaveFile) -> bool,
+    now: SystemTime,
+) -> Vec<SaveFile> {
+    if policy.is_empty() {
+        return Vec::new();
+    }
+
+    let mut by_slot = BTreeMap::<_, Vec<_>>::new();
+    for save in saves {
+        let created = save
+            .path
+            .file_name()
+            .and_then(|filename| filename.to_str())
+            .and_then(backup_created_secs);
+        if let Some(created) = created {
+            by_slot
+                .entry((save.chapter, save.slot))
+                .or_default()
+                .push((created, save));
+        }
+    }
+
+    let now = now
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut candidates = Vec::new();
+    for backups in by_slot.values_mut() {
+        // IDs break ties between backups taken in the same second, since
+        // ULIDs sort by when they were made.
+        backups.sort_by(|(a, a_save), (b, b_save)| {
+            b.cmp(a).then_with(|| b_save.id().cmp(&a_save.id()))
+        });
+        let mut days_kept = Vec::new();
+        let mut kept = Vec::with_capacity(backups.len());
+        for (rank, (created, save)) in backups.iter().enumerate() {
+            let age_days = now.saturating_sub(*created) / SECS_PER_DAY;
+            let keep = if policy.keep_last.is_some_and(|keep_last| rank < keep_last) {
+                true
+            } else if policy
+                .max_age_days
+                .is_some_and(|max_age_days| age_days >= max_age_days)
+            {
+                false
+            } else if let Some(daily_after_days) = policy.daily_after_days {
+                let day = local_day(*created);
+                if age_days < daily_after_days || !days_kept.contains(&day) {
+                    days_kept.push(day);
+                    true
+                } else {
+                    false
+                }
+            } else {
+                policy.keep_last.is_none() || policy.max_age_days.is_some()
+            };
+            kept.push(keep || is_pinned(save));
+        }
+        if let Some(max_per_slot) = policy.max_per_slot {
+            let mut count = kept.iter().filter(|keep| **keep).count();
+            for (keep, (_, save)) in kept.iter_mut().zip(backups.iter()).rev() {
+                if count <= max_per_slot {
+                    break;
+                }
+                if *keep && !is_pinned(save) {
+                    *keep = false;
+                    count -= 1;
+                }
+            }
+        }
+        for (keep, (created, save)) in kept.into_iter().zip(backups.iter()) {
+            if !keep {
+                candidates.push((*created, (*save).clone()));
+            }
+        }
+    }
+
+    candidates.sort_by_key(|(created, _)| *created);
+    candidates.into_iter().map(|(_, save)| save).collect()
+}
+
+fn local_day(secs: u64) -> NaiveDate {
+    let time: DateTime<Local> = (SystemTime::UNIX_EPOCH + Duration::from_secs(secs)).into();
+    time.date_naive()
+}
+
+/// Moves the backups in `store` that `policy` no longer keeps to the trash,
+/// returning them, and purges what has been in the trash too long. Pinned
+/// backups and those of frozen slots are left alone, as is the backup the
+/// last restore would be undone with.
+pub fn prune(store: &SaveStore, policy: &RetentionPolicy) -> io::Result<Vec<SaveFile>> {
+    store.purge_trash_older_than(policy.trash_duration())?;
+    if policy.is_empty() {
+        return Ok(Vec::new());
+    }
+    let saves = store
+        .load()
+        .map_err(|error| io::Error::other(error.to_string()))?;
+    let undo_backup = undo::last_restore(store)?.and_then(|last| last.pre_restore);
+    let branches = Branches::load(store.local_directory())?;
+    let entries = store.index()?.entries()?;
+    let mut by_branch = BTreeMap::<_, Vec<_>>::new();
+    for save in saves.local {
+        by_branch
+            .entry(branches.branch_of(&save).to_string())
+            .or_default()
+            .push(save);
+    }
+    let is_pinned = |save: &SaveFile| {
+        save.id()
+            .and_then(|id| entries.get(id))
+            .is_some_and(|entry| entry.pinned)
+    };
+    let now = SystemTime::now();
+    let candidates = by_branch
+        .values()
+        .flat_map(|saves| prune_candidates(saves, policy, is_pinned, now))
+        .collect::<Vec<_>>();
+    let mut pruned = Vec::new();
+    for save in candidates {
+        if store.frozen_until(save.chapter, save.slot)?.is_some()
+            || (undo_backup.is_some() && save.id() == undo_backup.as_deref())
+        {
+            continue;
+        }
+        store.delete(&save.path)?;
+        pruned.push(save);
+    }
+    Ok(pruned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameProfile;
+    use crate::fixtures::store_in;
+    use crate::save::Slot;
+    use std::path::PathBuf;
+
+    fn backup_at(profile: &GameProfile, slot: Slot, created: u64) -> SaveFile {
+        let id = format!("{}_{}", created, slot);
+        SaveFile {
+            path: PathBuf::from(profile.backup_filename(1, slot, "hash", &id)),
+            chapter: 1,
+            slot,
+            hash: Some("hash".to_string()),
+            modified: None,
+            size: None,
+            is_local: true,
+        }
+    }
+
+    #[test]
+    fn keeps_the_newest_then_one_a_day_then_nothing() {
+        let profile = GameProfile::deltarune(PathBuf::from("game"));
+        let now_secs = 100 * SECS_PER_DAY;
+        let noon = |days_ago: u64| now_secs - days_ago * SECS_PER_DAY - SECS_PER_DAY / 2;
+        let saves = vec![
+            backup_at(&profile, 0, noon(0)),
+            backup_at(&profile, 0, noon(1)),
+            backup_at(&profile, 0, noon(1) - 60),
+            backup_at(&profile, 0, noon(10)),
+            backup_at(&profile, 0, noon(10) - 60),
+            backup_at(&profile, 0, noon(40)),
+            backup_at(&profile, 1, noon(40)),
+        ];
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(now_secs);
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            daily_after_days: Some(7),
+            max_age_days: Some(30),
+            trash_days: None,
+            max_per_slot: None,
+        };
+        let pruned = prune_candidates(&saves, &policy, |_| false, now);
+        assert_eq!(
+            pruned.iter().map(|save| &save.path).collect::<Vec<_>>(),
+            vec![&saves[5].path, &saves[4].path]
+        );
+
+        assert!(prune_candidates(&saves, &RetentionPolicy::default(), |_| false, now).is_empty());
+        let keep_two = RetentionPolicy {
+            keep_last: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(prune_candidates(&saves, &keep_two, |_| false, now).len(), 4);
+    }
+
+    #[test]
+    fn backups_past_keep_last_wait_for_max_age_days() {
+        let profile = GameProfile::deltarune(PathBuf::from("game"));
+        let now_secs = 100 * SECS_PER_DAY;
+        let saves = [0, 1, 40]
+            .map(|days_ago| backup_at(&profile, 0, now_secs - days_ago * SECS_PER_DAY - 60))
+            .to_vec();
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(now_secs);
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            max_age_days: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(
+            prune_candidates(&saves, &policy, |_| false, now)
+                .iter()
+                .map(|save| &save.path)
+                .collect::<Vec<_>>(),
+            vec![&saves[2].path]
+        );
+    }
+
+    #[test]
+    fn slots_are_capped_by_evicting_their_oldest_unpinned_backups() {
+        let profile = GameProfile::deltarune(PathBuf::from("game"));
+        let saves = (0..5)
+            .map(|i| backup_at(&profile, 0, 1_000 + i))
+            .chain([backup_at(&profile, 1, 1_000)])
+            .collect::<Vec<_>>();
+        let cap_three = RetentionPolicy {
+            max_per_slot: Some(3),
+            ..Default::default()
+        };
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(2_000);
+        let oldest_pinned = |save: &SaveFile| save.path == saves[0].path;
+        assert_eq!(
+            prune_candidates(&saves, &cap_three, oldest_pinned, now)
+                .iter()
+                .map(|save| &save.path)
+                .collect::<Vec<_>>(),
+            vec![&saves[1].path, &saves[2].path]
+        );
+    }
+
+    #[test]
+    fn pinned_backups_are_never_pruned() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let ulid = |ms| ulid::Ulid::from_parts(ms, 0).to_string();
+        let pinned = store
+            .import_with_id(1, 0, b"before the fountain", &ulid(1_000))
+            .unwrap();
+        store
+            .import_with_id(1, 0, b"after the fountain", &ulid(2_000))
+            .unwrap();
+        let id = pinned.file_name().unwrap().to_str().unwrap();
+        assert!(store.index().unwrap().set_pinned(id, true).unwrap());
+
+        let keep_one = RetentionPolicy {
+            keep_last: Some(1),
+            ..Default::default()
+        };
+        assert!(prune(&store, &keep_one).unwrap().is_empty());
+        assert!(store.index().unwrap().set_pinned(id, false).unwrap());
+        assert_eq!(prune(&store, &keep_one).unwrap()[0].path, pinned);
+    }
+}
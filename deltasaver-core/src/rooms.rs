@@ -0,0 +1,126 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Names for the rooms saves are made in, which saves record only by index.
+//!
+//! The names are data, so that they can be filled in for new chapters and
+//! game updates without a new build: `data/rooms.json` is built in, and the
+//! user's `rooms.json` in the DELTASAVER config directory adds to and
+//! overrides it. Both map chapters to room indices to names:
+//!
+//! ```json
+//! {
+//!   "2": { "123": "Cyber Field", "240": "Queen's Mansion" }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::portable;
+use crate::save::Chapter;
+
+const ROOMS_FILENAME: &str = "rooms.json";
+
+const BUILTIN_ROOMS: &str = include_str!("../data/rooms.json");
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoomNames {
+    names: BTreeMap<Chapter, BTreeMap<u32, String>>,
+}
+
+impl RoomNames {
+    /// The names built into DELTASAVER.
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN_ROOMS.as_bytes()).expect("built-in room names are valid")
+    }
+
+    fn parse(contents: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            names: serde_json::from_slice(contents).map_err(io::Error::other)?,
+        })
+    }
+
+    /// Reads room names from `path`. A missing file means there are none.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// The built-in names with the user's laid over them.
+    pub fn load_default() -> io::Result<Self> {
+        let mut rooms = Self::builtin();
+        if let Some(path) = rooms_path() {
+            rooms.extend(Self::load(&path)?);
+        }
+        Ok(rooms)
+    }
+
+    /// Adds the names in `other`, replacing any for the same room.
+    pub fn extend(&mut self, other: RoomNames) {
+        for (chapter, names) in other.names {
+            self.names.entry(chapter).or_default().extend(names);
+        }
+    }
+
+    pub fn name(&self, chapter: Chapter, room: u32) -> Option<&str> {
+        self.names.get(&chapter)?.get(&room).map(String::as_str)
+    }
+
+    /// The room's name, or failing that its index, e.g. `Room 42`.
+    pub fn describe(&self, chapter: Chapter, room: u32) -> String {
+        match self.name(chapter, room) {
+            Some(name) => name.to_string(),
+            None => format!("Room {}", room),
+        }
+    }
+}
+
+/// Where the user's room names are kept, if this platform has a config
+/// directory.
+pub fn rooms_path() -> Option<PathBuf> {
+    Some(portable::config_directory()?.join(ROOMS_FILENAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save::CHAPTER_COUNT;
+
+    #[test]
+    fn user_names_add_to_and_override_the_builtin_ones() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join(ROOMS_FILENAME);
+        assert_eq!(RoomNames::load(&path).unwrap(), RoomNames::default());
+
+        let mut rooms = RoomNames::parse(br#"{"2": {"1": "Old name", "2": "Kept"}}"#).unwrap();
+        fs::write(
+            &path,
+            r#"{"2": {"1": "Cyber Field"}, "3": {"7": "Green Room"}}"#,
+        )
+        .unwrap();
+        rooms.extend(RoomNames::load(&path).unwrap());
+
+        assert_eq!(rooms.describe(2, 1), "Cyber Field");
+        assert_eq!(rooms.describe(2, 2), "Kept");
+        assert_eq!(rooms.describe(3, 7), "Green Room");
+        assert_eq!(rooms.describe(1, 7), "Room 7");
+    }
+
+    #[test]
+    fn builtin_names_are_for_chapters_the_game_has() {
+        // Panics if the built-in table does not parse.
+        let rooms = RoomNames::builtin();
+        for (chapter, names) in &rooms.names {
+            assert!((1..=CHAPTER_COUNT).contains(chapter), "Chapter {}", chapter);
+            for (room, name) in names {
+                assert!(!name.trim().is_empty(), "Chapter {} room {}", chapter, room);
+                assert_eq!(rooms.describe(*chapter, *room), *name);
+            }
+        }
+    }
+}
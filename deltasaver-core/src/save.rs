@@ -0,0 +1,152 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+pub type Chapter = u8;
+pub type Slot = u8;
+
+// tricky tony better not pull a trick
+pub const CHAPTER_COUNT: Chapter = 7;
+
+pub const BUILTIN_SLOT_MAX_INDEX: Slot = 2;
+
+/// When a chapter is finished, the game writes completion data for slot `n`
+/// to `filech{chapter}_{n + 3}`, which the next chapter reads on startup.
+pub const COMPLETION_SLOT_OFFSET: Slot = 3;
+
+/// Data shared across all chapters, such as which chapters have been reached.
+pub const PERSISTENT_FILENAME: &str = "dr.ini";
+
+/// Crockford's base32 alphabet, which leaves out letters that are easily
+/// confused with digits when a code is read aloud or retyped.
+const SHARE_CODE_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const SHARE_CODE_LENGTH: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct SaveFile {
+    pub path: PathBuf,
+    pub chapter: Chapter,
+    pub slot: Slot,
+    pub hash: Option<String>,
+    pub modified: Option<SystemTime>,
+    /// In bytes.
+    pub size: Option<u64>,
+    pub is_local: bool,
+}
+
+impl SaveFile {
+    pub fn display_name(&self) -> String {
+        if self.is_local {
+            format!(
+                "Chapter {}, Slot {} ({})",
+                self.chapter,
+                self.slot + 1,
+                self.hash.as_ref().map(|h| &h[..8]).unwrap_or("local")
+            )
+        } else {
+            format!("Chapter {}, Slot {}", self.chapter, self.slot + 1)
+        }
+    }
+
+    /// Like [`Self::display_name`], but naming a backup by `label`, its name
+    /// or note, rather than its hash if it has one, e.g. `Chapter 2, Slot 1
+    /// (before Spamton NEO)`.
+    pub fn display_name_with_label(&self, label: Option<&str>) -> String {
+        match label.map(str::trim).filter(|label| !label.is_empty()) {
+            Some(label) if self.is_local => format!(
+                "Chapter {}, Slot {} ({})",
+                self.chapter,
+                self.slot + 1,
+                label
+            ),
+            _ => self.display_name(),
+        }
+    }
+
+    /// A stable identifier for a backup, which is simply its filename in the
+    /// backup directory.
+    pub fn id(&self) -> Option<&str> {
+        self.path.file_name()?.to_str()
+    }
+
+    /// A short code identifying this save's contents, e.g. `1B3F-9QZK`. Since
+    /// it is derived from the content hash, the same save always has the same
+    /// code on every machine.
+    pub fn share_code(&self) -> Option<String> {
+        let hash = self.hash.as_ref()?;
+        let bits = u64::from_str_radix(hash.get(..10)?, 16).ok()?;
+
+        let mut code = String::with_capacity(SHARE_CODE_LENGTH + 1);
+        for i in 0..SHARE_CODE_LENGTH {
+            if i == SHARE_CODE_LENGTH / 2 {
+                code.push('-');
+            }
+            let index = (bits >> (5 * (SHARE_CODE_LENGTH - 1 - i))) & 0b11111;
+            code.push(SHARE_CODE_ALPHABET[index as usize] as char);
+        }
+        Some(code)
+    }
+}
+
+/// Normalizes a share code typed by the user, ignoring case and separators and
+/// accepting the usual look-alike substitutions.
+pub fn normalize_share_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        })
+        .collect()
+}
+
+/// The lowercase hex SHA-256 digest of `contents`.
+pub fn hash_contents(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes_name_backups_in_place_of_their_hash() {
+        let save = SaveFile {
+            path: PathBuf::new(),
+            chapter: 2,
+            slot: 0,
+            hash: Some(hash_contents(b"kris")),
+            modified: None,
+            size: None,
+            is_local: true,
+        };
+        assert_eq!(
+            save.display_name_with_label(Some(" before Spamton NEO ")),
+            "Chapter 2, Slot 1 (before Spamton NEO)"
+        );
+        assert_eq!(save.display_name_with_label(Some("")), save.display_name());
+        assert_eq!(save.display_name_with_label(None), save.display_name());
+    }
+
+    #[test]
+    fn share_codes_round_trip_through_normalization() {
+        let save = SaveFile {
+            path: PathBuf::new(),
+            chapter: 1,
+            slot: 0,
+            hash: Some(hash_contents(b"kris")),
+            modified: None,
+            size: None,
+            is_local: true,
+        };
+        let code = save.share_code().unwrap();
+        assert_eq!(code.len(), SHARE_CODE_LENGTH + 1);
+        assert_eq!(
+            normalize_share_code(&code.to_lowercase()),
+            code.replace('-', "")
+        );
+    }
+}
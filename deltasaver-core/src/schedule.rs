@@ -0,0 +1,95 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Backing up every occupied slot on an interval, for as long as a frontend
+//! keeps a [`Scheduler`] around and asks it whether a backup is due.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use crate::config::SchedulePolicy;
+use crate::save::{Chapter, SaveFile, Slot};
+use crate::store::SaveStore;
+
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    interval: Duration,
+    next: SystemTime,
+}
+
+impl Scheduler {
+    /// A scheduler whose first backup is one `interval` after `now`.
+    pub fn new(interval: Duration, now: SystemTime) -> Self {
+        Self {
+            interval,
+            next: now + interval,
+        }
+    }
+
+    pub fn is_due(&self, now: SystemTime) -> bool {
+        now >= self.next
+    }
+
+    /// How long until the next backup, which is zero once it is due.
+    pub fn time_until_next(&self, now: SystemTime) -> Duration {
+        self.next.duration_since(now).unwrap_or_default()
+    }
+
+    /// Schedules the next backup one interval after `now`.
+    pub fn advance(&mut self, now: SystemTime) {
+        self.next = now + self.interval;
+    }
+}
+
+/// The occupied slots among `game_saves` that `policy` has not turned off for
+/// `store`'s profile, in display order.
+pub fn scheduled_slots(
+    store: &SaveStore,
+    game_saves: &HashMap<(Chapter, Slot), SaveFile>,
+    policy: &SchedulePolicy,
+) -> Vec<(Chapter, Slot)> {
+    let profile = store.profile();
+    profile
+        .slots()
+        .filter(|slot| game_saves.contains_key(slot))
+        .filter(|&(chapter, slot)| {
+            policy.is_enabled_for(&profile.name, &profile.save_filename(chapter, slot))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+
+    #[test]
+    fn backups_come_due_each_interval() {
+        let start = SystemTime::UNIX_EPOCH;
+        let interval = Duration::from_secs(15 * 60);
+        let mut scheduler = Scheduler::new(interval, start);
+        assert!(!scheduler.is_due(start));
+        assert_eq!(scheduler.time_until_next(start), interval);
+
+        let later = start + interval + Duration::from_secs(1);
+        assert!(scheduler.is_due(later));
+        assert_eq!(scheduler.time_until_next(later), Duration::ZERO);
+        scheduler.advance(later);
+        assert!(!scheduler.is_due(later));
+    }
+
+    #[test]
+    fn only_occupied_enabled_slots_are_scheduled() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        for slot in 0..2 {
+            SyntheticSave::default()
+                .write_to(store.game_directory(), slot)
+                .unwrap();
+        }
+
+        let mut policy = SchedulePolicy::default();
+        policy.set_enabled_for("DELTARUNE", "filech1_1", false);
+        let saves = store.load().unwrap();
+        assert_eq!(scheduled_slots(&store, &saves.game, &policy), vec![(1, 0)]);
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Finding backups by whatever the user remembers about them.
+//!
+//! A query is a list of words, all of which a backup must match, ignoring
+//! case. Most words match a backup whose name, note, or a tag contains them, or
+//! whose hash, share code, or date starts with them. A few prefixes narrow a
+//! word down:
+//!
+//! - `chapter:2` or `ch:2` matches backups of chapter 2.
+//! - `tag:boss` matches backups tagged exactly `boss`.
+//! - `date:2025-06` matches backups taken in June 2025, local time.
+//!
+//! The matches are then listed in the user's [`BackupOrder`].
+
+use chrono::{DateTime, Local};
+use std::cmp::Reverse;
+use std::time::{Duration, SystemTime};
+
+use crate::config::BackupOrder;
+use crate::index;
+use crate::profile::backup_created_secs;
+use crate::save::{Chapter, SaveFile, normalize_share_code};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Chapter(Chapter),
+    Tag(String),
+    Date(String),
+    Text(String),
+}
+
+/// A parsed search query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Self {
+        let terms = query
+            .split_whitespace()
+            .map(|word| {
+                let word = word.to_lowercase();
+                let Some((prefix, value)) = word.split_once(':') else {
+                    return Term::Text(word);
+                };
+                match prefix {
+                    "chapter" | "ch" => match value.parse() {
+                        Ok(chapter) => Term::Chapter(chapter),
+                        Err(_) => Term::Text(word),
+                    },
+                    "tag" => Term::Tag(value.to_string()),
+                    "date" => Term::Date(value.to_string()),
+                    _ => Term::Text(word),
+                }
+            })
+            .collect();
+        Self { terms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Whether `save`, with `entry` its entry in the index if it has one,
+    /// matches every word of the query.
+    pub fn matches(&self, save: &SaveFile, entry: Option<&index::Entry>) -> bool {
+        let date = created_date(save);
+        let tags = || entry.into_iter().flat_map(|entry| &entry.tags);
+        self.terms.iter().all(|term| match term {
+            Term::Chapter(chapter) => save.chapter == *chapter,
+            Term::Tag(tag) => tags().any(|other| other.to_lowercase() == *tag),
+            Term::Date(prefix) => date.as_ref().is_some_and(|date| date.starts_with(prefix)),
+            Term::Text(text) => {
+                entry
+                    .into_iter()
+                    .flat_map(|entry| [&entry.name, &entry.note])
+                    .flatten()
+                    .any(|label| label.to_lowercase().contains(text))
+                    || tags().any(|tag| tag.to_lowercase().contains(text))
+                    || save
+                        .hash
+                        .as_ref()
+                        .is_some_and(|hash| hash.starts_with(text))
+                    || save.share_code().is_some_and(|code| {
+                        let text = normalize_share_code(text);
+                        !text.is_empty() && normalize_share_code(&code).starts_with(&text)
+                    })
+                    || date.as_ref().is_some_and(|date| date.starts_with(text))
+            }
+        })
+    }
+}
+
+/// Sorts `saves` in `order`, where `entry_of` finds a backup's entry in the
+/// index if it has one. Backups that tie stay newest first.
+pub fn sort<'a>(
+    saves: &mut [&SaveFile],
+    order: BackupOrder,
+    entry_of: impl Fn(&SaveFile) -> Option<&'a index::Entry>,
+) {
+    saves.sort_by_key(|save| Reverse(created(save)));
+    match order {
+        BackupOrder::NewestFirst => {}
+        BackupOrder::OldestFirst => saves.reverse(),
+        BackupOrder::ByNote => saves.sort_by_cached_key(|save| {
+            let label = entry_of(save)
+                .and_then(index::Entry::label)
+                .map(str::to_lowercase);
+            (label.is_none(), label)
+        }),
+        BackupOrder::BySize => saves
+            .sort_by_key(|save| Reverse(entry_of(save).and_then(|entry| entry.size).or(save.size))),
+    }
+}
+
+/// When `save` was taken, or failing that, last modified.
+fn created(save: &SaveFile) -> Option<SystemTime> {
+    save.id()
+        .and_then(backup_created_secs)
+        .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+        .or(save.modified)
+}
+
+/// The local date `save` was taken, as `YYYY-MM-DD`, or failing that, last
+/// modified.
+fn created_date(save: &SaveFile) -> Option<String> {
+    let created = created(save)?;
+    Some(
+        DateTime::<Local>::from(created)
+            .format("%Y-%m-%d")
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save::hash_contents;
+    use std::collections::BTreeSet;
+    use std::path::PathBuf;
+
+    #[test]
+    fn every_word_must_match_the_note_tags_hash_chapter_or_date() {
+        let hash = hash_contents(b"kris");
+        let save = SaveFile {
+            path: PathBuf::from(format!("filech2_0_{}_1700000000_0", hash)),
+            chapter: 2,
+            slot: 0,
+            hash: Some(hash.clone()),
+            modified: None,
+            size: None,
+            is_local: true,
+        };
+        let entry = index::Entry {
+            note: Some("Just before Spamton NEO".to_string()),
+            tags: BTreeSet::from(["boss".to_string()]),
+            ..Default::default()
+        };
+        let matches = |query: &str| Query::parse(query).matches(&save, Some(&entry));
+
+        assert!(Query::parse("  ").is_empty());
+        assert!(matches(""));
+        assert!(matches("spamton"));
+        assert!(matches("BOS"));
+        assert!(matches(&hash[..6]));
+        assert!(matches(&save.share_code().unwrap()));
+        assert!(matches("chapter:2 tag:boss neo"));
+        assert!(matches("date:2023-11"));
+        assert!(!matches("ch:1"));
+        assert!(!matches("tag:bos"));
+        assert!(!matches("spamton snowgrave"));
+        assert!(!Query::parse("spamton").matches(&save, None));
+    }
+
+    #[test]
+    fn backups_sort_by_time_note_or_size() {
+        let backup = |secs: u64| SaveFile {
+            path: PathBuf::from(format!("filech1_0_{}_{}_0", hash_contents(b"x"), secs)),
+            chapter: 1,
+            slot: 0,
+            hash: Some(hash_contents(b"x")),
+            modified: None,
+            size: Some(secs),
+            is_local: true,
+        };
+        let (old, middle, new) = (
+            backup(1_700_000_000),
+            backup(1_700_000_100),
+            backup(1_700_000_200),
+        );
+        let entry = |note: &str| index::Entry {
+            note: Some(note.to_string()),
+            ..Default::default()
+        };
+        let entries = [
+            (old.path.clone(), entry("b")),
+            (middle.path.clone(), entry("A")),
+        ];
+        let entry_of = |save: &SaveFile| {
+            entries
+                .iter()
+                .find(|(path, _)| *path == save.path)
+                .map(|(_, entry)| entry)
+        };
+        let sorted = |order| {
+            let mut saves = vec![&middle, &old, &new];
+            sort(&mut saves, order, entry_of);
+            saves
+                .iter()
+                .map(|save| save.path.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let newest_first = vec![new.path.clone(), middle.path.clone(), old.path.clone()];
+        assert_eq!(sorted(BackupOrder::NewestFirst), newest_first);
+        assert_eq!(sorted(BackupOrder::BySize), newest_first);
+        assert_eq!(
+            sorted(BackupOrder::OldestFirst),
+            vec![old.path.clone(), middle.path.clone(), new.path.clone()]
+        );
+        assert_eq!(
+            sorted(BackupOrder::ByNote),
+            vec![middle.path.clone(), old.path.clone(), new.path.clone()]
+        );
+    }
+}
@@ -0,0 +1,303 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Whole-playthrough snapshots: every file in the game's save directory,
+//! every slot of every chapter along with the files the game keeps beside
+//! them, copied aside together under a name, and put back together.
+//!
+//! Each snapshot is a directory under `snapshots` in the backup directory
+//! named `{name}_{seconds since the epoch}`, the same as the snapshots taken
+//! when the game updates, which can be restored the same way.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::store::{SaveStore, copy_directory_files};
+
+/// Staging for a restore, beside the game's save directory so it can be
+/// renamed into place.
+const RESTORE_SUFFIX: &str = ".deltasaver-restore";
+
+/// The save directory being replaced, until the restore finishes.
+const REPLACED_SUFFIX: &str = ".deltasaver-replaced";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub name: String,
+    pub created: SystemTime,
+    pub path: PathBuf,
+}
+
+impl Snapshot {
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let (name, secs) = path.file_name()?.to_str()?.rsplit_once('_')?;
+        Some(Self {
+            name: name.to_string(),
+            created: SystemTime::UNIX_EPOCH + Duration::from_secs(secs.parse().ok()?),
+            path,
+        })
+    }
+
+    /// The directory name, unique among a store's snapshots.
+    pub fn id(&self) -> Option<&str> {
+        self.path.file_name()?.to_str()
+    }
+}
+
+/// Copies every file in the game's save directory into a new snapshot named
+/// `name`. Fails while the backups are encrypted, since snapshots are not.
+pub fn take(store: &SaveStore, name: &str) -> io::Result<Snapshot> {
+    store.check_unencrypted("snapshots")?;
+    let name = sanitize(name, "snapshot");
+    let created = SystemTime::now();
+    let secs = created
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = store
+        .snapshots_directory()
+        .join(format!("{}_{}", name, secs));
+    if path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("a snapshot named {} was just taken", name),
+        ));
+    }
+    copy_directory_files(store.game_directory(), &path)?;
+    Ok(Snapshot {
+        name,
+        created: SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+        path,
+    })
+}
+
+/// Every snapshot of `store`, newest first.
+pub fn list(store: &SaveStore) -> io::Result<Vec<Snapshot>> {
+    let mut snapshots = Vec::new();
+    match fs::read_dir(store.snapshots_directory()) {
+        Ok(entries) => {
+            for entry in entries {
+                let entry = entry?;
+                if entry.file_type()?.is_dir()
+                    && let Some(snapshot) = Snapshot::from_path(entry.path())
+                {
+                    snapshots.push(snapshot);
+                }
+            }
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => return Err(error),
+    }
+    snapshots.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| b.path.cmp(&a.path)));
+    Ok(snapshots)
+}
+
+/// The snapshot of `store` whose [`Snapshot::id`] is `id`.
+pub fn find(store: &SaveStore, id: &str) -> io::Result<Snapshot> {
+    list(store)?
+        .into_iter()
+        .find(|snapshot| snapshot.id() == Some(id))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no snapshot {}", id)))
+}
+
+/// Puts the game's save directory back exactly as it was in `snapshot`,
+/// removing files it did not have, after snapshotting it as it is now. That
+/// snapshot is returned, so the restore can be undone.
+///
+/// The new directory is assembled beside the current one and swapped in by
+/// renaming, so the game never sees a mix of the two. Fails without changing
+/// anything if it would overwrite a frozen slot, and if any file cannot be
+/// put in place, everything is rolled back.
+pub fn restore(store: &SaveStore, snapshot: &Snapshot) -> io::Result<Snapshot> {
+    let game_directory = store.game_directory();
+    let profile = store.profile();
+    for chapter in 1..=profile.chapter_count {
+        for slot in 0..profile.slot_count {
+            let filename = profile.save_filename(chapter, slot);
+            if fs::read(game_directory.join(&filename)).ok()
+                != fs::read(snapshot.path.join(&filename)).ok()
+            {
+                store.check_not_frozen(chapter, slot)?;
+            }
+        }
+    }
+
+    let before = take(store, &format!("before restoring {}", snapshot.name))?;
+    if let Err(error) = replace_game_directory(game_directory, snapshot) {
+        let _ = fs::remove_dir_all(&before.path);
+        return Err(error);
+    }
+    Ok(before)
+}
+
+/// Swaps the files of `snapshot` in for those in `game_directory`, leaving
+/// `game_directory` as it was if that fails. If `game_directory` is a
+/// symlink or junction, say to keep the saves in a synced folder, the
+/// directory it points to is the one swapped, so the link stays in place.
+fn replace_game_directory(game_directory: &Path, snapshot: &Snapshot) -> io::Result<()> {
+    let game_directory = &fs::canonicalize(game_directory)?;
+    let staging = sibling(game_directory, RESTORE_SUFFIX);
+    let replaced = sibling(game_directory, REPLACED_SUFFIX);
+    for leftover in [&staging, &replaced] {
+        match fs::remove_dir_all(leftover) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+            _ => {}
+        }
+    }
+    let mut moved = Vec::new();
+    let swapped = copy_directory_files(&snapshot.path, &staging).and_then(|()| {
+        // Snapshots only hold files, so whatever the game keeps in
+        // subdirectories carries over as it is.
+        for entry in fs::read_dir(game_directory)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                fs::rename(entry.path(), staging.join(entry.file_name()))?;
+                moved.push(entry.file_name());
+            }
+        }
+        fs::rename(game_directory, &replaced)?;
+        if let Err(error) = fs::rename(&staging, game_directory) {
+            fs::rename(&replaced, game_directory)?;
+            return Err(error);
+        }
+        Ok(())
+    });
+    if let Err(error) = swapped {
+        for name in moved {
+            fs::rename(staging.join(&name), game_directory.join(&name))?;
+        }
+        let _ = fs::remove_dir_all(&staging);
+        return Err(error);
+    }
+    fs::remove_dir_all(&replaced)
+}
+
+fn sibling(directory: &Path, suffix: &str) -> PathBuf {
+    let mut name = directory.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// `name` with anything that could not be in a directory name on every
+/// platform replaced, including path separators and dots, so it can never
+/// name a directory outside the one it is joined onto. An empty name becomes
+/// `fallback`.
+pub(crate) fn sanitize(name: &str, fallback: &str) -> String {
+    let name = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '&') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    if name.is_empty() {
+        fallback.to_string()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+
+    #[test]
+    fn restoring_a_snapshot_puts_back_every_file() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let game = store.game_directory().to_path_buf();
+        SyntheticSave::default().write_to(&game, 0).unwrap();
+        fs::write(game.join("filech1_3"), "completion").unwrap();
+        fs::write(game.join("dr.ini"), "settings").unwrap();
+
+        let snapshot = take(&store, "end of chapter 1?").unwrap();
+        assert_eq!(snapshot.name, "end of chapter 1-");
+
+        SyntheticSave {
+            gold: 7,
+            ..Default::default()
+        }
+        .write_to(&game, 0)
+        .unwrap();
+        fs::write(game.join("filech1_1"), "later").unwrap();
+        fs::create_dir_all(game.join("screenshots")).unwrap();
+
+        let before = restore(&store, &snapshot).unwrap();
+        assert_eq!(
+            fs::read(game.join("filech1_0")).unwrap(),
+            SyntheticSave::default().to_bytes()
+        );
+        assert_eq!(
+            fs::read_to_string(game.join("filech1_3")).unwrap(),
+            "completion"
+        );
+        assert_eq!(fs::read_to_string(game.join("dr.ini")).unwrap(), "settings");
+        assert!(!game.join("filech1_1").exists());
+        assert!(game.join("screenshots").is_dir());
+        assert_eq!(
+            fs::read_to_string(before.path.join("filech1_1")).unwrap(),
+            "later"
+        );
+        assert_eq!(list(&store).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn a_failed_restore_leaves_the_save_directory_as_it_was() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let game = store.game_directory().to_path_buf();
+        fs::write(game.join("screenshots"), "a file back then").unwrap();
+        let snapshot = take(&store, "old").unwrap();
+
+        // The game's directory can't be moved into the snapshot over the
+        // file of the same name.
+        fs::remove_file(game.join("screenshots")).unwrap();
+        fs::create_dir_all(game.join("screenshots")).unwrap();
+        fs::create_dir_all(game.join("mods")).unwrap();
+        SyntheticSave::default().write_to(&game, 0).unwrap();
+
+        assert!(restore(&store, &snapshot).is_err());
+        assert!(game.join("screenshots").is_dir());
+        assert!(game.join("mods").is_dir());
+        assert_eq!(
+            fs::read(game.join("filech1_0")).unwrap(),
+            SyntheticSave::default().to_bytes()
+        );
+        assert!(!sibling(&game, RESTORE_SUFFIX).exists());
+        assert_eq!(list(&store).unwrap(), vec![snapshot]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restoring_into_a_linked_save_directory_keeps_the_link() {
+        let root = tempfile::tempdir().unwrap();
+        let synced = root.path().join("synced");
+        fs::create_dir_all(&synced).unwrap();
+        std::os::unix::fs::symlink(&synced, root.path().join("game")).unwrap();
+        let store = store_in(root.path()).unwrap();
+        let game = store.game_directory().to_path_buf();
+        SyntheticSave::default().write_to(&game, 0).unwrap();
+        let snapshot = take(&store, "old").unwrap();
+        fs::write(game.join("filech1_1"), "later").unwrap();
+
+        restore(&store, &snapshot).unwrap();
+        assert!(
+            fs::symlink_metadata(&game)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(
+            fs::read(synced.join("filech1_0")).unwrap(),
+            SyntheticSave::default().to_bytes()
+        );
+        assert!(!synced.join("filech1_1").exists());
+        assert!(!sibling(&synced, REPLACED_SUFFIX).exists());
+    }
+}
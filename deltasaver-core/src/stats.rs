@@ -0,0 +1,100 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Per-chapter statistics, for comparing progress between profiles.
+
+use std::fs;
+use std::time::Duration;
+
+use crate::format;
+use crate::save::Chapter;
+use crate::store::{SaveStore, Saves};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChapterStats {
+    pub chapter: Chapter,
+    /// The longest play time among the chapter's game slots, or `None` if
+    /// none of them could be read.
+    pub play_time: Option<Duration>,
+    /// How many game slots of the chapter have a save.
+    pub slots_used: usize,
+    /// How many backups of the chapter there are.
+    pub backups: usize,
+}
+
+/// Statistics for every chapter of `store`'s profile, from `saves` as loaded
+/// from it.
+pub fn chapter_stats(store: &SaveStore, saves: &Saves) -> Vec<ChapterStats> {
+    (1..=store.profile().chapter_count)
+        .map(|chapter| {
+            let game_saves = saves
+                .game
+                .values()
+                .filter(|save| save.chapter == chapter)
+                .collect::<Vec<_>>();
+            ChapterStats {
+                chapter,
+                play_time: game_saves
+                    .iter()
+                    .filter_map(|save| read_play_time(&fs::read(&save.path).ok()?))
+                    .max(),
+                slots_used: game_saves.len(),
+                backups: saves
+                    .local
+                    .iter()
+                    .filter(|save| save.chapter == chapter)
+                    .count(),
+            }
+        })
+        .collect()
+}
+
+/// The play time recorded in a DELTARUNE save, or `None` if `contents` is not
+/// one.
+pub fn read_play_time(contents: &[u8]) -> Option<Duration> {
+    let text = std::str::from_utf8(contents).ok()?;
+    let frames = text
+        .lines()
+        .nth(format::TIME_LINE)?
+        .trim()
+        .parse::<f64>()
+        .ok()?;
+    (frames.is_finite() && frames >= 0.0)
+        .then(|| Duration::from_secs(frames as u64 / format::FRAMES_PER_SECOND))
+}
+
+/// Writes a play time as `h:mm:ss`.
+pub fn format_play_time(play_time: Duration) -> String {
+    let secs = play_time.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+
+    #[test]
+    fn play_time_is_the_longest_slot_of_each_chapter() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        for (slot, minutes) in [(0, 5), (1, 90)] {
+            SyntheticSave {
+                time_frames: minutes * 60 * format::FRAMES_PER_SECOND,
+                ..Default::default()
+            }
+            .write_to(store.game_directory(), slot)
+            .unwrap();
+        }
+        store.backup(1, 0).unwrap();
+
+        let stats = chapter_stats(&store, &store.load().unwrap());
+        assert_eq!(stats[0].play_time, Some(Duration::from_secs(90 * 60)));
+        assert_eq!(stats[0].slots_used, 2);
+        assert_eq!(stats[0].backups, 1);
+        assert_eq!(stats[1].play_time, None);
+        assert_eq!(
+            format_play_time(Duration::from_secs(90 * 60 + 5)),
+            "1:30:05"
+        );
+    }
+}
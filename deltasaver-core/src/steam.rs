@@ -0,0 +1,200 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DELTARUNE_APP_ID: u32 = 1671210;
+pub const UNDERTALE_APP_ID: u32 = 391540;
+
+/// The default Steam installation directories that exist on this machine.
+fn steam_roots() -> Vec<PathBuf> {
+    let candidates = if cfg!(target_os = "windows") {
+        vec![PathBuf::from(r"C:\Program Files (x86)\Steam")]
+    } else if cfg!(target_os = "macos") {
+        dirs::data_dir()
+            .map(|directory| directory.join("Steam"))
+            .into_iter()
+            .collect()
+    } else if cfg!(target_os = "linux") {
+        // `~/.steam/steam` is usually a symlink to the real installation, but
+        // older installs and some distributions only have one or the other.
+        // Flatpak Steam keeps everything inside its sandbox.
+        let flatpak_home = dirs::home_dir().map(|home| {
+            home.join(".var")
+                .join("app")
+                .join("com.valvesoftware.Steam")
+        });
+        [
+            dirs::home_dir().map(|home| home.join(".steam").join("steam")),
+            dirs::data_dir().map(|directory| directory.join("Steam")),
+            flatpak_home
+                .as_ref()
+                .map(|home| home.join(".local").join("share").join("Steam")),
+            flatpak_home.map(|home| home.join(".steam").join("steam")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut roots = Vec::new();
+    let mut canonical_roots = Vec::new();
+    for candidate in candidates {
+        if let Ok(canonical_root) = candidate.canonicalize()
+            && !canonical_roots.contains(&canonical_root)
+        {
+            canonical_roots.push(canonical_root);
+            roots.push(candidate);
+        }
+    }
+    roots
+}
+
+/// Every Steam library folder listed in `libraryfolders.vdf`, starting with
+/// the Steam installations themselves.
+pub fn library_folders() -> Vec<PathBuf> {
+    let mut folders = Vec::new();
+    for root in steam_roots() {
+        for folder in library_folders_of(&root) {
+            if !folders.contains(&folder) {
+                folders.push(folder);
+            }
+        }
+    }
+    folders
+}
+
+fn library_folders_of(root: &Path) -> Vec<PathBuf> {
+    let mut folders = vec![root.to_path_buf()];
+    if let Ok(contents) = fs::read_to_string(root.join("steamapps").join("libraryfolders.vdf")) {
+        for (key, value) in vdf_pairs(&contents) {
+            let folder = PathBuf::from(value);
+            if key == "path" && !folders.contains(&folder) {
+                folders.push(folder);
+            }
+        }
+    }
+    folders
+}
+
+/// The `AppData\Local` directory of the Windows user Proton runs `app_id` as
+/// in each Steam library, which is where a Windows game played on Linux or a
+/// Steam Deck keeps its saves. Only a library the game has been run from
+/// under Proton will actually have one.
+pub fn proton_local_app_data_candidates(app_id: u32) -> Vec<PathBuf> {
+    library_folders()
+        .iter()
+        .map(|folder| proton_local_app_data_in(folder, app_id))
+        .collect()
+}
+
+fn proton_local_app_data_in(library_folder: &Path, app_id: u32) -> PathBuf {
+    library_folder
+        .join("steamapps")
+        .join("compatdata")
+        .join(app_id.to_string())
+        .join("pfx/drive_c/users/steamuser/AppData/Local")
+}
+
+/// Copies of `filename` that Steam Cloud keeps for `app_id`, one per Steam
+/// user who has played the game on this machine.
+pub fn cloud_copies(app_id: u32, filename: &str) -> Vec<PathBuf> {
+    steam_roots()
+        .iter()
+        .flat_map(|root| cloud_copies_in(root, app_id, filename))
+        .collect()
+}
+
+fn cloud_copies_in(root: &Path, app_id: u32, filename: &str) -> Vec<PathBuf> {
+    let Ok(users) = fs::read_dir(root.join("userdata")) else {
+        return Vec::new();
+    };
+    users
+        .filter_map(|user| {
+            let path = user
+                .ok()?
+                .path()
+                .join(app_id.to_string())
+                .join("remote")
+                .join(filename);
+            path.is_file().then_some(path)
+        })
+        .collect()
+}
+
+/// Finds `appmanifest_<app_id>.acf` in whichever library the game is
+/// installed into.
+pub fn app_manifest_path(app_id: u32) -> Option<PathBuf> {
+    library_folders()
+        .into_iter()
+        .map(|folder| {
+            folder
+                .join("steamapps")
+                .join(format!("appmanifest_{}.acf", app_id))
+        })
+        .find(|path| path.exists())
+}
+
+/// Reads the `buildid` field of an app manifest, which Steam bumps every time
+/// it installs an update.
+pub fn read_build_id(manifest_path: &Path) -> Option<String> {
+    let contents = fs::read_to_string(manifest_path).ok()?;
+    vdf_pairs(&contents)
+        .find(|(key, _)| key == "buildid")
+        .map(|(_, value)| value)
+}
+
+/// Yields the `"key" "value"` lines of a Valve KeyValues file, ignoring the
+/// nesting since the keys we care about are unambiguous.
+fn vdf_pairs(contents: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    contents.lines().filter_map(|line| {
+        let mut fields = line.split('"').skip(1).step_by(2);
+        let key = fields.next()?;
+        let value = fields.next()?;
+        Some((key.to_string(), value.replace(r"\\", r"\")))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_libraries_and_proton_prefixes() {
+        let root = tempfile::tempdir().unwrap();
+        let steam = root.path().join("Steam");
+        let library = root.path().join("SD Card");
+        fs::create_dir_all(steam.join("steamapps")).unwrap();
+        fs::write(
+            steam.join("steamapps").join("libraryfolders.vdf"),
+            format!(
+                "\"libraryfolders\"\n{{\n\t\"0\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t}}\n}}\n",
+                library.display()
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            library_folders_of(&steam),
+            vec![steam.clone(), library.clone()]
+        );
+
+        let local_app_data = proton_local_app_data_in(&library, DELTARUNE_APP_ID);
+        assert!(
+            local_app_data.ends_with(
+                "steamapps/compatdata/1671210/pfx/drive_c/users/steamuser/AppData/Local"
+            )
+        );
+        assert!(local_app_data.starts_with(&library));
+
+        let remote = steam.join("userdata/12345/1671210/remote");
+        fs::create_dir_all(&remote).unwrap();
+        fs::write(remote.join("filech1_0"), "save").unwrap();
+        assert_eq!(
+            cloud_copies_in(&steam, DELTARUNE_APP_ID, "filech1_0"),
+            vec![remote.join("filech1_0")]
+        );
+        assert!(cloud_copies_in(&steam, DELTARUNE_APP_ID, "filech1_1").is_empty());
+    }
+}
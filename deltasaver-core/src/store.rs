@@ -0,0 +1,2738 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+use chrono::{DateTime, Local};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::branch::{Branches, DEFAULT_BRANCH};
+use crate::config::DirectoryOverride;
+use crate::encryption::{self, Key};
+use crate::format;
+use crate::freeze::{self, Freezes};
+use crate::git;
+use crate::history::{self, Migration};
+use crate::index::{self, Index};
+use crate::portable;
+use crate::profile::{self, DELTARUNE_PROFILE_NAME, GameProfile, backup_created_secs, backup_id};
+use crate::save::{Chapter, SaveFile, Slot, hash_contents};
+use crate::snapshot;
+use crate::steam;
+use ulid::Ulid;
+
+/// Backups for profiles other than DELTARUNE live in a subdirectory named
+/// after the profile, so they never mix with DELTARUNE's.
+const PROFILES_DIRECTORY: &str = "profiles";
+
+const HANDOFFS_DIRECTORY: &str = "handoffs";
+
+const SNAPSHOTS_DIRECTORY: &str = "snapshots";
+
+/// Backup contents, each stored once under its hash no matter how many
+/// backups share it. The backups themselves are empty files, named as
+/// before, that refer to their contents by the hash in their name.
+const OBJECTS_DIRECTORY: &str = "objects";
+
+/// Deleted backups, kept for a while in case the deletion was a mistake.
+const TRASH_DIRECTORY: &str = "trash";
+
+/// The files the game reads along with a slot, backed up with it: one
+/// directory per backup, named by its ID, holding an empty
+/// `{hash}_{filename}` for each file, whose contents are in the object store.
+const COMPANIONS_DIRECTORY: &str = "companions";
+
+/// Objects are compressed with zstd, which saves shrink to a fraction of
+/// their size under. Saves are small, so the slowest levels are still fast.
+const COMPRESSION_LEVEL: i32 = 19;
+
+/// Every zstd frame starts with this, which no save does, so compressed and
+/// uncompressed files can be told apart.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Consecutive backups of a slot barely differ, so most objects are stored
+/// as the difference from a base: a zstd frame compressed with the base's
+/// contents as its dictionary, after this and the base's hash. Bases are
+/// always stored whole, so reading any object takes at most one other.
+const DELTA_MAGIC: &[u8; 8] = b"DSDELTA1";
+
+/// The length of [`DELTA_MAGIC`] and the hex SHA-256 hash after it.
+const DELTA_HEADER_LENGTH: usize = DELTA_MAGIC.len() + 64;
+
+/// A difference is only kept if it is at most this fraction of the object
+/// compressed on its own. Past that, the slot has drifted far enough from
+/// its base that the object is stored whole and becomes the next base.
+const DELTA_SAVINGS_FACTOR: usize = 2;
+
+/// Remembers the last Steam build of the game we saw, so we can tell when an
+/// update has been installed.
+const GAME_BUILD_ID_FILENAME: &str = "game_build_id";
+
+#[derive(Debug, Clone)]
+pub enum LoadError {
+    Io(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        LoadError::Io(error.to_string())
+    }
+}
+
+/// A new build of the game, found by [`SaveStore::snapshot_on_game_update`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameUpdate {
+    /// The game's saves were snapshotted before it could run, into this
+    /// directory.
+    Snapshotted(PathBuf),
+    /// Snapshots are off because they are not encrypted and the backups are,
+    /// so the update is reported again until it is skipped with
+    /// [`SaveStore::skip_game_update_snapshot`].
+    Unsnapshotted,
+}
+
+/// Everything found by [`SaveStore::load`].
+#[derive(Debug, Clone, Default)]
+pub struct Saves {
+    pub game: HashMap<(Chapter, Slot), SaveFile>,
+    /// Files the game keeps past its last slot, see
+    /// [`GameProfile::parse_extra_filename`].
+    pub extra: HashMap<(Chapter, Slot), SaveFile>,
+    /// Backups, including those of extra files.
+    pub local: Vec<SaveFile>,
+}
+
+impl Saves {
+    /// The backup with the given [`SaveFile::id`], if any.
+    pub fn find_backup(&self, id: &str) -> Option<&SaveFile> {
+        self.local.iter().find(|save| save.id() == Some(id))
+    }
+}
+
+/// What [`SaveStore::migrate_legacy_backups`] did.
+#[derive(Debug, Clone, Default)]
+pub struct LegacyMigration {
+    /// The new paths of the backups migrated.
+    pub migrated: Vec<PathBuf>,
+    /// Files that looked like legacy backups but were left alone, with why.
+    pub skipped: Vec<(String, String)>,
+}
+
+/// A backup found by [`SaveStore::check_integrity`] that could no longer be
+/// restored as it was taken.
+#[derive(Debug, Clone)]
+pub struct Damaged {
+    pub save: SaveFile,
+    /// What is wrong with it, e.g. that its contents no longer match.
+    pub problem: String,
+}
+
+/// The pair of directories DELTASAVER works between: the game's own save
+/// directory, described by a [`GameProfile`], and the directory where backups
+/// are kept.
+#[derive(Debug, Clone)]
+pub struct SaveStore {
+    profile: GameProfile,
+    local_directory: PathBuf,
+    /// Whether [`Self::backup`] skips slots unchanged since their latest
+    /// backup.
+    skip_identical: bool,
+    /// The key to the backups, if they are encrypted and were unlocked.
+    key: Option<Key>,
+    /// Where every backup is also kept as a plain file, if anywhere.
+    mirror_directory: Option<PathBuf>,
+    /// The git repository every backup is also committed to, if any.
+    git_directory: Option<PathBuf>,
+}
+
+/// Where the backups of the profile named `name` go in `data_directory`.
+/// Profile names come from the user's `profiles.json`, so are sanitized to
+/// keep them inside the profiles directory.
+fn profile_directory(data_directory: &Path, name: &str) -> PathBuf {
+    if name == DELTARUNE_PROFILE_NAME {
+        data_directory.to_path_buf()
+    } else {
+        data_directory
+            .join(PROFILES_DIRECTORY)
+            .join(snapshot::sanitize(name, "profile"))
+    }
+}
+
+fn now_since_epoch() -> Duration {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+}
+
+impl SaveStore {
+    pub fn new(profile: GameProfile, local_directory: PathBuf) -> Self {
+        Self {
+            profile,
+            local_directory,
+            skip_identical: false,
+            key: None,
+            mirror_directory: None,
+            git_directory: None,
+        }
+    }
+
+    /// The DELTARUNE store at the platform's usual locations, or `None` if
+    /// this platform has no local data directory or is not supported.
+    pub fn at_default_location() -> Option<Self> {
+        Self::for_profile(GameProfile::deltarune_at_default_location()?)
+    }
+
+    /// The store for `profile`, keeping backups in the platform's local data
+    /// directory, or the portable directory in portable mode.
+    pub fn for_profile(profile: GameProfile) -> Option<Self> {
+        let local_directory = profile_directory(&portable::data_directory()?, &profile.name);
+        let mut store = Self::new(profile, local_directory);
+        store.detect_chapters();
+        Some(store)
+    }
+
+    /// This store, but for the installation of the game with its saves in
+    /// `game_directory`. Backups are shared between installations, so saves
+    /// can be moved from one to another.
+    pub fn with_game_directory(&self, game_directory: PathBuf) -> Self {
+        let mut profile = self.profile.clone();
+        profile.save_directory = game_directory;
+        let mut store = Self {
+            profile,
+            ..self.clone()
+        };
+        store.detect_chapters();
+        store
+    }
+
+    /// This store, but not backing up a slot whose contents match its latest
+    /// backup, which [`Self::backup`] returns instead.
+    pub fn with_skip_identical(&self, skip_identical: bool) -> Self {
+        Self {
+            skip_identical,
+            ..self.clone()
+        }
+    }
+
+    /// Whether the backups are encrypted, see [`encryption`].
+    pub fn is_encrypted(&self) -> bool {
+        encryption::is_enabled(&self.local_directory)
+    }
+
+    /// Fails if the backups are encrypted, before making `what`, which would
+    /// be kept beside them unencrypted.
+    pub(crate) fn check_unencrypted(&self, what: &str) -> io::Result<()> {
+        encryption::check_unencrypted(&self.local_directory, what)
+    }
+
+    /// Whether the backups are encrypted and have not been unlocked, so
+    /// they can be listed but not read or added to.
+    pub fn is_locked(&self) -> bool {
+        self.key.is_none() && self.is_encrypted()
+    }
+
+    /// This store, unlocked with `passphrase`.
+    pub fn unlock(&self, passphrase: &str) -> io::Result<Self> {
+        Ok(Self {
+            key: Some(encryption::unlock(&self.local_directory, passphrase)?),
+            ..self.clone()
+        })
+    }
+
+    /// Encrypts the contents of every backup with `passphrase` from now on,
+    /// returning this store unlocked. Fails if the store keeps a mirror or
+    /// commits to git, whose copies are not encrypted, if any backup has a
+    /// name, note, or tag, which the index keeps in the clear, or if any
+    /// snapshot, chapter handoff, or backup that could not be moved into the
+    /// object store is left, listing them.
+    pub fn encrypt(&self, passphrase: &str) -> io::Result<Self> {
+        if self.keeps_unencrypted_copies() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the mirror and git keep unencrypted copies; remove them from config.toml first",
+            ));
+        }
+        if self.local_directory.is_dir()
+            && self
+                .index()?
+                .entries()?
+                .values()
+                .any(|entry| entry.name.is_some() || entry.note.is_some() || !entry.tags.is_empty())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "names, notes, and tags are not encrypted; clear them from every backup first",
+            ));
+        }
+        self.ensure_local_directory()?;
+        let copies = self.unencrypted_copies()?;
+        if !copies.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "these are not encrypted; remove them first: {}",
+                    copies
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ));
+        }
+        let store = Self {
+            key: Some(encryption::enable(&self.local_directory, passphrase)?),
+            ..self.clone()
+        };
+        let sealed = store.seal_objects()?;
+        history::record(&self.local_directory, Migration::Encryption, sealed)?;
+        Ok(store)
+    }
+
+    /// This store with the directories in `directories` in place of its own.
+    pub fn with_directory_override(&self, directories: &DirectoryOverride) -> Self {
+        let mut store = match &directories.saves {
+            Some(saves) => self.with_game_directory(saves.clone()),
+            None => self.clone(),
+        };
+        if let Some(backups) = &directories.backups {
+            store.local_directory = backups.clone();
+            store.detect_chapters();
+        }
+        if let Some(mirror) = &directories.mirror {
+            store = store.with_mirror_directory(Some(mirror.clone()));
+        }
+        if let Some(git) = &directories.git {
+            store = store.with_git_directory(Some(git.clone()));
+        }
+        store
+    }
+
+    /// This store, also keeping every backup in `mirror_directory` as an
+    /// ordinary file, so backups can be recovered with nothing but a file
+    /// manager. The copies are named for their chapter, slot, and when they
+    /// were taken, e.g. `Chapter 1 Slot 1 2025-06-01 18-30-00 {id}`, are
+    /// neither compressed nor encrypted, and are made read-only. Copying one
+    /// into the game's save directory under the slot's filename restores it.
+    /// Backing up fails while the backups are encrypted and a mirror is set,
+    /// rather than leave unencrypted copies of them.
+    pub fn with_mirror_directory(&self, mirror_directory: Option<PathBuf>) -> Self {
+        Self {
+            mirror_directory,
+            ..self.clone()
+        }
+    }
+
+    pub fn mirror_directory(&self) -> Option<&Path> {
+        self.mirror_directory.as_deref()
+    }
+
+    /// This store, also committing every new backup to the git repository at
+    /// `git_directory`, created if need be; see [`crate::git`].
+    pub fn with_git_directory(&self, git_directory: Option<PathBuf>) -> Self {
+        Self {
+            git_directory,
+            ..self.clone()
+        }
+    }
+
+    pub fn git_directory(&self) -> Option<&Path> {
+        self.git_directory.as_deref()
+    }
+
+    /// Whether the store mirrors or commits backups to git, keeping copies
+    /// of them that are not encrypted, so it cannot back up while they are.
+    pub fn keeps_unencrypted_copies(&self) -> bool {
+        self.mirror_directory.is_some() || self.git_directory.is_some()
+    }
+
+    /// Shows chapters past the profile's `chapter_count` if either directory
+    /// has saves for them, e.g. from a mod that adds chapters.
+    pub fn detect_chapters(&mut self) {
+        let last_chapter = [self.game_directory(), self.local_directory()]
+            .into_iter()
+            .filter_map(|directory| self.profile.last_chapter_in(directory))
+            .max();
+        if let Some(last_chapter) = last_chapter {
+            self.profile.chapter_count = self.profile.chapter_count.max(last_chapter);
+        }
+    }
+
+    /// Parses the name of any backup in the store, of a save or an extra
+    /// file.
+    fn parse_backup_filename(&self, filename: &str) -> Option<(Chapter, Slot, String)> {
+        self.profile
+            .parse_backup_filename(filename)
+            .or_else(|| self.profile.parse_extra_backup_filename(filename))
+    }
+
+    pub fn profile(&self) -> &GameProfile {
+        &self.profile
+    }
+
+    pub fn game_directory(&self) -> &Path {
+        &self.profile.save_directory
+    }
+
+    pub fn local_directory(&self) -> &Path {
+        &self.local_directory
+    }
+
+    /// Creates the backup directory if it does not exist yet, and migrates
+    /// backups made by older versions to the current layout, recording each
+    /// migration in the directory's [`history`].
+    pub fn ensure_local_directory(&self) -> io::Result<()> {
+        fs::create_dir_all(&self.local_directory)?;
+        let migrated = self.migrate_to_objects()?;
+        if migrated > 0 {
+            history::record(&self.local_directory, Migration::ObjectStore, migrated)?;
+        }
+        let recompressed = self.recompress_objects()?;
+        if recompressed > 0 {
+            history::record(
+                &self.local_directory,
+                Migration::Recompression,
+                recompressed,
+            )?;
+        }
+        if !self.is_encrypted() {
+            self.sync_mirror()?;
+        }
+        Ok(())
+    }
+
+    /// The index of the backups' notes, tags, and sizes, see [`index`].
+    pub fn index(&self) -> io::Result<Index> {
+        Index::open(&self.local_directory)
+    }
+
+    /// The migrations made to the backup directory, oldest first.
+    pub fn history(&self) -> io::Result<Vec<history::Entry>> {
+        history::load(&self.local_directory)
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.local_directory.join(OBJECTS_DIRECTORY).join(hash)
+    }
+
+    /// Stores `contents` compressed, and encrypted if the backups are, under
+    /// their hash unless they already are. If `base` is given, they are
+    /// stored as the difference from it when that is much smaller.
+    fn write_object(&self, hash: &str, contents: &[u8], base: Option<&str>) -> io::Result<()> {
+        let objects_directory = self.local_directory.join(OBJECTS_DIRECTORY);
+        fs::create_dir_all(&objects_directory)?;
+        if self.is_locked() {
+            return Err(locked_error());
+        }
+        if self.object_path(hash).exists() {
+            return Ok(());
+        }
+        let stored = self.encode_object(contents, base)?;
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(self.object_path(hash))
+        {
+            Ok(mut file) => file.write_all(&stored),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// `contents` as they are stored, see [`Self::write_object`]. A base that
+    /// cannot be read is passed over, so a damaged base never stops a backup.
+    fn encode_object(&self, contents: &[u8], base: Option<&str>) -> io::Result<Vec<u8>> {
+        let whole = zstd::bulk::compress(contents, COMPRESSION_LEVEL)?;
+        if let Some(base) = base
+            && let Ok(dictionary) = self.read_object(base)
+        {
+            let delta = zstd::bulk::Compressor::with_dictionary(COMPRESSION_LEVEL, &dictionary)?
+                .compress(contents)?;
+            if delta.len() * DELTA_SAVINGS_FACTOR <= whole.len() {
+                let mut stored = DELTA_MAGIC.to_vec();
+                stored.extend_from_slice(base.as_bytes());
+                stored.extend(self.seal(delta)?);
+                return Ok(stored);
+            }
+        }
+        self.seal(whole)
+    }
+
+    /// The object a new backup of `chapter`'s `slot` is stored as the
+    /// difference from: its latest backup's, or that one's base if it has
+    /// one.
+    fn delta_base(&self, chapter: Chapter, slot: Slot) -> io::Result<Option<String>> {
+        let Some((_, hash)) = self.latest_backup(chapter, slot)? else {
+            return Ok(None);
+        };
+        match self.object_base(&hash) {
+            Ok(base) => Ok(Some(base.unwrap_or(hash))),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// The object the one stored under `hash` is the difference from, if it
+    /// is stored as one.
+    fn object_base(&self, hash: &str) -> io::Result<Option<String>> {
+        let mut header = Vec::with_capacity(DELTA_HEADER_LENGTH);
+        File::open(self.object_path(hash))?
+            .take(DELTA_HEADER_LENGTH as u64)
+            .read_to_end(&mut header)?;
+        Ok(split_delta(&header).0.map(str::to_string))
+    }
+
+    /// `stored` encrypted if the backups are, failing if they are locked.
+    fn seal(&self, stored: Vec<u8>) -> io::Result<Vec<u8>> {
+        match &self.key {
+            Some(key) => encryption::seal(key, &stored),
+            None if self.is_encrypted() => Err(locked_error()),
+            None => Ok(stored),
+        }
+    }
+
+    /// `stored` decrypted if it was encrypted, failing if the backups are
+    /// locked.
+    fn unseal(&self, stored: Vec<u8>) -> io::Result<Vec<u8>> {
+        if !encryption::is_sealed(&stored) {
+            return Ok(stored);
+        }
+        let key = self.key.as_ref().ok_or_else(locked_error)?;
+        encryption::open(key, &stored)
+    }
+
+    /// Encrypts every object not yet encrypted, compressing it too if it was
+    /// not, returning how many there were.
+    fn seal_objects(&self) -> io::Result<usize> {
+        let objects_directory = self.local_directory.join(OBJECTS_DIRECTORY);
+        if !objects_directory.is_dir() {
+            return Ok(0);
+        }
+        let mut sealed = 0;
+        for entry in fs::read_dir(&objects_directory)? {
+            let path = entry?.path();
+            let stored = fs::read(&path)?;
+            let (base, mut contents) = split_delta(&stored);
+            if encryption::is_sealed(contents) {
+                continue;
+            }
+            let compressed;
+            if !contents.starts_with(&ZSTD_MAGIC) {
+                compressed = zstd::bulk::compress(contents, COMPRESSION_LEVEL)?;
+                contents = &compressed;
+            }
+            let mut replacement = Vec::new();
+            if let Some(base) = base {
+                replacement.extend_from_slice(DELTA_MAGIC);
+                replacement.extend_from_slice(base.as_bytes());
+            }
+            replacement.extend(self.seal(contents.to_vec())?);
+            replace_object(&path, &replacement)?;
+            sealed += 1;
+        }
+        Ok(sealed)
+    }
+
+    /// Moves the contents of every backup still holding them, in the trash
+    /// too, into the object store, leaving the backup as an empty reference.
+    /// Backups whose contents no longer match their name are left alone, so
+    /// restoring them still reports the damage. Returns how many backups were
+    /// moved.
+    fn migrate_to_objects(&self) -> io::Result<usize> {
+        let mut migrated = 0;
+        for (path, hash) in self.inline_backups()? {
+            let contents = fs::read(&path)?;
+            if hash_contents(&contents) != hash {
+                continue;
+            }
+            self.write_object(&hash, &contents, None)?;
+            fs::write(&path, [])?;
+            migrated += 1;
+        }
+        Ok(migrated)
+    }
+
+    /// The backups, in the trash too, holding their contents themselves
+    /// rather than referring to an object, with the hash each is named for.
+    fn inline_backups(&self) -> io::Result<Vec<(PathBuf, String)>> {
+        let trash_directory = self.local_directory.join(TRASH_DIRECTORY);
+        let mut backups = Vec::new();
+        for directory in [&self.local_directory, &trash_directory] {
+            let entries = match fs::read_dir(directory) {
+                Ok(entries) => entries,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+                Err(error) => return Err(error),
+            };
+            for entry in entries {
+                let entry = entry?;
+                let Some((_, _, hash)) = entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|filename| self.parse_backup_filename(filename))
+                else {
+                    continue;
+                };
+                if entry.file_type()?.is_file() && entry.metadata()?.len() > 0 {
+                    backups.push((entry.path(), hash));
+                }
+            }
+        }
+        Ok(backups)
+    }
+
+    /// Everything in the backup directory kept unencrypted whatever the
+    /// backups are: each snapshot and chapter handoff, and each backup the
+    /// object store could not take in.
+    fn unencrypted_copies(&self) -> io::Result<Vec<PathBuf>> {
+        let mut copies = Vec::new();
+        for directory in [
+            self.snapshots_directory(),
+            self.local_directory.join(HANDOFFS_DIRECTORY),
+        ] {
+            match fs::read_dir(directory) {
+                Ok(entries) => {
+                    for entry in entries {
+                        copies.push(entry?.path());
+                    }
+                }
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error),
+            }
+        }
+        copies.extend(self.inline_backups()?.into_iter().map(|(path, _)| path));
+        copies.sort();
+        Ok(copies)
+    }
+
+    /// Reads back every backup and the files kept with it, checking that they
+    /// still hash to what was backed up, so bit rot or a truncated object
+    /// turns up before the backup is needed.
+    pub fn check_integrity(&self) -> io::Result<Vec<Damaged>> {
+        if self.is_locked() {
+            return Err(locked_error());
+        }
+        let saves = self
+            .load()
+            .map_err(|error| io::Error::other(error.to_string()))?;
+        let mut damaged = Vec::new();
+        for save in saves.local {
+            let mut problems = Vec::new();
+            match self.read_backup(&save.path) {
+                Ok(contents) => {
+                    let hash = hash_contents(&contents);
+                    if save.hash.as_ref().is_some_and(|recorded| *recorded != hash) {
+                        problems.push("its contents do not match its hash".to_string());
+                    }
+                }
+                Err(error) => problems.push(format!("it cannot be read: {}", error)),
+            }
+            for (filename, hash) in self.recorded_companions(&save.path)? {
+                match self.read_object(&hash) {
+                    Ok(contents) if hash_contents(&contents) == hash => {}
+                    Ok(_) => problems.push(format!("its copy of {} does not match", filename)),
+                    Err(error) => problems.push(format!(
+                        "its copy of {} cannot be read: {}",
+                        filename, error
+                    )),
+                }
+            }
+            if !problems.is_empty() {
+                damaged.push(Damaged {
+                    save,
+                    problem: problems.join("; "),
+                });
+            }
+        }
+        Ok(damaged)
+    }
+
+    /// Renames the backups made before backups were identified by ULIDs,
+    /// named `filech{chapter}_{slot}_{hash}_{secs}_{nanos}`, to the current
+    /// naming, storing their contents as objects and keeping their notes,
+    /// tags, branch, and companion files. Files named like a backup that
+    /// cannot be read as one, or whose contents no longer match their name,
+    /// are reported and left as they are.
+    pub fn migrate_legacy_backups(&self) -> io::Result<LegacyMigration> {
+        if self.is_locked() {
+            return Err(locked_error());
+        }
+        let mut migration = LegacyMigration::default();
+        let index = self.index()?;
+        let mut branches = Branches::load(&self.local_directory)?;
+        // Anything starting like a save filename is taken for a backup; with
+        // no such start to go by, only names that parse are.
+        let prefix = self
+            .profile
+            .filename_pattern
+            .split('{')
+            .next()
+            .unwrap_or_default();
+        for entry in fs::read_dir(&self.local_directory)? {
+            let entry = entry?;
+            let Some(filename) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let Some((chapter, slot, hash)) = self.parse_backup_filename(&filename) else {
+                if !prefix.is_empty() && filename.starts_with(prefix) {
+                    migration.skipped.push((
+                        filename,
+                        "is not named like a backup of any slot".to_string(),
+                    ));
+                }
+                continue;
+            };
+            let Some(ulid) = profile::ulid_for_legacy_backup(&filename) else {
+                continue;
+            };
+            let contents = match self.read_backup(&entry.path()) {
+                Ok(contents) => contents,
+                Err(error) => {
+                    migration.skipped.push((filename, error.to_string()));
+                    continue;
+                }
+            };
+            if hash_contents(&contents) != hash {
+                migration.skipped.push((
+                    filename,
+                    "is damaged: its contents do not match its hash".to_string(),
+                ));
+                continue;
+            }
+
+            self.write_object(&hash, &contents, None)?;
+            let backup_path = write_new_file(
+                &self.local_directory.join(self.profile.backup_filename(
+                    chapter,
+                    slot,
+                    &hash,
+                    &ulid.to_string(),
+                )),
+                &[],
+            )?;
+            if let Ok(modified) = entry.metadata()?.modified() {
+                OpenOptions::new()
+                    .write(true)
+                    .open(&backup_path)?
+                    .set_modified(modified)?;
+            }
+            let new_filename = backup_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            let indexed = index.get(&filename)?.unwrap_or_else(|| index::Entry {
+                chapter,
+                slot,
+                hash: hash.clone(),
+                size: Some(contents.len() as u64),
+                ..Default::default()
+            });
+            index.record(
+                &new_filename,
+                &index::Entry {
+                    created: backup_created_secs(&new_filename),
+                    ..indexed
+                },
+            )?;
+            index.remove(&filename)?;
+            if let (Some(old_id), Some(new_id)) = (backup_id(&filename), backup_id(&new_filename)) {
+                branches.rename_backup(old_id, new_id);
+            }
+            if let (Some(old_companions), Some(new_companions)) = (
+                self.companions_directory(&entry.path()),
+                self.companions_directory(&backup_path),
+            ) && old_companions.is_dir()
+            {
+                fs::rename(old_companions, new_companions)?;
+            }
+            fs::remove_file(entry.path())?;
+            self.mirror(&backup_path, &contents)?;
+            migration.migrated.push(backup_path);
+        }
+        if !migration.migrated.is_empty() {
+            branches.save(&self.local_directory)?;
+            history::record(
+                &self.local_directory,
+                Migration::LegacyNames,
+                migration.migrated.len(),
+            )?;
+        }
+        Ok(migration)
+    }
+
+    /// Compresses the objects stored before objects were compressed,
+    /// returning how many there were.
+    fn recompress_objects(&self) -> io::Result<usize> {
+        let objects_directory = self.local_directory.join(OBJECTS_DIRECTORY);
+        if !objects_directory.is_dir() {
+            return Ok(0);
+        }
+        let mut recompressed = 0;
+        for entry in fs::read_dir(&objects_directory)? {
+            let path = entry?.path();
+            let contents = fs::read(&path)?;
+            if contents.starts_with(&ZSTD_MAGIC)
+                || contents.starts_with(DELTA_MAGIC)
+                || encryption::is_sealed(&contents)
+            {
+                continue;
+            }
+            let compressed = zstd::bulk::compress(&contents, COMPRESSION_LEVEL)?;
+            replace_object(&path, &self.seal(compressed)?)?;
+            recompressed += 1;
+        }
+        Ok(recompressed)
+    }
+
+    /// The contents of `save`, whether a backup or a save in the game's
+    /// directory.
+    pub fn read_save(&self, save: &SaveFile) -> io::Result<Vec<u8>> {
+        if save.is_local {
+            self.read_backup(&save.path)
+        } else {
+            fs::read(&save.path)
+        }
+    }
+
+    /// The contents of the backup at `backup_path`, from the object store or,
+    /// for backups not yet migrated, the backup itself.
+    pub fn read_backup(&self, backup_path: &Path) -> io::Result<Vec<u8>> {
+        let contents = fs::read(backup_path)?;
+        if !contents.is_empty() {
+            return decompress(contents);
+        }
+        let hash = backup_path
+            .file_name()
+            .and_then(|filename| filename.to_str())
+            .and_then(|filename| self.parse_backup_filename(filename))
+            .map(|(_, _, hash)| hash)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is not a backup", backup_path.display()),
+                )
+            })?;
+        self.read_object(&hash)
+    }
+
+    fn read_object(&self, hash: &str) -> io::Result<Vec<u8>> {
+        let stored = fs::read(self.object_path(hash))?;
+        let (base, delta) = split_delta(&stored);
+        let Some(base) = base else {
+            return decompress(self.unseal(stored)?);
+        };
+        let dictionary = self.read_object(base)?;
+        let mut contents = Vec::new();
+        zstd::stream::read::Decoder::with_dictionary(
+            self.unseal(delta.to_vec())?.as_slice(),
+            &dictionary,
+        )?
+        .read_to_end(&mut contents)?;
+        Ok(contents)
+    }
+
+    /// The size of the contents stored under `hash`, before compression.
+    fn object_size(&self, hash: &str) -> Option<u64> {
+        let stored = fs::read(self.object_path(hash)).ok()?;
+        let stored = self.unseal(split_delta(&stored).1.to_vec()).ok()?;
+        if !stored.starts_with(&ZSTD_MAGIC) {
+            return Some(stored.len() as u64);
+        }
+        match zstd::zstd_safe::get_frame_content_size(&stored) {
+            Ok(Some(size)) => Some(size),
+            _ => None,
+        }
+    }
+
+    /// Where the backup at `backup_path` is mirrored, if it is.
+    fn mirror_path(&self, backup_path: &Path) -> Option<PathBuf> {
+        let mirror_directory = self.mirror_directory.as_ref()?;
+        let filename = backup_path.file_name()?.to_str()?;
+        let (chapter, slot, _) = self.parse_backup_filename(filename)?;
+        let id = backup_id(filename).filter(|id| Ulid::from_string(id).is_ok())?;
+        let created = SystemTime::UNIX_EPOCH + Duration::from_secs(backup_created_secs(filename)?);
+        let title = if self.profile.has_chapters() {
+            format!("Chapter {}", chapter)
+        } else {
+            self.profile.name.clone()
+        };
+        Some(mirror_directory.join(format!(
+            "{} Slot {} {} {}",
+            title,
+            slot + 1,
+            DateTime::<Local>::from(created).format("%Y-%m-%d %H-%M-%S"),
+            id
+        )))
+    }
+
+    /// Copies `contents`, the backup at `backup_path`, into the mirror.
+    fn mirror(&self, backup_path: &Path, contents: &[u8]) -> io::Result<()> {
+        let Some(mirror_path) = self.mirror_path(backup_path) else {
+            return Ok(());
+        };
+        if let Some(parent) = mirror_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        remove_mirrored(&mirror_path)?;
+        fs::write(&mirror_path, contents)?;
+        let mut permissions = fs::metadata(&mirror_path)?.permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&mirror_path, permissions)
+    }
+
+    /// Brings the mirror up to date with the backups, copying any missing
+    /// and removing copies of backups since deleted. Files in the mirror
+    /// directory that are not copies are left alone.
+    pub fn sync_mirror(&self) -> io::Result<()> {
+        let Some(mirror_directory) = &self.mirror_directory else {
+            return Ok(());
+        };
+        self.check_unencrypted("mirrored copies")?;
+        let mut expected = HashMap::new();
+        for entry in fs::read_dir(&self.local_directory)? {
+            let backup_path = entry?.path();
+            if let Some(mirror_path) = self.mirror_path(&backup_path) {
+                expected.insert(mirror_path, backup_path);
+            }
+        }
+        if mirror_directory.is_dir() {
+            for entry in fs::read_dir(mirror_directory)? {
+                let mirror_path = entry?.path();
+                let is_copy = mirror_path
+                    .file_name()
+                    .and_then(|filename| filename.to_str())
+                    .and_then(|filename| filename.rsplit_once(' '))
+                    .is_some_and(|(_, id)| Ulid::from_string(id).is_ok());
+                if is_copy && expected.remove(&mirror_path).is_none() {
+                    remove_mirrored(&mirror_path)?;
+                }
+            }
+        }
+        for backup_path in expected.into_values() {
+            self.mirror(&backup_path, &self.read_backup(&backup_path)?)?;
+        }
+        Ok(())
+    }
+
+    /// Scans both directories for saves.
+    pub fn load(&self) -> Result<Saves, LoadError> {
+        let mut saves = Saves::default();
+
+        if self.game_directory().exists() {
+            for entry in fs::read_dir(self.game_directory())? {
+                let path = entry?.path();
+                let Some(filename) = path.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                let game_slot = self.profile.parse_save_filename(filename);
+                let extra_slot = self.profile.parse_extra_filename(filename);
+                if let Some((chapter, slot)) = game_slot.or(extra_slot) {
+                    let metadata = fs::metadata(&path).ok();
+                    let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+                    let size = metadata.map(|m| m.len());
+                    let save = SaveFile {
+                        path: path.clone(),
+                        chapter,
+                        slot,
+                        hash: None,
+                        modified,
+                        size,
+                        is_local: false,
+                    };
+                    if game_slot.is_some() {
+                        saves.game.insert((chapter, slot), save);
+                    } else {
+                        saves.extra.insert((chapter, slot), save);
+                    }
+                }
+            }
+        }
+
+        if self.local_directory.exists() {
+            saves.local = self.backups_in(&self.local_directory)?;
+        }
+
+        Ok(saves)
+    }
+
+    /// The backups in `directory`, the backup directory or the trash. Sizes
+    /// come from the index, which backups missing from it are added to.
+    fn backups_in(&self, directory: &Path) -> io::Result<Vec<SaveFile>> {
+        // Listing still works, if more slowly, where the index cannot be
+        // written, like a backup directory on read-only media.
+        let index = self.index().ok();
+        let entries = index
+            .as_ref()
+            .and_then(|index| index.entries().ok())
+            .unwrap_or_default();
+        let mut backups = Vec::new();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            let Some(filename) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if let Some((chapter, slot, hash)) = self.parse_backup_filename(filename) {
+                let metadata = fs::metadata(&path).ok();
+                let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+                let size = match metadata.map(|m| m.len()) {
+                    Some(0) => match entries.get(filename) {
+                        Some(entry) => entry.size,
+                        None => {
+                            let size = self.object_size(&hash);
+                            if let Some(index) = &index {
+                                let entry = index::Entry {
+                                    chapter,
+                                    slot,
+                                    hash: hash.clone(),
+                                    created: backup_created_secs(filename),
+                                    size,
+                                    ..Default::default()
+                                };
+                                let _ = index.record(filename, &entry);
+                            }
+                            size
+                        }
+                    },
+                    size => size,
+                };
+                backups.push(SaveFile {
+                    path: path.clone(),
+                    chapter,
+                    slot,
+                    hash: Some(hash),
+                    modified,
+                    size,
+                    is_local: true,
+                });
+            }
+        }
+        Ok(backups)
+    }
+
+    /// Copies the game's current file for `chapter`'s `slot` into the backup
+    /// directory, returning the path of the new backup. If the store skips
+    /// identical backups and the slot has not changed since its latest
+    /// backup, returns that backup instead.
+    pub fn backup(&self, chapter: Chapter, slot: Slot) -> io::Result<PathBuf> {
+        self.backup_with_note(chapter, slot, None)
+    }
+
+    /// Like [`Self::backup`], with `note` as the commit message if backups
+    /// are also committed to git.
+    pub fn backup_with_note(
+        &self,
+        chapter: Chapter,
+        slot: Slot,
+        note: Option<&str>,
+    ) -> io::Result<PathBuf> {
+        let contents = fs::read(
+            self.game_directory()
+                .join(self.profile.save_filename(chapter, slot)),
+        )?;
+        let companions = self.current_companions(chapter, slot)?;
+        if self.skip_identical
+            && let Some((latest_path, latest_hash)) = self.latest_backup(chapter, slot)?
+            && latest_hash == hash_contents(&contents)
+            && self.recorded_companions(&latest_path)? == companion_hashes(&companions)
+        {
+            return Ok(latest_path);
+        }
+        self.add(
+            chapter,
+            slot,
+            &contents,
+            &Ulid::new().to_string(),
+            &companions,
+            note,
+        )
+    }
+
+    /// The files besides `chapter`'s `slot` that the game reads with it, so
+    /// restoring the slot alone could leave them out of step: its completion
+    /// file, if the profile has them, and the profile's shared files.
+    fn companion_filenames(&self, chapter: Chapter, slot: Slot) -> Vec<String> {
+        if self.profile.is_extra_slot(slot) {
+            return Vec::new();
+        }
+        let mut filenames = Vec::new();
+        if let Some(offset) = self.profile.completion_slot_offset {
+            filenames.push(self.profile.save_filename(chapter, slot + offset));
+        }
+        filenames.extend(self.profile.shared_files.iter().cloned());
+        filenames
+    }
+
+    /// The companion files of `chapter`'s `slot` in the game directory now,
+    /// by filename.
+    fn current_companions(
+        &self,
+        chapter: Chapter,
+        slot: Slot,
+    ) -> io::Result<Vec<(String, Vec<u8>)>> {
+        let mut companions = Vec::new();
+        for filename in self.companion_filenames(chapter, slot) {
+            match fs::read(self.game_directory().join(&filename)) {
+                Ok(contents) => companions.push((filename, contents)),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(companions)
+    }
+
+    fn companions_directory(&self, backup_path: &Path) -> Option<PathBuf> {
+        let id = backup_id(backup_path.file_name()?.to_str()?)?;
+        Some(self.local_directory.join(COMPANIONS_DIRECTORY).join(id))
+    }
+
+    fn write_companions(
+        &self,
+        backup_path: &Path,
+        companions: &[(String, Vec<u8>)],
+    ) -> io::Result<()> {
+        let Some(companions_directory) = self.companions_directory(backup_path) else {
+            return Ok(());
+        };
+        if companions.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(&companions_directory)?;
+        for (filename, contents) in companions {
+            let hash = hash_contents(contents);
+            self.write_object(&hash, contents, None)?;
+            fs::write(
+                companions_directory.join(format!("{}_{}", hash, filename)),
+                [],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The companion files backed up with the backup at `backup_path`, as
+    /// `(filename, hash)`, sorted by filename.
+    fn recorded_companions(&self, backup_path: &Path) -> io::Result<Vec<(String, String)>> {
+        let Some(companions_directory) = self.companions_directory(backup_path) else {
+            return Ok(Vec::new());
+        };
+        let entries = match fs::read_dir(companions_directory) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        let mut companions = Vec::new();
+        for entry in entries {
+            let name = entry?.file_name();
+            if let Some((hash, filename)) = name.to_str().and_then(|name| name.split_once('_')) {
+                companions.push((filename.to_string(), hash.to_string()));
+            }
+        }
+        companions.sort();
+        Ok(companions)
+    }
+
+    /// The filenames of the files backed up with the backup at
+    /// `backup_path` and restored with it, such as DELTARUNE's completion
+    /// files and `dr.ini`.
+    pub fn companions(&self, backup_path: &Path) -> io::Result<Vec<String>> {
+        Ok(self
+            .recorded_companions(backup_path)?
+            .into_iter()
+            .map(|(filename, _)| filename)
+            .collect())
+    }
+
+    /// The path and recorded hash of the newest backup of `chapter`'s `slot`.
+    fn latest_backup(&self, chapter: Chapter, slot: Slot) -> io::Result<Option<(PathBuf, String)>> {
+        let entries = match fs::read_dir(&self.local_directory) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        let mut latest = None;
+        for entry in entries {
+            let filename = entry?.file_name();
+            let Some(filename) = filename.to_str() else {
+                continue;
+            };
+            let Some((backup_chapter, backup_slot, hash)) = self.parse_backup_filename(filename)
+            else {
+                continue;
+            };
+            if (backup_chapter, backup_slot) != (chapter, slot) {
+                continue;
+            }
+            let created = (
+                backup_created_secs(filename),
+                backup_id(filename).map(str::to_string),
+            );
+            if latest
+                .as_ref()
+                .is_none_or(|(latest_created, _, _)| created > *latest_created)
+            {
+                latest = Some((created, self.local_directory.join(filename), hash));
+            }
+        }
+        Ok(latest.map(|(_, path, hash)| (path, hash)))
+    }
+
+    /// Adds `contents` to the backup directory as a backup of `chapter`'s
+    /// `slot`, returning the path of the new backup.
+    pub fn import(&self, chapter: Chapter, slot: Slot, contents: &[u8]) -> io::Result<PathBuf> {
+        self.import_with_id(chapter, slot, contents, &Ulid::new().to_string())
+    }
+
+    /// Like [`Self::import`], but keeping the ID the backup had elsewhere, and
+    /// with it when it was taken.
+    pub fn import_with_id(
+        &self,
+        chapter: Chapter,
+        slot: Slot,
+        contents: &[u8],
+        id: &str,
+    ) -> io::Result<PathBuf> {
+        self.add(chapter, slot, contents, id, &[], None)
+    }
+
+    /// Adds `contents` as a backup of `chapter`'s `slot` with the ID `id`,
+    /// along with `companions`, then copies it to the mirror and commits it
+    /// to git, if the store does.
+    fn add(
+        &self,
+        chapter: Chapter,
+        slot: Slot,
+        contents: &[u8],
+        id: &str,
+        companions: &[(String, Vec<u8>)],
+        note: Option<&str>,
+    ) -> io::Result<PathBuf> {
+        if self.mirror_directory.is_some() {
+            self.check_unencrypted("mirrored copies")?;
+        }
+        if self.git_directory.is_some() {
+            self.check_unencrypted("git commits")?;
+        }
+        if note.is_some() {
+            self.check_unencrypted("notes")?;
+        }
+        let hash = hash_contents(contents);
+        let base = self.delta_base(chapter, slot)?;
+        self.write_object(&hash, contents, base.as_deref())?;
+        let filename = self.profile.backup_filename(chapter, slot, &hash, id);
+        let backup_path = write_new_file(&self.local_directory.join(&filename), &[])?;
+        let entry = index::Entry {
+            chapter,
+            slot,
+            hash: hash.clone(),
+            created: backup_created_secs(&filename),
+            size: Some(contents.len() as u64),
+            note: note.map(str::to_string),
+            ..Default::default()
+        };
+        // The backup exists now, so an index that cannot be written, like
+        // one on read-only media, only leaves it to be indexed when listed.
+        if let Some(id) = backup_path.file_name().and_then(|name| name.to_str()) {
+            let _ = self.index().and_then(|index| index.record(id, &entry));
+        }
+        let mut branches = Branches::load(&self.local_directory)?;
+        if branches.active() != DEFAULT_BRANCH
+            && let Some(stored_filename) = backup_path.file_name().and_then(|name| name.to_str())
+        {
+            branches.add(stored_filename);
+            branches.save(&self.local_directory)?;
+        }
+        self.write_companions(&backup_path, companions)?;
+        self.mirror(&backup_path, contents)?;
+        if let Some(git_directory) = &self.git_directory {
+            let slot_name = format!("Chapter {} Slot {}", chapter, slot + 1);
+            let message = format!(
+                "{}\n\n{}, backup {}\n",
+                note.unwrap_or(&format!("Back up {}", slot_name)),
+                slot_name,
+                id
+            );
+            let mut files = vec![(self.profile.save_filename(chapter, slot), contents.to_vec())];
+            files.extend(companions.iter().cloned());
+            git::commit(git_directory, &files, &message)?;
+        }
+        Ok(backup_path)
+    }
+
+    /// Overwrites the game's file for `chapter`'s `slot` with a backup, then
+    /// reads it back to check that it arrived intact. A cloud sync client
+    /// racing the write, for instance, could otherwise leave the game with a
+    /// truncated or different save without anyone noticing.
+    pub fn restore(&self, backup_path: &Path, chapter: Chapter, slot: Slot) -> io::Result<()> {
+        if self.profile.is_extra_slot(slot) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "extra files are read-only and cannot be restored",
+            ));
+        }
+        self.check_not_frozen(chapter, slot)?;
+        let contents = self.read_backup(backup_path)?;
+        let hash = hash_contents(&contents);
+        let recorded_hash = backup_path
+            .file_name()
+            .and_then(|filename| filename.to_str())
+            .and_then(|filename| self.parse_backup_filename(filename))
+            .map(|(_, _, hash)| hash);
+        if let Some(recorded_hash) = recorded_hash.filter(|recorded| *recorded != hash) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} is damaged: its contents hash to {} but it was saved as {}",
+                    backup_path.display(),
+                    hash,
+                    recorded_hash
+                ),
+            ));
+        }
+
+        let game_path = self
+            .game_directory()
+            .join(self.profile.save_filename(chapter, slot));
+        write_atomically(&game_path, &contents)?;
+
+        verify_written(
+            &game_path,
+            &hash,
+            &format!("Chapter {} Slot {}", chapter, slot + 1),
+        )?;
+
+        // A completion file goes with the slot it is restored into, and one
+        // there already goes if the backup was taken before the chapter was
+        // completed, so the two cannot disagree.
+        let target_completion = self
+            .profile
+            .completion_slot_offset
+            .map(|offset| self.profile.save_filename(chapter, slot + offset));
+        let origin_completion = backup_path
+            .file_name()
+            .and_then(|filename| filename.to_str())
+            .and_then(|filename| self.parse_backup_filename(filename))
+            .zip(self.profile.completion_slot_offset)
+            .map(|((origin_chapter, origin_slot, _), offset)| {
+                self.profile
+                    .save_filename(origin_chapter, origin_slot + offset)
+            });
+        let mut restored_completion = false;
+        for (filename, companion_hash) in self.recorded_companions(backup_path)? {
+            let target = match (&origin_completion, &target_completion) {
+                (Some(origin), Some(target)) if *origin == filename => {
+                    restored_completion = true;
+                    target.clone()
+                }
+                _ => filename,
+            };
+            let target_path = self.game_directory().join(&target);
+            write_atomically(&target_path, &self.read_object(&companion_hash)?)?;
+            verify_written(&target_path, &companion_hash, &target)?;
+        }
+        if let Some(target) = target_completion.filter(|_| !restored_completion) {
+            match fs::remove_file(self.game_directory().join(target)) {
+                Ok(()) => {}
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether the backup at `backup_path` can be restored into
+    /// `chapter`, returning a warning for the user to accept first if it was
+    /// taken in another chapter. Fails if it is not a full save at all, see
+    /// [`format::has_full_layout`].
+    pub fn cross_chapter_warning(
+        &self,
+        backup_path: &Path,
+        chapter: Chapter,
+    ) -> io::Result<Option<String>> {
+        let Some((origin, _, _)) = backup_path
+            .file_name()
+            .and_then(|filename| filename.to_str())
+            .and_then(|filename| self.parse_backup_filename(filename))
+            .filter(|(origin, _, _)| *origin != chapter)
+        else {
+            return Ok(None);
+        };
+        if !format::has_full_layout(&self.read_backup(backup_path)?) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} is not a complete save, so it cannot go into another chapter",
+                    backup_path.display()
+                ),
+            ));
+        }
+        Ok(Some(format!(
+            "This backup is from Chapter {}. Chapter {} may crash or corrupt its \
+             progress when it loads it.",
+            origin, chapter
+        )))
+    }
+
+    /// Checks the backup at `backup_path` before it is restored into
+    /// `chapter`, returning a warning for the user to accept first if it
+    /// looks corrupted, see [`format::validate`], or was taken in another
+    /// chapter, see [`Self::cross_chapter_warning`].
+    pub fn restore_warning(
+        &self,
+        backup_path: &Path,
+        chapter: Chapter,
+    ) -> io::Result<Option<String>> {
+        let cross_chapter = self.cross_chapter_warning(backup_path, chapter)?;
+        let corrupted = format::validate(&self.read_backup(backup_path)?)
+            .err()
+            .map(|problem| {
+                format!(
+                    "This backup looks corrupted: {}. The game may fail to load it.",
+                    problem
+                )
+            });
+        Ok(match (corrupted, cross_chapter) {
+            (Some(corrupted), Some(cross_chapter)) => {
+                Some(format!("{} {}", corrupted, cross_chapter))
+            }
+            (corrupted, cross_chapter) => corrupted.or(cross_chapter),
+        })
+    }
+
+    /// Moves a backup to the trash, unless it is of a frozen slot. It can be
+    /// put back with [`Self::restore_from_trash`] until it is purged.
+    pub fn delete(&self, backup_path: &Path) -> io::Result<()> {
+        let filename = backup_path.file_name().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a backup", backup_path.display()),
+            )
+        })?;
+        if let Some((chapter, slot, _)) = filename
+            .to_str()
+            .and_then(|filename| self.parse_backup_filename(filename))
+        {
+            self.check_not_frozen(chapter, slot)?;
+        }
+        let trash_directory = self.local_directory.join(TRASH_DIRECTORY);
+        fs::create_dir_all(&trash_directory)?;
+        let trash_path = trash_directory.join(filename);
+        fs::rename(backup_path, &trash_path)?;
+        // Its modification time becomes when it was trashed, which is what
+        // the trash is emptied by.
+        OpenOptions::new()
+            .write(true)
+            .open(&trash_path)?
+            .set_modified(SystemTime::now())?;
+        if let Some(mirror_path) = self.mirror_path(backup_path) {
+            remove_mirrored(&mirror_path)?;
+        }
+        Ok(())
+    }
+
+    /// The backups in the trash, with [`SaveFile::modified`] being when they
+    /// were trashed.
+    pub fn trash(&self) -> io::Result<Vec<SaveFile>> {
+        match self.backups_in(&self.local_directory.join(TRASH_DIRECTORY)) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            result => result,
+        }
+    }
+
+    /// Puts the backup at `trash_path`, in the trash, back among the others,
+    /// returning its path there.
+    pub fn restore_from_trash(&self, trash_path: &Path) -> io::Result<PathBuf> {
+        let filename = trash_path.file_name().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a backup", trash_path.display()),
+            )
+        })?;
+        let backup_path = self.local_directory.join(filename);
+        fs::rename(trash_path, &backup_path)?;
+        if let Some(created) = filename.to_str().and_then(backup_created_secs) {
+            OpenOptions::new()
+                .write(true)
+                .open(&backup_path)?
+                .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(created))?;
+        }
+        if self.mirror_directory.is_some() {
+            self.mirror(&backup_path, &self.read_backup(&backup_path)?)?;
+        }
+        Ok(backup_path)
+    }
+
+    /// Permanently deletes whatever has been in the trash for longer than
+    /// `age`, returning it.
+    pub fn purge_trash_older_than(&self, age: Duration) -> io::Result<Vec<SaveFile>> {
+        let now = SystemTime::now();
+        let mut purged = Vec::new();
+        for save in self.trash()? {
+            let expired = save.modified.is_some_and(|trashed| {
+                now.duration_since(trashed)
+                    .is_ok_and(|in_trash| in_trash > age)
+            });
+            if expired {
+                self.purge(&save.path)?;
+                purged.push(save);
+            }
+        }
+        Ok(purged)
+    }
+
+    /// Permanently deletes a backup, in the trash or not, unless it is of a
+    /// frozen slot, along with its contents once no other backup shares them.
+    pub fn purge(&self, backup_path: &Path) -> io::Result<()> {
+        let origin = backup_path
+            .file_name()
+            .and_then(|filename| filename.to_str())
+            .and_then(|filename| self.parse_backup_filename(filename));
+        if let Some((chapter, slot, _)) = &origin {
+            self.check_not_frozen(*chapter, *slot)?;
+        }
+        let companions = self.recorded_companions(backup_path)?;
+        fs::remove_file(backup_path)?;
+        if let Some(id) = backup_path.file_name().and_then(|name| name.to_str()) {
+            self.index()?.remove(id)?;
+        }
+        if let Some(mirror_path) = self.mirror_path(backup_path) {
+            remove_mirrored(&mirror_path)?;
+        }
+        if !companions.is_empty()
+            && let Some(companions_directory) = self.companions_directory(backup_path)
+        {
+            fs::remove_dir_all(companions_directory)?;
+        }
+
+        let mut hashes = origin
+            .map(|(_, _, hash)| hash)
+            .into_iter()
+            .chain(companions.into_iter().map(|(_, hash)| hash))
+            .collect::<Vec<_>>();
+        while let Some(hash) = hashes.pop() {
+            if self.object_in_use(&hash)? {
+                continue;
+            }
+            // The base may have been kept only for this object.
+            hashes.extend(self.object_base(&hash).ok().flatten());
+            match fs::remove_file(self.object_path(&hash)) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// How many bytes of stored objects purging every backup in
+    /// `backup_paths` would free: those only they, or files backed up with
+    /// them, refer to, along with any base kept only for those.
+    pub fn reclaimable_bytes(&self, backup_paths: &[PathBuf]) -> io::Result<u64> {
+        let trash_directory = self.local_directory.join(TRASH_DIRECTORY);
+        let mut kept = HashSet::new();
+        let mut candidates = Vec::new();
+        for directory in [&self.local_directory, &trash_directory] {
+            let entries = match fs::read_dir(directory) {
+                Ok(entries) => entries,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+                Err(error) => return Err(error),
+            };
+            for entry in entries {
+                let path = entry?.path();
+                let Some((_, _, hash)) = path
+                    .file_name()
+                    .and_then(|filename| filename.to_str())
+                    .and_then(|filename| self.parse_backup_filename(filename))
+                else {
+                    continue;
+                };
+                let hashes = self
+                    .recorded_companions(&path)?
+                    .into_iter()
+                    .map(|(_, hash)| hash)
+                    .chain([hash]);
+                if backup_paths.contains(&path) {
+                    candidates.extend(hashes);
+                } else {
+                    kept.extend(hashes);
+                }
+            }
+        }
+        // Bases of objects still in use stay with them.
+        let mut bases = kept.iter().cloned().collect::<Vec<_>>();
+        while let Some(hash) = bases.pop() {
+            if let Some(base) = self.object_base(&hash).ok().flatten()
+                && kept.insert(base.clone())
+            {
+                bases.push(base);
+            }
+        }
+        let mut freed = HashSet::new();
+        let mut reclaimable = 0;
+        while let Some(hash) = candidates.pop() {
+            if kept.contains(&hash) || !freed.insert(hash.clone()) {
+                continue;
+            }
+            if let Ok(metadata) = fs::metadata(self.object_path(&hash)) {
+                reclaimable += metadata.len();
+            }
+            candidates.extend(self.object_base(&hash).ok().flatten());
+        }
+        Ok(reclaimable)
+    }
+
+    /// Whether any backup, or file backed up with one, refers to the object
+    /// stored under `hash`, or any other object is stored as the difference
+    /// from it.
+    fn object_in_use(&self, hash: &str) -> io::Result<bool> {
+        let marker = format!("_{}_", hash);
+        let trash_directory = self.local_directory.join(TRASH_DIRECTORY);
+        for directory in [&self.local_directory, &trash_directory] {
+            let entries = match fs::read_dir(directory) {
+                Ok(entries) => entries,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+                Err(error) => return Err(error),
+            };
+            for entry in entries {
+                if entry?.file_name().to_string_lossy().contains(&marker) {
+                    return Ok(true);
+                }
+            }
+        }
+        let prefix = format!("{}_", hash);
+        let companions_directories =
+            match fs::read_dir(self.local_directory.join(COMPANIONS_DIRECTORY)) {
+                Ok(entries) => Some(entries),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+                Err(error) => return Err(error),
+            };
+        for directory in companions_directories.into_iter().flatten() {
+            for entry in fs::read_dir(directory?.path())? {
+                if entry?.file_name().to_string_lossy().starts_with(&prefix) {
+                    return Ok(true);
+                }
+            }
+        }
+        let objects = match fs::read_dir(self.local_directory.join(OBJECTS_DIRECTORY)) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(error) => return Err(error),
+        };
+        for entry in objects {
+            let Some(other) = entry?.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            if other != hash && self.object_base(&other)?.as_deref() == Some(hash) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// When the freeze on `chapter`'s `slot` ends, or `None` if it is not
+    /// frozen.
+    pub fn frozen_until(&self, chapter: Chapter, slot: Slot) -> io::Result<Option<SystemTime>> {
+        let freezes = Freezes::load(&self.local_directory)?;
+        Ok(freezes.frozen_until(&self.profile.save_filename(chapter, slot)))
+    }
+
+    /// Blocks restoring into `chapter`'s `slot`, and deleting its backups,
+    /// until `until`.
+    pub fn freeze(&self, chapter: Chapter, slot: Slot, until: SystemTime) -> io::Result<()> {
+        self.ensure_local_directory()?;
+        let mut freezes = Freezes::load(&self.local_directory)?;
+        freezes.freeze(&self.profile.save_filename(chapter, slot), until);
+        freezes.save(&self.local_directory)
+    }
+
+    /// Lifts the freeze on `chapter`'s `slot`, returning whether it was
+    /// frozen.
+    pub fn unfreeze(&self, chapter: Chapter, slot: Slot) -> io::Result<bool> {
+        let mut freezes = Freezes::load(&self.local_directory)?;
+        let was_frozen = freezes.unfreeze(&self.profile.save_filename(chapter, slot));
+        if was_frozen {
+            freezes.save(&self.local_directory)?;
+        }
+        Ok(was_frozen)
+    }
+
+    pub(crate) fn check_not_frozen(&self, chapter: Chapter, slot: Slot) -> io::Result<()> {
+        match self.frozen_until(chapter, slot)? {
+            Some(until) => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "Chapter {} Slot {} is frozen until {}; unfreeze it first",
+                    chapter,
+                    slot + 1,
+                    freeze::format_local_time(until)
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Snapshots a finished chapter's slot together with the completion and
+    /// persistent files the next chapter will read, so the handoff between
+    /// chapters can be replayed later. The slot itself is also backed up as
+    /// usual. Fails while the backups are encrypted, since the handoff's
+    /// copies are not.
+    pub fn chapter_handoff(&self, chapter: Chapter, slot: Slot) -> io::Result<PathBuf> {
+        self.check_unencrypted("chapter handoffs")?;
+        let slot_filename = self.profile.save_filename(chapter, slot);
+        let Some(completion_slot_offset) = self.profile.completion_slot_offset else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{} does not hand off between chapters", self.profile.name),
+            ));
+        };
+        let completion_filename = self
+            .profile
+            .save_filename(chapter, slot + completion_slot_offset);
+
+        let slot_path = self.game_directory().join(&slot_filename);
+        let completion_path = self.game_directory().join(&completion_filename);
+        if !completion_path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Chapter {} Slot {} has no completion file ({}). Finish the chapter first.",
+                    chapter,
+                    slot + 1,
+                    completion_filename
+                ),
+            ));
+        }
+
+        self.backup(chapter, slot)?;
+
+        let handoff_directory = self.local_directory.join(HANDOFFS_DIRECTORY).join(format!(
+            "chapter{}_slot{}_{}",
+            chapter,
+            slot,
+            now_since_epoch().as_secs()
+        ));
+        fs::create_dir_all(&handoff_directory)?;
+
+        let mut files = vec![
+            (slot_path, slot_filename),
+            (completion_path, completion_filename),
+        ];
+        for shared_filename in &self.profile.shared_files {
+            let shared_path = self.game_directory().join(shared_filename);
+            if shared_path.exists() {
+                files.push((shared_path, shared_filename.clone()));
+            }
+        }
+        for (source, filename) in files {
+            fs::copy(&source, handoff_directory.join(filename))?;
+        }
+
+        Ok(handoff_directory)
+    }
+
+    /// Where copies of the whole game save directory are kept, one directory
+    /// each, see [`crate::snapshot`].
+    pub(crate) fn snapshots_directory(&self) -> PathBuf {
+        self.local_directory.join(SNAPSHOTS_DIRECTORY)
+    }
+
+    /// Copies the whole game save directory aside whenever Steam has installed
+    /// a new build of the game since we last looked, because updates
+    /// occasionally migrate or alter save data.
+    pub fn snapshot_on_game_update(&self) -> io::Result<Option<GameUpdate>> {
+        match self.game_build_id() {
+            Some(build_id) => self.snapshot_on_build(&build_id),
+            None => Ok(None),
+        }
+    }
+
+    /// Takes the game as updated without snapshotting its saves, after
+    /// [`GameUpdate::Unsnapshotted`].
+    pub fn skip_game_update_snapshot(&self) -> io::Result<()> {
+        match self.game_build_id() {
+            Some(build_id) => self.record_build_id(&build_id),
+            None => Ok(()),
+        }
+    }
+
+    /// The build of the game Steam has installed, if it installed it.
+    fn game_build_id(&self) -> Option<String> {
+        self.profile
+            .steam_app_id
+            .and_then(steam::app_manifest_path)
+            .and_then(|manifest_path| steam::read_build_id(&manifest_path))
+    }
+
+    fn record_build_id(&self, build_id: &str) -> io::Result<()> {
+        fs::write(self.local_directory.join(GAME_BUILD_ID_FILENAME), build_id)
+    }
+
+    /// Snapshots the game save directory if `build_id` is not the build last
+    /// seen. The new build is only recorded once it is snapshotted, so an
+    /// update seen while snapshots are off is reported until it is skipped.
+    fn snapshot_on_build(&self, build_id: &str) -> io::Result<Option<GameUpdate>> {
+        let previous_build_id =
+            fs::read_to_string(self.local_directory.join(GAME_BUILD_ID_FILENAME)).ok();
+        if previous_build_id.as_deref() == Some(build_id) {
+            return Ok(None);
+        }
+
+        // The first time we see the game there is nothing to compare against.
+        let update = match previous_build_id {
+            Some(previous_build_id) => {
+                if self.is_encrypted() {
+                    return Ok(Some(GameUpdate::Unsnapshotted));
+                }
+                let snapshot_directory = self.snapshots_directory().join(format!(
+                    "update_{}_to_{}_{}",
+                    previous_build_id,
+                    build_id,
+                    now_since_epoch().as_secs()
+                ));
+                copy_directory_files(self.game_directory(), &snapshot_directory)?;
+                Some(GameUpdate::Snapshotted(snapshot_directory))
+            }
+            None => None,
+        };
+
+        self.record_build_id(build_id)?;
+        Ok(update)
+    }
+}
+
+fn companion_hashes(companions: &[(String, Vec<u8>)]) -> Vec<(String, String)> {
+    let mut hashes = companions
+        .iter()
+        .map(|(filename, contents)| (filename.clone(), hash_contents(contents)))
+        .collect::<Vec<_>>();
+    hashes.sort();
+    hashes
+}
+
+/// Removes a mirrored copy, which is read-only, if it exists.
+fn remove_mirrored(mirror_path: &Path) -> io::Result<()> {
+    let metadata = match fs::metadata(mirror_path) {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error),
+    };
+    let mut permissions = metadata.permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(mirror_path, permissions)?;
+    fs::remove_file(mirror_path)
+}
+
+/// Replaces the object at `path` with `stored`, so an interrupted migration
+/// never leaves it half-written.
+fn replace_object(path: &Path, stored: &[u8]) -> io::Result<()> {
+    write_atomically(path, stored)
+}
+
+/// Re-reads what was just restored to `path`, described to the user as
+/// `what`, failing unless it hashes to `expected_hash`, the hash the backup
+/// was stored under.
+fn verify_written(path: &Path, expected_hash: &str, what: &str) -> io::Result<()> {
+    let written_hash = hash_contents(&fs::read(path)?);
+    if written_hash != expected_hash {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "could not verify the restore: {} hashes to {} but the backup to {}",
+                what, written_hash, expected_hash
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Writes `contents` to `path` so that it holds either its old contents or
+/// all of the new ones, even if we crash or lose power partway: they are
+/// written to a temporary file beside it, flushed to disk, then renamed over
+/// it.
+fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".deltasaver-partial");
+    let temporary = PathBuf::from(temporary);
+    let written = (|| {
+        let mut file = File::create(&temporary)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&temporary, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    written?;
+    // Makes the rename itself durable. Windows has no equivalent for
+    // directories, and NTFS journals renames anyway.
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
+
+fn locked_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "the backups are encrypted; unlock them with the passphrase first",
+    )
+}
+
+/// The base of the object `stored`, if it is stored as the difference from
+/// one, and the rest of it.
+fn split_delta(stored: &[u8]) -> (Option<&str>, &[u8]) {
+    stored
+        .strip_prefix(DELTA_MAGIC)
+        .filter(|rest| rest.len() >= DELTA_HEADER_LENGTH - DELTA_MAGIC.len())
+        .map(|rest| rest.split_at(DELTA_HEADER_LENGTH - DELTA_MAGIC.len()))
+        .and_then(|(base, delta)| Some((Some(str::from_utf8(base).ok()?), delta)))
+        .unwrap_or((None, stored))
+}
+
+/// `stored` decompressed if it is zstd-compressed, or as it is otherwise, as
+/// objects written before compression are.
+fn decompress(stored: Vec<u8>) -> io::Result<Vec<u8>> {
+    if stored.starts_with(&ZSTD_MAGIC) {
+        zstd::decode_all(stored.as_slice())
+    } else {
+        Ok(stored)
+    }
+}
+
+/// Writes `contents` to `path`, or to `path` with a `-{n}` suffix if something
+/// is already there, so an existing backup is never overwritten. Returns the
+/// path written.
+fn write_new_file(path: &Path, contents: &[u8]) -> io::Result<PathBuf> {
+    let mut candidate = path.to_path_buf();
+    for suffix in 1.. {
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&candidate)
+        {
+            Ok(mut file) => {
+                file.write_all(contents)?;
+                return Ok(candidate);
+            }
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                let mut name = path.as_os_str().to_owned();
+                name.push(format!("-{}", suffix));
+                candidate = PathBuf::from(name);
+            }
+            Err(error) => return Err(error),
+        }
+    }
+    unreachable!()
+}
+
+pub(crate) fn copy_directory_files(source: &Path, destination: &Path) -> io::Result<()> {
+    fs::create_dir_all(destination)?;
+    for entry in fs::read_dir(source)? {
+        let path = entry?.path();
+        if let Some(filename) = path.file_name().filter(|_| path.is_file()) {
+            fs::copy(&path, destination.join(filename))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+
+    #[test]
+    fn backup_then_restore_round_trips() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let before = SyntheticSave {
+            room: 10,
+            ..Default::default()
+        };
+        let slot_path = before.write_to(store.game_directory(), 0).unwrap();
+
+        let backup_path = store.backup(1, 0).unwrap();
+        SyntheticSave {
+            room: 20,
+            ..Default::default()
+        }
+        .write_to(store.game_directory(), 0)
+        .unwrap();
+        store.restore(&backup_path, 1, 0).unwrap();
+
+        assert_eq!(fs::read(&slot_path).unwrap(), before.to_bytes());
+    }
+
+    #[test]
+    fn profile_names_stay_inside_the_profiles_directory() {
+        let data = Path::new("data");
+        assert_eq!(profile_directory(data, DELTARUNE_PROFILE_NAME), data);
+        for name in ["../x", "/etc", "a/../../b", ""] {
+            let directory = profile_directory(data, name);
+            assert_eq!(
+                directory.parent(),
+                Some(data.join(PROFILES_DIRECTORY).as_path())
+            );
+            assert!(!directory.ends_with(".."));
+        }
+        assert_eq!(
+            profile_directory(data, "UNDERTALE"),
+            data.join(PROFILES_DIRECTORY).join("UNDERTALE")
+        );
+    }
+
+    #[test]
+    fn restore_refuses_damaged_backups() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let slot_path = SyntheticSave::default()
+            .write_to(store.game_directory(), 0)
+            .unwrap();
+        let backup_path = store.backup(1, 0).unwrap();
+
+        fs::write(&backup_path, b"truncated").unwrap();
+        let error = store.restore(&backup_path, 1, 0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            fs::read(&slot_path).unwrap(),
+            SyntheticSave::default().to_bytes()
+        );
+    }
+
+    #[test]
+    fn restoring_into_another_chapter_warns_and_needs_a_full_save() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        SyntheticSave::default()
+            .write_to(store.game_directory(), 0)
+            .unwrap();
+        let backup_path = store.backup(1, 0).unwrap();
+        assert_eq!(store.cross_chapter_warning(&backup_path, 1).unwrap(), None);
+        assert!(
+            store
+                .cross_chapter_warning(&backup_path, 2)
+                .unwrap()
+                .unwrap()
+                .contains("Chapter 1")
+        );
+        store.restore(&backup_path, 2, 1).unwrap();
+        assert_eq!(
+            fs::read(store.game_directory().join("filech2_1")).unwrap(),
+            SyntheticSave::default().to_bytes()
+        );
+
+        fs::write(store.game_directory().join("filech1_1"), "not a save").unwrap();
+        let partial = store.backup(1, 1).unwrap();
+        assert_eq!(
+            store.cross_chapter_warning(&partial, 2).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(store.restore_warning(&backup_path, 1).unwrap(), None);
+        assert!(
+            store
+                .restore_warning(&partial, 1)
+                .unwrap()
+                .unwrap()
+                .contains("looks corrupted")
+        );
+    }
+
+    #[test]
+    fn colliding_backup_names_get_a_suffix() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root
+            .path()
+            .join("filech1_0_abcd_01ARZ3NDEKTSV4RRFFQ69G5FAV");
+        assert_eq!(write_new_file(&path, b"first").unwrap(), path);
+
+        let suffixed = write_new_file(&path, b"second").unwrap();
+        assert_eq!(
+            suffixed.file_name().unwrap(),
+            "filech1_0_abcd_01ARZ3NDEKTSV4RRFFQ69G5FAV-1"
+        );
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+        assert_eq!(fs::read(&suffixed).unwrap(), b"second");
+    }
+
+    #[test]
+    fn load_finds_game_saves_and_backups() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let save = SyntheticSave {
+            chapter: 2,
+            ..Default::default()
+        };
+        save.write_to(store.game_directory(), 1).unwrap();
+        fs::write(store.game_directory().join("dr.ini"), b"ini").unwrap();
+        store.backup(2, 1).unwrap();
+
+        let saves = store.load().unwrap();
+        assert!(saves.game.contains_key(&(2, 1)));
+        assert_eq!(saves.game.len(), 1);
+        assert_eq!(saves.local.len(), 1);
+        assert_eq!(
+            saves.local[0].hash.as_deref(),
+            Some(hash_contents(&save.to_bytes()).as_str())
+        );
+    }
+
+    #[test]
+    fn undertale_saves_are_found_and_backed_up() {
+        let root = tempfile::tempdir().unwrap();
+        let store = SaveStore::new(
+            GameProfile::undertale(root.path().join("game")),
+            root.path().join("local"),
+        );
+        fs::create_dir_all(store.game_directory()).unwrap();
+        store.ensure_local_directory().unwrap();
+        for filename in ["file0", "file9", "undertale.ini"] {
+            fs::write(store.game_directory().join(filename), filename).unwrap();
+        }
+
+        let backup_path = store.backup(1, 0).unwrap();
+        let saves = store.load().unwrap();
+        assert_eq!(saves.game.keys().collect::<Vec<_>>(), vec![&(1, 0)]);
+        assert_eq!(saves.local.len(), 1);
+
+        fs::write(store.game_directory().join("file0"), "changed").unwrap();
+        store.restore(&backup_path, 1, 0).unwrap();
+        assert_eq!(
+            fs::read_to_string(store.game_directory().join("file0")).unwrap(),
+            "file0"
+        );
+        assert!(store.chapter_handoff(1, 0).is_err());
+    }
+
+    #[test]
+    fn chapter_handoff_requires_completion_file() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let save = SyntheticSave::default();
+        save.write_to(store.game_directory(), 0).unwrap();
+        assert!(store.chapter_handoff(1, 0).is_err());
+
+        save.write_to(store.game_directory(), 3).unwrap();
+        let handoff = store.chapter_handoff(1, 0).unwrap();
+        assert!(handoff.join("filech1_0").exists());
+        assert!(handoff.join("filech1_3").exists());
+    }
+
+    #[test]
+    fn chapters_added_by_mods_are_detected() {
+        let root = tempfile::tempdir().unwrap();
+        let mut store = store_in(root.path()).unwrap();
+        let contents = SyntheticSave::default().to_bytes();
+        fs::write(store.game_directory().join("filech9_1"), &contents).unwrap();
+        // Completion files are not slots, so they do not count.
+        fs::write(store.game_directory().join("filech12_3"), &contents).unwrap();
+        assert!(store.load().unwrap().game.is_empty());
+
+        store.detect_chapters();
+        assert_eq!(store.profile().chapter_count, 9);
+        let saves = store.load().unwrap();
+        assert_eq!(saves.game.keys().collect::<Vec<_>>(), vec![&(9, 1)]);
+    }
+
+    #[test]
+    fn frozen_slots_refuse_restores_and_deletes() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        SyntheticSave::default()
+            .write_to(store.game_directory(), 0)
+            .unwrap();
+        let backup_path = store.backup(1, 0).unwrap();
+
+        let until = SystemTime::now() + Duration::from_secs(3600);
+        store.freeze(1, 0, until).unwrap();
+        assert!(store.frozen_until(1, 0).unwrap().is_some());
+        assert_eq!(store.frozen_until(1, 1).unwrap(), None);
+        let error = store.restore(&backup_path, 1, 0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(store.delete(&backup_path).is_err());
+        // The freeze file is not mistaken for a backup.
+        assert_eq!(store.load().unwrap().local.len(), 1);
+
+        assert!(store.unfreeze(1, 0).unwrap());
+        store.restore(&backup_path, 1, 0).unwrap();
+        store.delete(&backup_path).unwrap();
+    }
+
+    #[test]
+    fn unchanged_slots_are_not_backed_up_twice_when_skipping() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap().with_skip_identical(true);
+        SyntheticSave::default()
+            .write_to(store.game_directory(), 0)
+            .unwrap();
+        let first = store.backup(1, 0).unwrap();
+        assert_eq!(store.backup(1, 0).unwrap(), first);
+
+        SyntheticSave {
+            gold: 5,
+            ..Default::default()
+        }
+        .write_to(store.game_directory(), 0)
+        .unwrap();
+        let second = store.backup(1, 0).unwrap();
+        assert_ne!(second, first);
+        assert_eq!(store.load().unwrap().local.len(), 2);
+
+        store.with_skip_identical(false).backup(1, 0).unwrap();
+        assert_eq!(store.load().unwrap().local.len(), 3);
+    }
+
+    #[test]
+    fn deleted_backups_wait_in_the_trash() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let contents = SyntheticSave::default().to_bytes();
+        let backup_path = store.import(1, 0, &contents).unwrap();
+        let hash = store.load().unwrap().local[0].hash.clone().unwrap();
+
+        store.delete(&backup_path).unwrap();
+        assert!(store.load().unwrap().local.is_empty());
+        let trash = store.trash().unwrap();
+        assert_eq!(trash.len(), 1);
+        assert!(store.object_path(&hash).exists());
+
+        let restored = store.restore_from_trash(&trash[0].path).unwrap();
+        assert_eq!(restored, backup_path);
+        assert_eq!(store.read_backup(&restored).unwrap(), contents);
+
+        store.delete(&restored).unwrap();
+        assert!(
+            store
+                .purge_trash_older_than(Duration::from_secs(3600))
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            store.purge_trash_older_than(Duration::ZERO).unwrap().len(),
+            1
+        );
+        assert!(store.trash().unwrap().is_empty());
+        assert!(!store.object_path(&hash).exists());
+    }
+
+    #[test]
+    fn identical_backups_share_one_object_and_old_backups_migrate() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let contents = SyntheticSave::default().to_bytes();
+        let hash = hash_contents(&contents);
+        let first = store.import(1, 0, &contents).unwrap();
+        let second = store.import(2, 1, &contents).unwrap();
+        assert_eq!(fs::metadata(&first).unwrap().len(), 0);
+        assert_eq!(store.read_backup(&second).unwrap(), contents);
+        assert_eq!(
+            fs::read_dir(root.path().join("local").join(OBJECTS_DIRECTORY))
+                .unwrap()
+                .count(),
+            1
+        );
+        assert_eq!(
+            store.load().unwrap().local[0].size,
+            Some(contents.len() as u64)
+        );
+
+        store.purge(&first).unwrap();
+        assert!(store.object_path(&hash).exists());
+        store.purge(&second).unwrap();
+        assert!(!store.object_path(&hash).exists());
+
+        let old = root
+            .path()
+            .join("local")
+            .join(format!("filech1_0_{}_01ARZ3NDEKTSV4RRFFQ69G5FAV", hash));
+        fs::write(&old, &contents).unwrap();
+        store.ensure_local_directory().unwrap();
+        assert_eq!(fs::metadata(&old).unwrap().len(), 0);
+        assert_eq!(store.read_backup(&old).unwrap(), contents);
+    }
+
+    #[test]
+    fn integrity_checks_find_damaged_and_missing_objects() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let game = store.game_directory().to_path_buf();
+        SyntheticSave::default().write_to(&game, 0).unwrap();
+        fs::write(game.join("filech1_3"), "completion").unwrap();
+        let intact = store.backup(1, 0).unwrap();
+        SyntheticSave {
+            chapter: 2,
+            ..Default::default()
+        }
+        .write_to(&game, 1)
+        .unwrap();
+        let rotted = store.backup(2, 1).unwrap();
+        assert!(store.check_integrity().unwrap().is_empty());
+
+        let hash = |path: &Path| {
+            store
+                .parse_backup_filename(path.file_name().unwrap().to_str().unwrap())
+                .unwrap()
+                .2
+        };
+        fs::write(store.object_path(&hash(&rotted)), b"bit rot").unwrap();
+        let (_, completion_hash) = store.recorded_companions(&intact).unwrap().remove(0);
+        fs::remove_file(store.object_path(&completion_hash)).unwrap();
+
+        let mut damaged = store.check_integrity().unwrap();
+        damaged.sort_by(|a, b| a.save.path.cmp(&b.save.path));
+        assert_eq!(
+            damaged
+                .iter()
+                .map(|damaged| &damaged.save.path)
+                .collect::<Vec<_>>(),
+            vec![&intact, &rotted]
+        );
+        assert!(damaged[0].problem.contains("filech1_3"));
+    }
+
+    #[test]
+    fn legacy_backups_are_renamed_and_unreadable_ones_reported() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let contents = SyntheticSave::default().to_bytes();
+        let hash = hash_contents(&contents);
+        let local = root.path().join("local");
+        let legacy = format!("filech1_0_{}_1700000000_500000000", hash);
+        fs::write(local.join(&legacy), &contents).unwrap();
+        fs::write(
+            local.join(format!("filech2_1_{}_1700000001_0", hash)),
+            "cut",
+        )
+        .unwrap();
+        fs::write(local.join("filech1_0.bak"), &contents).unwrap();
+        store.ensure_local_directory().unwrap();
+        store
+            .index()
+            .unwrap()
+            .record(
+                &legacy,
+                &index::Entry {
+                    chapter: 1,
+                    slot: 0,
+                    hash: hash.clone(),
+                    note: Some("before the fountain".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let mut branches = Branches::load(&local).unwrap();
+        branches.switch("pacifist");
+        branches.add(&legacy);
+        branches.save(&local).unwrap();
+
+        let migration = store.migrate_legacy_backups().unwrap();
+        assert_eq!(migration.migrated.len(), 1);
+        let mut skipped = migration
+            .skipped
+            .iter()
+            .map(|(filename, _)| filename.as_str())
+            .collect::<Vec<_>>();
+        skipped.sort();
+        assert_eq!(
+            skipped,
+            vec![
+                "filech1_0.bak",
+                format!("filech2_1_{}_1700000001_0", hash).as_str()
+            ]
+        );
+        assert!(!local.join(&legacy).exists());
+
+        let migrated = &migration.migrated[0];
+        let filename = migrated.file_name().unwrap().to_str().unwrap();
+        assert!(Ulid::from_string(backup_id(filename).unwrap()).is_ok());
+        assert_eq!(backup_created_secs(filename), Some(1_700_000_000));
+        assert_eq!(store.read_backup(migrated).unwrap(), contents);
+        let entry = store.index().unwrap().get(filename).unwrap().unwrap();
+        assert_eq!(entry.note.as_deref(), Some("before the fountain"));
+        let saves = store.load().unwrap();
+        let save = saves.find_backup(filename).unwrap();
+        assert_eq!(Branches::load(&local).unwrap().branch_of(save), "pacifist");
+        assert_eq!(
+            store.history().unwrap().last().unwrap().migration,
+            Migration::LegacyNames
+        );
+
+        assert!(store.migrate_legacy_backups().unwrap().migrated.is_empty());
+    }
+
+    #[test]
+    fn extra_files_are_kept_apart_and_read_only() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        SyntheticSave::default()
+            .write_to(store.game_directory(), 0)
+            .unwrap();
+        fs::write(store.game_directory().join("filech1_3"), b"completion").unwrap();
+
+        let saves = store.load().unwrap();
+        assert_eq!(saves.game.len(), 1);
+        assert!(saves.extra.contains_key(&(1, 3)));
+
+        let backup_path = store.backup(1, 3).unwrap();
+        assert_eq!(store.read_backup(&backup_path).unwrap(), b"completion");
+        assert_eq!(store.load().unwrap().local[0].slot, 3);
+        let error = store.restore(&backup_path, 1, 3).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn objects_are_compressed_and_uncompressed_ones_still_read() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let contents = SyntheticSave::default().to_bytes();
+        let hash = hash_contents(&contents);
+        let backup_path = store.import(1, 0, &contents).unwrap();
+        let stored = fs::read(store.object_path(&hash)).unwrap();
+        assert!(stored.starts_with(&ZSTD_MAGIC));
+        assert!(stored.len() < contents.len());
+        assert_eq!(store.read_backup(&backup_path).unwrap(), contents);
+        assert_eq!(
+            store.load().unwrap().local[0].size,
+            Some(contents.len() as u64)
+        );
+
+        fs::write(store.object_path(&hash), &contents).unwrap();
+        assert_eq!(store.read_backup(&backup_path).unwrap(), contents);
+
+        assert!(store.history().unwrap().is_empty());
+        store.ensure_local_directory().unwrap();
+        assert!(
+            fs::read(store.object_path(&hash))
+                .unwrap()
+                .starts_with(&ZSTD_MAGIC)
+        );
+        let history = store.history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].migration, Migration::Recompression);
+        assert_eq!(history[0].count, 1);
+    }
+
+    #[test]
+    fn consecutive_backups_are_stored_as_deltas() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let mut save = SyntheticSave {
+            flags: (0..2000)
+                .map(|flag| (flag, (flag as i64 * 7919) % 1009))
+                .collect(),
+            ..Default::default()
+        };
+        let before = save.to_bytes();
+        let base_backup = store.import(1, 0, &before).unwrap();
+        save.gold = 5;
+        save.flags.insert(1500, 1);
+        let after = save.to_bytes();
+        let delta_backup = store.import(1, 0, &after).unwrap();
+
+        let base_hash = hash_contents(&before);
+        let delta_hash = hash_contents(&after);
+        let stored = fs::read(store.object_path(&delta_hash)).unwrap();
+        assert_eq!(split_delta(&stored).0, Some(base_hash.as_str()));
+        assert!(stored.len() * 4 < fs::read(store.object_path(&base_hash)).unwrap().len());
+        assert_eq!(store.read_backup(&delta_backup).unwrap(), after);
+        let saves = store.load().unwrap().local;
+        let listed = saves.iter().find(|save| save.path == delta_backup).unwrap();
+        assert_eq!(listed.size, Some(after.len() as u64));
+
+        let encrypted = store.encrypt("hunter2").unwrap();
+        assert_eq!(encrypted.read_backup(&delta_backup).unwrap(), after);
+
+        encrypted.purge(&base_backup).unwrap();
+        assert!(store.object_path(&base_hash).exists());
+        assert_eq!(encrypted.read_backup(&delta_backup).unwrap(), after);
+        encrypted.purge(&delta_backup).unwrap();
+        assert!(!store.object_path(&delta_hash).exists());
+        assert!(!store.object_path(&base_hash).exists());
+    }
+
+    #[test]
+    fn encrypted_backups_need_the_passphrase() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let before = SyntheticSave::default().to_bytes();
+        let old_backup = store.import(1, 0, &before).unwrap();
+
+        let encrypted = store.encrypt("hunter2").unwrap();
+        let stored = fs::read(store.object_path(&hash_contents(&before))).unwrap();
+        assert!(encryption::is_sealed(&stored));
+        assert_eq!(encrypted.read_backup(&old_backup).unwrap(), before);
+        let after = SyntheticSave {
+            gold: 5,
+            ..Default::default()
+        }
+        .to_bytes();
+        let new_backup = encrypted.import(1, 1, &after).unwrap();
+        assert_eq!(store.history().unwrap()[0].migration, Migration::Encryption);
+
+        let locked = store_in(root.path()).unwrap();
+        assert!(locked.is_locked());
+        assert_eq!(locked.load().unwrap().local.len(), 2);
+        assert_eq!(
+            locked.read_backup(&new_backup).unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+        assert!(locked.import(1, 2, &after).is_err());
+        assert!(locked.unlock("wrong").is_err());
+        let unlocked = locked.unlock("hunter2").unwrap();
+        assert!(!unlocked.is_locked());
+        assert_eq!(unlocked.read_backup(&new_backup).unwrap(), after);
+    }
+
+    #[test]
+    fn nothing_unencrypted_is_kept_beside_encrypted_backups() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let mirror_directory = root.path().join("mirror");
+        let mirrored = store.with_mirror_directory(Some(mirror_directory.clone()));
+        assert!(mirrored.encrypt("hunter2").is_err());
+        let git_directory = root.path().join("history");
+        let committing = store.with_git_directory(Some(git_directory.clone()));
+        assert!(committing.encrypt("hunter2").is_err());
+        let contents = SyntheticSave::default().to_bytes();
+        let backup_path = store.import(1, 0, &contents).unwrap();
+        let id = backup_path.file_name().unwrap().to_str().unwrap();
+        store.index().unwrap().tag(id, "pacifist").unwrap();
+        assert!(store.encrypt("hunter2").is_err());
+        assert!(store.index().unwrap().untag(id, "pacifist").unwrap());
+
+        let encrypted = store.encrypt("hunter2").unwrap();
+        let index = encrypted.index().unwrap();
+        assert!(index.set_note(id, "before the fountain").is_err());
+        assert!(index.set_name(id, "Queen's mansion").is_err());
+        assert!(index.tag(id, "pacifist").is_err());
+        assert!(index.set_note(id, "").unwrap());
+        assert!(index.set_pinned(id, true).unwrap());
+        fs::write(encrypted.game_directory().join("filech1_0"), &contents).unwrap();
+        fs::write(encrypted.game_directory().join("filech1_3"), "completion").unwrap();
+        assert_eq!(
+            encrypted.chapter_handoff(1, 0).unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+        assert!(crate::snapshot::take(&encrypted, "everything").is_err());
+        assert!(!encrypted.snapshots_directory().exists());
+
+        let mirrored = encrypted.with_mirror_directory(Some(mirror_directory.clone()));
+        assert!(mirrored.backup(1, 0).is_err());
+        assert!(mirrored.sync_mirror().is_err());
+        assert!(!mirror_directory.exists());
+        assert!(encrypted.backup_with_note(1, 0, Some("noted")).is_err());
+        let committing = encrypted.with_git_directory(Some(git_directory.clone()));
+        assert!(committing.backup(1, 0).is_err());
+        assert!(!git_directory.exists());
+        assert_eq!(encrypted.load().unwrap().local.len(), 1);
+    }
+
+    #[test]
+    fn plaintext_copies_are_stored_or_listed_before_encrypting() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let local = root.path().join("local");
+        let contents = SyntheticSave::default().to_bytes();
+        let inline = local.join(format!(
+            "filech1_0_{}_{}",
+            hash_contents(&contents),
+            ulid::Ulid::new()
+        ));
+        fs::write(&inline, &contents).unwrap();
+        let trashed_contents = b"trashed";
+        fs::create_dir_all(local.join(TRASH_DIRECTORY)).unwrap();
+        let trashed = local.join(TRASH_DIRECTORY).join(format!(
+            "filech1_1_{}_{}",
+            hash_contents(trashed_contents),
+            ulid::Ulid::new()
+        ));
+        fs::write(&trashed, trashed_contents).unwrap();
+        let damaged = local.join(format!(
+            "filech1_2_{}_{}",
+            hash_contents(b"whole"),
+            ulid::Ulid::new()
+        ));
+        fs::write(&damaged, "cut").unwrap();
+        fs::write(store.game_directory().join("filech1_0"), &contents).unwrap();
+        let snapshot = crate::snapshot::take(&store, "everything").unwrap();
+
+        let error = store.encrypt("hunter2").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        let message = error.to_string();
+        assert!(message.contains(&snapshot.path.display().to_string()));
+        assert!(message.contains(&damaged.display().to_string()));
+        assert!(!message.contains(&inline.display().to_string()));
+        assert!(!message.contains(&trashed.display().to_string()));
+        assert_eq!(fs::metadata(&inline).unwrap().len(), 0);
+        assert_eq!(fs::metadata(&trashed).unwrap().len(), 0);
+        assert!(!store.is_encrypted());
+
+        fs::remove_dir_all(store.snapshots_directory()).unwrap();
+        fs::remove_file(&damaged).unwrap();
+        let encrypted = store.encrypt("hunter2").unwrap();
+        let stored = fs::read(store.object_path(&hash_contents(trashed_contents))).unwrap();
+        assert!(encryption::is_sealed(&stored));
+        assert_eq!(encrypted.read_backup(&inline).unwrap(), contents);
+    }
+
+    #[test]
+    fn game_updates_are_snapshotted_or_wait_to_be_skipped() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        fs::write(store.game_directory().join("filech1_0"), "save").unwrap();
+        assert_eq!(store.snapshot_on_build("1").unwrap(), None);
+        assert_eq!(store.snapshot_on_build("1").unwrap(), None);
+        let Some(GameUpdate::Snapshotted(snapshot)) = store.snapshot_on_build("2").unwrap() else {
+            panic!("the update was not snapshotted");
+        };
+        assert!(snapshot.join("filech1_0").is_file());
+
+        assert!(store.encrypt("hunter2").is_err());
+        fs::remove_dir_all(store.snapshots_directory()).unwrap();
+        let encrypted = store.encrypt("hunter2").unwrap();
+        for _ in 0..2 {
+            assert_eq!(
+                encrypted.snapshot_on_build("3").unwrap(),
+                Some(GameUpdate::Unsnapshotted)
+            );
+        }
+        encrypted.record_build_id("3").unwrap();
+        assert_eq!(encrypted.snapshot_on_build("3").unwrap(), None);
+        assert!(!encrypted.snapshots_directory().exists());
+    }
+
+    #[test]
+    fn backing_up_survives_an_unwritable_index() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        fs::create_dir(store.local_directory().join("index.sqlite")).unwrap();
+        let backup_path = store
+            .import(1, 0, &SyntheticSave::default().to_bytes())
+            .unwrap();
+        assert_eq!(store.load().unwrap().local[0].path, backup_path);
+    }
+
+    #[test]
+    fn the_mirror_keeps_plain_copies_in_sync() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let contents = SyntheticSave::default().to_bytes();
+        let unmirrored = store.import(1, 0, &contents).unwrap();
+
+        let mirror_directory = root.path().join("mirror");
+        fs::create_dir_all(&mirror_directory).unwrap();
+        fs::write(mirror_directory.join("notes.txt"), "mine").unwrap();
+        let store = store.with_mirror_directory(Some(mirror_directory.clone()));
+        store.ensure_local_directory().unwrap();
+        let mirrored = store.import(1, 1, &contents).unwrap();
+
+        let copies = |directory: &Path| {
+            let mut filenames = fs::read_dir(directory)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .collect::<Vec<_>>();
+            filenames.sort();
+            filenames
+        };
+        let filenames = copies(&mirror_directory);
+        assert_eq!(filenames.len(), 3);
+        assert!(filenames[0].starts_with("Chapter 1 Slot 1 "));
+        assert!(filenames[1].starts_with("Chapter 1 Slot 2 "));
+        let copy = mirror_directory.join(&filenames[1]);
+        assert_eq!(fs::read(&copy).unwrap(), contents);
+        assert!(fs::metadata(&copy).unwrap().permissions().readonly());
+
+        store.delete(&mirrored).unwrap();
+        fs::remove_file(&unmirrored).unwrap();
+        store.sync_mirror().unwrap();
+        assert_eq!(copies(&mirror_directory), vec!["notes.txt".to_string()]);
+    }
+
+    #[test]
+    fn completion_and_shared_files_go_with_their_slot() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap().with_skip_identical(true);
+        let game = store.game_directory().to_path_buf();
+        SyntheticSave::default().write_to(&game, 0).unwrap();
+        fs::write(game.join("filech1_3"), "chapter 1 done").unwrap();
+        fs::write(game.join("dr.ini"), "persistent").unwrap();
+
+        let first = store.backup(1, 0).unwrap();
+        assert_eq!(
+            store.companions(&first).unwrap(),
+            vec!["dr.ini".to_string(), "filech1_3".to_string()]
+        );
+        assert_eq!(store.load().unwrap().local.len(), 1);
+        fs::write(game.join("filech1_3"), "chapter 1 done again").unwrap();
+        let second = store.backup(1, 0).unwrap();
+        assert_ne!(first, second);
+
+        fs::write(game.join("dr.ini"), "changed").unwrap();
+        store.restore(&first, 1, 1).unwrap();
+        assert_eq!(
+            fs::read_to_string(game.join("filech1_4")).unwrap(),
+            "chapter 1 done"
+        );
+        assert_eq!(
+            fs::read_to_string(game.join("dr.ini")).unwrap(),
+            "persistent"
+        );
+        assert!(fs::read_dir(&game).unwrap().all(|entry| {
+            !entry
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .ends_with(".deltasaver-partial")
+        }));
+
+        store.purge(&first).unwrap();
+        store.purge(&second).unwrap();
+        assert!(
+            store
+                .companions_directory(&first)
+                .is_some_and(|path| !path.exists())
+        );
+        assert_eq!(
+            fs::read_dir(root.path().join("local").join(OBJECTS_DIRECTORY))
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn restoring_from_before_completion_removes_the_completion_file() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let game = store.game_directory().to_path_buf();
+        SyntheticSave::default().write_to(&game, 0).unwrap();
+        let before_completion = store.backup(1, 0).unwrap();
+        assert!(store.companions(&before_completion).unwrap().is_empty());
+
+        fs::write(game.join("filech1_3"), "chapter 1 done").unwrap();
+        store.restore(&before_completion, 1, 0).unwrap();
+        assert!(!game.join("filech1_3").exists());
+    }
+
+    #[test]
+    fn restores_are_verified_against_the_backup_hash() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("filech1_0");
+        fs::write(&path, "save").unwrap();
+        verify_written(&path, &hash_contents(b"save"), "Chapter 1 Slot 1").unwrap();
+        let error =
+            verify_written(&path, &hash_contents(b"other"), "Chapter 1 Slot 1").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("Chapter 1 Slot 1"));
+    }
+}
@@ -0,0 +1,318 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! What a save records about the game, read from the fields at the lines
+//! [`format`] gives, so saves can be told apart by more than their slot.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::Duration;
+
+use crate::format;
+use crate::rooms::RoomNames;
+use crate::save::Chapter;
+use crate::stats::{format_play_time, read_play_time};
+
+/// Displayed as e.g. `KRIS, Room 42, 1:30:05`, or with the room named by
+/// [`SaveSummary::describe`], with the rest as given by
+/// [`SaveSummary::progress`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveSummary {
+    /// The name typed at the start of the game.
+    pub name: String,
+    /// The index of the room the game was saved in.
+    pub room: u32,
+    pub play_time: Duration,
+    /// Dark dollars.
+    pub gold: u32,
+    pub lv: u32,
+    /// The party leader's HP and max HP.
+    pub hp: u32,
+    pub max_hp: u32,
+    /// The active party members, leader first.
+    pub party: Vec<PartyMember>,
+    pub route: Route,
+    /// The IDs of the enemies recruited, see [`format::RECRUIT_FLAG_BASE`].
+    pub recruited: BTreeSet<usize>,
+    /// How many key items are held.
+    pub key_items: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    Normal,
+    /// Set on Snowgrave and kept by every chapter after, see
+    /// [`format::WEIRD_ROUTE_FLAG`].
+    Weird,
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Route::Normal => f.write_str("Normal"),
+            Route::Weird => f.write_str("Weird"),
+        }
+    }
+}
+
+/// A party member and what they have equipped, by item ID, where 0 is
+/// nothing. Displayed as e.g. `Susie: weapon 2, armor none and 5`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartyMember {
+    /// See [`format::CHARACTER_NAMES`].
+    pub character: usize,
+    pub weapon: u32,
+    pub armor: [u32; 2],
+}
+
+impl PartyMember {
+    fn parse(lines: &[&str], character: usize) -> Option<Self> {
+        let stat = |offset| read_number(lines, format::character_line(character, offset));
+        Some(Self {
+            character,
+            weapon: stat(format::WEAPON_OFFSET)?,
+            armor: [stat(format::ARMOR1_OFFSET)?, stat(format::ARMOR2_OFFSET)?],
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        format::CHARACTER_NAMES[self.character]
+    }
+}
+
+impl fmt::Display for PartyMember {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let item = |id: u32| match id {
+            0 => "none".to_string(),
+            id => id.to_string(),
+        };
+        write!(
+            f,
+            "{}: weapon {}, armor {} and {}",
+            self.name(),
+            item(self.weapon),
+            item(self.armor[0]),
+            item(self.armor[1])
+        )
+    }
+}
+
+impl SaveSummary {
+    /// Reads the summary of a DELTARUNE save, or `None` if `contents` is not
+    /// one.
+    pub fn parse(contents: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(contents).ok()?;
+        let lines = text.lines().collect::<Vec<_>>();
+        let party = (0..format::PARTY_SIZE)
+            .map(|i| {
+                let character = read_number(&lines, format::PARTY_LINE + i)? as usize;
+                (character < format::CHARACTER_COUNT).then_some(character)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let leader = party[0];
+        Some(Self {
+            name: lines.get(format::NAME_LINE)?.trim().to_string(),
+            room: read_number(&lines, format::ROOM_LINE)?,
+            play_time: read_play_time(contents)?,
+            gold: read_number(&lines, format::GOLD_LINE)?,
+            lv: read_number(&lines, format::LV_LINE)?,
+            hp: read_number(&lines, format::character_line(leader, format::HP_OFFSET))?,
+            max_hp: read_number(
+                &lines,
+                format::character_line(leader, format::MAX_HP_OFFSET),
+            )?,
+            party: party
+                .into_iter()
+                .filter(|&character| character != 0)
+                .map(|character| PartyMember::parse(&lines, character))
+                .collect::<Option<_>>()?,
+            route: match read_number(&lines, format::FLAGS_LINE + format::WEIRD_ROUTE_FLAG)? {
+                0 => Route::Normal,
+                _ => Route::Weird,
+            },
+            recruited: format::RECRUIT_IDS
+                .iter()
+                .flat_map(|ids| ids.iter())
+                .copied()
+                .filter(|id| {
+                    read_flag(&lines, format::RECRUIT_FLAG_BASE + id).is_some_and(|flag| flag > 0.0)
+                })
+                .collect(),
+            key_items: (0..format::INVENTORY_SLOTS)
+                .filter(|slot| {
+                    let line = format::INVENTORY_LINE
+                        + slot * format::INVENTORY_KINDS
+                        + format::KEY_ITEM_KIND;
+                    read_number(&lines, line).is_some_and(|item| item != 0)
+                })
+                .count(),
+        })
+    }
+
+    /// A rough measure of how much of `chapter` is done, from 0 to 1: the
+    /// share of its enemies recruited, if known, and of the key item slots
+    /// filled. Saves keep no record of the rooms visited, so those cannot
+    /// count.
+    pub fn completion(&self, chapter: Chapter) -> f32 {
+        let key_items = self.key_items as f32 / format::INVENTORY_SLOTS as f32;
+        match self.recruits(chapter) {
+            Some((recruited, total)) => (recruited as f32 / total as f32 + key_items) / 2.0,
+            None => key_items,
+        }
+    }
+
+    /// How many of `chapter`'s recruitable enemies were recruited and how
+    /// many there are, or `None` if the chapter's recruits are not known.
+    pub fn recruits(&self, chapter: Chapter) -> Option<(usize, usize)> {
+        let ids = format::RECRUIT_IDS.get(usize::from(chapter).checked_sub(1)?)?;
+        Some((
+            ids.iter().filter(|id| self.recruited.contains(id)).count(),
+            ids.len(),
+        ))
+    }
+
+    /// Like its display, but naming the room if `rooms` knows it, e.g.
+    /// `KRIS, Cyber Field, 1:30:05`.
+    pub fn describe(&self, rooms: &RoomNames, chapter: Chapter) -> String {
+        format!(
+            "{}, {}, {}",
+            self.name,
+            rooms.describe(chapter, self.room),
+            format_play_time(self.play_time)
+        )
+    }
+
+    /// E.g. `LV 3, HP 120/160, D$ 250`.
+    pub fn progress(&self) -> String {
+        format!(
+            "LV {}, HP {}/{}, D$ {}",
+            self.lv, self.hp, self.max_hp, self.gold
+        )
+    }
+}
+
+impl fmt::Display for SaveSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, Room {}, {}",
+            self.name,
+            self.room,
+            format_play_time(self.play_time)
+        )
+    }
+}
+
+/// Flags may be negative, unlike the numbers [`read_number`] reads.
+fn read_flag(lines: &[&str], flag: usize) -> Option<f64> {
+    lines
+        .get(format::FLAGS_LINE + flag)?
+        .trim()
+        .parse::<f64>()
+        .ok()
+}
+
+/// GameMaker writes every number as a real, so whole values may still come
+/// with a fractional part.
+fn read_number(lines: &[&str], line: usize) -> Option<u32> {
+    let number = lines.get(line)?.trim().parse::<f64>().ok()?;
+    (number.is_finite() && number >= 0.0 && number <= u32::MAX as f64).then_some(number as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SyntheticSave;
+
+    #[test]
+    fn reads_name_room_play_time_and_progress() {
+        let contents = SyntheticSave {
+            name: "SUSIE".to_string(),
+            room: 42,
+            gold: 250,
+            lv: 3,
+            hp: 120,
+            max_hp: 160,
+            time_frames: (90 * 60 + 5) * format::FRAMES_PER_SECOND,
+            ..Default::default()
+        }
+        .to_bytes();
+
+        let summary = SaveSummary::parse(&contents).unwrap();
+        assert_eq!(summary.name, "SUSIE");
+        assert_eq!(summary.room, 42);
+        assert_eq!(summary.to_string(), "SUSIE, Room 42, 1:30:05");
+        assert_eq!(
+            summary.describe(&RoomNames::default(), 2),
+            summary.to_string()
+        );
+        assert_eq!(summary.progress(), "LV 3, HP 120/160, D$ 250");
+        assert_eq!(summary.route, Route::Normal);
+        assert_eq!(SaveSummary::parse(b"KRIS\n"), None);
+    }
+
+    #[test]
+    fn saves_with_the_weird_route_flag_are_on_it() {
+        let contents = SyntheticSave {
+            chapter: 2,
+            flags: [(format::WEIRD_ROUTE_FLAG, 3)].into(),
+            ..Default::default()
+        }
+        .to_bytes();
+        assert_eq!(SaveSummary::parse(&contents).unwrap().route, Route::Weird);
+    }
+
+    #[test]
+    fn counts_recruits_but_not_lost_ones() {
+        let recruit = |i: usize| format::RECRUIT_FLAG_BASE + format::RECRUIT_IDS[1][i];
+        let contents = SyntheticSave {
+            chapter: 2,
+            flags: [(recruit(0), 1), (recruit(1), 1), (recruit(2), -1)].into(),
+            ..Default::default()
+        }
+        .to_bytes();
+
+        let summary = SaveSummary::parse(&contents).unwrap();
+        assert_eq!(summary.recruits(2), Some((2, format::RECRUIT_IDS[1].len())));
+        assert_eq!(summary.recruits(1), Some((0, format::RECRUIT_IDS[0].len())));
+        assert_eq!(summary.recruits(4), None);
+    }
+
+    #[test]
+    fn completion_counts_recruits_and_key_items() {
+        let mut lines = SyntheticSave::default().lines();
+        for slot in 0..format::INVENTORY_SLOTS / 2 + 1 {
+            lines
+                [format::INVENTORY_LINE + slot * format::INVENTORY_KINDS + format::KEY_ITEM_KIND] =
+                "3".to_string();
+        }
+        for &id in format::RECRUIT_IDS[0] {
+            lines[format::FLAGS_LINE + format::RECRUIT_FLAG_BASE + id] = "1".to_string();
+        }
+        let summary = SaveSummary::parse(lines.join("\n").as_bytes()).unwrap();
+
+        assert_eq!(summary.key_items, 7);
+        let key_items = 7.0 / format::INVENTORY_SLOTS as f32;
+        assert_eq!(summary.completion(1), (1.0 + key_items) / 2.0);
+        assert_eq!(summary.completion(2), key_items / 2.0);
+        assert_eq!(summary.completion(4), key_items);
+    }
+
+    #[test]
+    fn reads_the_party_and_their_equipment() {
+        let mut lines = SyntheticSave {
+            party: [2, 1, 0],
+            ..Default::default()
+        }
+        .lines();
+        lines[format::character_line(2, format::WEAPON_OFFSET)] = "2".to_string();
+        lines[format::character_line(2, format::ARMOR2_OFFSET)] = "5".to_string();
+        let contents = lines.join("\n");
+
+        let party = SaveSummary::parse(contents.as_bytes()).unwrap().party;
+        assert_eq!(
+            party.iter().map(PartyMember::name).collect::<Vec<_>>(),
+            ["Susie", "Kris"]
+        );
+        assert_eq!(party[0].to_string(), "Susie: weapon 2, armor none and 5");
+    }
+}
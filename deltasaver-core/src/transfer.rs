@@ -0,0 +1,293 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Saves as base64 text, for pasting into and out of chat messages.
+//!
+//! A copied save is written as `{filename}:{base64}`, e.g.
+//! `filech2_0:MTIz...`, so that pasting it back in also recovers the slot it
+//! came from. Bare base64, as other tools share saves, is accepted too.
+//!
+//! Share codes are a shorter form for sending a save in a message: the save
+//! compressed with zstd, as `ds1.{filename}.{base64url}.{checksum}`, where the
+//! checksum is the first four bytes of the SHA-256 of the compressed save in
+//! hex, so that a code cut short in pasting is caught rather than imported.
+
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::profile::GameProfile;
+use crate::save::{Chapter, Slot};
+
+/// Real saves are a few hundred kilobytes at most, so anything much larger
+/// was not meant for us.
+pub(crate) const MAX_SAVE_SIZE: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    Empty,
+    NotBase64(String),
+    TooLarge,
+    NotASave,
+    UnknownFilename(String),
+    /// A share code whose checksum does not match, as when part of it was
+    /// lost in pasting.
+    ChecksumMismatch,
+    /// A share code whose save does not decompress.
+    Corrupt(String),
+    /// Text starting like a share code but not made of its parts.
+    Malformed,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Empty => f.write_str("there is nothing to import"),
+            DecodeError::NotBase64(message) => write!(f, "not valid base64: {}", message),
+            DecodeError::TooLarge => f.write_str("too large to be a save file"),
+            DecodeError::NotASave => f.write_str("does not look like a save file"),
+            DecodeError::UnknownFilename(filename) => {
+                write!(f, "{} is not a save slot of this game", filename)
+            }
+            DecodeError::ChecksumMismatch => {
+                f.write_str("share code is incomplete or mistyped (checksum mismatch)")
+            }
+            DecodeError::Corrupt(message) => write!(f, "share code is corrupt: {}", message),
+            DecodeError::Malformed => write!(
+                f,
+                "not a share code: it should look like {}.FILENAME.DATA.CHECKSUM",
+                SHARE_CODE_PREFIX
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedSave {
+    /// The slot the save was copied from, if the text said.
+    pub origin: Option<(Chapter, Slot)>,
+    pub contents: Vec<u8>,
+}
+
+/// Encodes a save named `filename` for [`decode_save`].
+pub fn encode_save(filename: &str, contents: &[u8]) -> String {
+    format!("{}:{}", filename, STANDARD.encode(contents))
+}
+
+/// Begins every share code, naming the version of the format.
+const SHARE_CODE_PREFIX: &str = "ds1";
+
+/// Saves are mostly repetitive text, so compressing harder costs little.
+const SHARE_CODE_COMPRESSION_LEVEL: i32 = 19;
+
+/// Encodes a save named `filename` as a share code.
+pub fn encode_share_code(filename: &str, contents: &[u8]) -> std::io::Result<String> {
+    let compressed = zstd::bulk::compress(contents, SHARE_CODE_COMPRESSION_LEVEL)?;
+    Ok(format!(
+        "{}.{}.{}.{}",
+        SHARE_CODE_PREFIX,
+        filename,
+        URL_SAFE_NO_PAD.encode(&compressed),
+        share_code_checksum(&compressed)
+    ))
+}
+
+fn share_code_checksum(compressed: &[u8]) -> String {
+    Sha256::digest(compressed)[..4]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Decodes a code produced by [`encode_share_code`], checking its checksum
+/// and that the result is plausibly a save.
+pub fn decode_share_code(profile: &GameProfile, text: &str) -> Result<DecodedSave, DecodeError> {
+    // Chat clients like to wrap long lines.
+    let code = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    if code.is_empty() {
+        return Err(DecodeError::Empty);
+    }
+    // Filenames may have dots in them, but the data and checksum never do,
+    // so those are split off from the right.
+    let Some((filename, encoded, checksum)) = code
+        .strip_prefix(SHARE_CODE_PREFIX)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .and_then(|rest| {
+            let (rest, checksum) = rest.rsplit_once('.')?;
+            let (filename, encoded) = rest.rsplit_once('.')?;
+            Some((filename, encoded, checksum))
+        })
+        .filter(|(filename, encoded, checksum)| {
+            !filename.is_empty() && !encoded.is_empty() && !checksum.is_empty()
+        })
+    else {
+        return Err(DecodeError::Malformed);
+    };
+    let origin = profile
+        .parse_save_filename(filename)
+        .ok_or_else(|| DecodeError::UnknownFilename(filename.to_string()))?;
+    let compressed = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|error| DecodeError::NotBase64(error.to_string()))?;
+    if !share_code_checksum(&compressed).eq_ignore_ascii_case(checksum) {
+        return Err(DecodeError::ChecksumMismatch);
+    }
+    let contents = zstd::bulk::decompress(&compressed, MAX_SAVE_SIZE)
+        .map_err(|error| DecodeError::Corrupt(error.to_string()))?;
+
+    if !looks_like_save(&contents) {
+        return Err(DecodeError::NotASave);
+    }
+
+    Ok(DecodedSave {
+        origin: Some(origin),
+        contents,
+    })
+}
+
+/// Whether `text` is meant as a share code rather than base64.
+fn is_share_code(text: &str) -> bool {
+    text.starts_with(&format!("{}.", SHARE_CODE_PREFIX))
+}
+
+/// Decodes text produced by [`encode_save`], [`encode_share_code`], or plain
+/// base64, checking that the result is plausibly a save.
+pub fn decode_save(profile: &GameProfile, text: &str) -> Result<DecodedSave, DecodeError> {
+    let text = text.trim();
+    if is_share_code(text) {
+        return decode_share_code(profile, text);
+    }
+    let (origin, encoded) = match text.split_once(':') {
+        Some((filename, encoded)) => {
+            let origin = profile
+                .parse_save_filename(filename)
+                .ok_or_else(|| DecodeError::UnknownFilename(filename.to_string()))?;
+            (Some(origin), encoded)
+        }
+        None => (None, text),
+    };
+
+    // Chat clients like to wrap long lines.
+    let encoded = encoded
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    if encoded.is_empty() {
+        return Err(DecodeError::Empty);
+    }
+    if encoded.len() / 4 * 3 > MAX_SAVE_SIZE {
+        return Err(DecodeError::TooLarge);
+    }
+    let contents = STANDARD
+        .decode(encoded)
+        .map_err(|error| DecodeError::NotBase64(error.to_string()))?;
+
+    if !looks_like_save(&contents) {
+        return Err(DecodeError::NotASave);
+    }
+
+    Ok(DecodedSave { origin, contents })
+}
+
+/// Whether `contents` are plausibly a save: GameMaker writes saves as lines
+/// of text.
+pub(crate) fn looks_like_save(contents: &[u8]) -> bool {
+    std::str::from_utf8(contents).is_ok_and(|text| text.contains('\n'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SyntheticSave;
+    use std::path::PathBuf;
+
+    #[test]
+    fn copied_saves_round_trip_with_their_slot() {
+        let profile = GameProfile::deltarune(PathBuf::new());
+        let contents = SyntheticSave::default().to_bytes();
+        let text = encode_save("filech1_2", &contents);
+
+        let decoded = decode_save(&profile, &text).unwrap();
+        assert_eq!(decoded.origin, Some((1, 2)));
+        assert_eq!(decoded.contents, contents);
+
+        let bare = STANDARD.encode(&contents);
+        let wrapped = format!("{}\n{}", &bare[..40], &bare[40..]);
+        assert_eq!(decode_save(&profile, &wrapped).unwrap().origin, None);
+    }
+
+    #[test]
+    fn share_codes_round_trip_and_catch_damage() {
+        let profile = GameProfile::deltarune(PathBuf::new());
+        let contents = SyntheticSave::default().to_bytes();
+        let code = encode_share_code("filech2_1", &contents).unwrap();
+
+        let wrapped = format!("{}\n{}", &code[..30], &code[30..]);
+        let decoded = decode_save(&profile, &wrapped).unwrap();
+        assert_eq!(decoded.origin, Some((2, 1)));
+        assert_eq!(decoded.contents, contents);
+
+        let (payload, checksum) = code.rsplit_once('.').unwrap();
+        let truncated = format!("{}.{}", &payload[..payload.len() - 4], checksum);
+        assert!(decode_share_code(&profile, &truncated).is_err());
+        let mistyped = format!("{}.{}", payload, "00000000");
+        assert_eq!(
+            decode_share_code(&profile, &mistyped),
+            Err(DecodeError::ChecksumMismatch)
+        );
+        assert_eq!(
+            decode_share_code(&profile, "ds1.filech2_1"),
+            Err(DecodeError::Malformed)
+        );
+    }
+
+    #[test]
+    fn share_codes_keep_dots_in_filenames() {
+        let profile = GameProfile {
+            filename_pattern: "slot{slot}.sav".to_string(),
+            ..GameProfile::deltarune(PathBuf::new())
+        };
+        let contents = SyntheticSave::default().to_bytes();
+        let code = encode_share_code("slot2.sav", &contents).unwrap();
+        let decoded = decode_share_code(&profile, &code).unwrap();
+        assert_eq!(decoded.origin, Some((1, 2)));
+        assert_eq!(decoded.contents, contents);
+    }
+
+    #[test]
+    fn share_codes_are_shorter_than_base64() {
+        let contents = SyntheticSave::default().to_bytes();
+        let code = encode_share_code("filech1_2", &contents).unwrap();
+        assert!(code.starts_with("ds1.filech1_2."));
+        assert!(code.len() < encode_save("filech1_2", &contents).len());
+
+        let parts = code.split('.').collect::<Vec<_>>();
+        let compressed = URL_SAFE_NO_PAD.decode(parts[2]).unwrap();
+        assert_eq!(parts[3], share_code_checksum(&compressed));
+        assert_eq!(
+            zstd::bulk::decompress(&compressed, MAX_SAVE_SIZE).unwrap(),
+            contents
+        );
+    }
+
+    #[test]
+    fn rejects_things_that_are_not_saves() {
+        let profile = GameProfile::deltarune(PathBuf::new());
+        assert_eq!(decode_save(&profile, "  "), Err(DecodeError::Empty));
+        assert!(matches!(
+            decode_save(&profile, "not base64!"),
+            Err(DecodeError::NotBase64(_))
+        ));
+        assert_eq!(
+            decode_save(&profile, &STANDARD.encode([0xff, 0x00, 0x10])),
+            Err(DecodeError::NotASave)
+        );
+        assert_eq!(
+            decode_save(&profile, "dr.ini:MTIz"),
+            Err(DecodeError::UnknownFilename("dr.ini".to_string()))
+        );
+    }
+}
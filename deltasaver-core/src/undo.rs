@@ -0,0 +1,124 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! Undoing the last restore, in case it was a misclick.
+//!
+//! Every restore through [`crate::conflict::restore_watched`] first backs up
+//! what was in the slot, and the last one is remembered in
+//! `last_restore.json` in the backup directory, so it can be undone even
+//! after the app is closed. Retention never prunes that backup.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::conflict;
+use crate::save::{Chapter, Slot};
+use crate::store::SaveStore;
+
+const LAST_RESTORE_FILENAME: &str = "last_restore.json";
+
+/// The last restore made.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastRestore {
+    pub chapter: Chapter,
+    pub slot: Slot,
+    /// The filename of the backup of what was in the slot before, in the
+    /// backup directory, or `None` if the slot was empty.
+    pub pre_restore: Option<String>,
+}
+
+/// Remembers a restore into `chapter`'s `slot`, which held what
+/// `pre_restore` backed up, if anything.
+pub fn remember(
+    store: &SaveStore,
+    chapter: Chapter,
+    slot: Slot,
+    pre_restore: Option<&Path>,
+) -> io::Result<()> {
+    let last_restore = LastRestore {
+        chapter,
+        slot,
+        pre_restore: pre_restore
+            .and_then(|path| path.file_name())
+            .map(|filename| filename.to_string_lossy().into_owned()),
+    };
+    let contents = serde_json::to_vec_pretty(&last_restore).map_err(io::Error::other)?;
+    fs::write(
+        store.local_directory().join(LAST_RESTORE_FILENAME),
+        contents,
+    )
+}
+
+/// The last restore made, if it has not been undone.
+pub fn last_restore(store: &SaveStore) -> io::Result<Option<LastRestore>> {
+    match fs::read(store.local_directory().join(LAST_RESTORE_FILENAME)) {
+        Ok(contents) => serde_json::from_slice(&contents)
+            .map(Some)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Puts back what the slot held before the last restore, returning that
+/// restore. Whatever the slot holds now is backed up first, so undoing can be
+/// undone in turn.
+pub fn undo(store: &SaveStore) -> io::Result<LastRestore> {
+    let last = last_restore(store)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no restore to undo"))?;
+    match &last.pre_restore {
+        Some(filename) => {
+            conflict::restore_watched(
+                store,
+                store.local_directory().join(filename),
+                last.chapter,
+                last.slot,
+            )?;
+        }
+        None => {
+            store.check_not_frozen(last.chapter, last.slot)?;
+            let game_path = store
+                .game_directory()
+                .join(store.profile().save_filename(last.chapter, last.slot));
+            let now = store.backup(last.chapter, last.slot)?;
+            fs::remove_file(game_path)?;
+            remember(store, last.chapter, last.slot, Some(&now))?;
+        }
+    }
+    Ok(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+
+    #[test]
+    fn undoing_a_restore_puts_back_what_was_there() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let game_path = store.game_directory().join("filech1_0");
+        assert_eq!(last_restore(&store).unwrap(), None);
+
+        let old = SyntheticSave::default().to_bytes();
+        let backup = store.import(1, 0, &old).unwrap();
+        let progress = SyntheticSave {
+            gold: 300,
+            ..Default::default()
+        }
+        .to_bytes();
+        fs::write(&game_path, &progress).unwrap();
+
+        conflict::restore_watched(&store, backup.clone(), 1, 0).unwrap();
+        assert_eq!(fs::read(&game_path).unwrap(), old);
+        undo(&store).unwrap();
+        assert_eq!(fs::read(&game_path).unwrap(), progress);
+
+        fs::remove_file(&game_path).unwrap();
+        conflict::restore_watched(&store, backup, 1, 0).unwrap();
+        assert_eq!(last_restore(&store).unwrap().unwrap().pre_restore, None);
+        undo(&store).unwrap();
+        assert!(!game_path.exists());
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+//! A read-mostly view of a vault, meaning a game's save slots together with
+//! DELTASAVER's backups of them, for tools other than DELTASAVER itself, such
+//! as dashboards.
+//!
+//! Unlike the rest of this crate, this module is a stable interface: it only
+//! changes in backwards-compatible ways within a major version. Its structs
+//! and enums are `#[non_exhaustive]` so fields and variants can be added, and
+//! it does not expose how backups are named or laid out on disk, so tools
+//! built on it keep working when that changes.
+//!
+//! ```no_run
+//! use deltasaver_core::vault::Vault;
+//!
+//! let vault = Vault::open(None)?;
+//! for save in vault.list_saves()? {
+//!     println!("Chapter {} Slot {}: {:?}", save.chapter, save.slot + 1, save.backup_id);
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io;
+use std::time::{Duration, SystemTime};
+
+use crate::config;
+use crate::profile::{self, backup_created_secs, backup_id};
+use crate::save::{Chapter, SaveFile, Slot};
+use crate::store::SaveStore;
+use crate::watch::SaveWatcher;
+
+/// A save in a vault: either a game slot as it is now, or a backup of one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Save {
+    pub chapter: Chapter,
+    /// Counting from 0, so the game's Slot 1 is 0.
+    pub slot: Slot,
+    /// The backup's ID, or `None` for a game slot.
+    pub backup_id: Option<String>,
+    /// The SHA-256 of the contents in lowercase hex, if known.
+    pub hash: Option<String>,
+    /// When the backup was taken, or when the game last wrote the slot.
+    pub time: Option<SystemTime>,
+    /// In bytes.
+    pub size: Option<u64>,
+}
+
+impl Save {
+    fn from_save_file(save: &SaveFile) -> Self {
+        let filename = save.id().filter(|_| save.is_local);
+        let backup_id = filename.and_then(backup_id).map(str::to_string);
+        let created = filename
+            .and_then(backup_created_secs)
+            .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        Self {
+            chapter: save.chapter,
+            slot: save.slot,
+            time: created.or(save.modified),
+            backup_id,
+            hash: save.hash.clone(),
+            size: save.size,
+        }
+    }
+}
+
+/// Something that happened in a vault, from [`Vault::subscribe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// The game wrote a slot.
+    SlotWritten { chapter: Chapter, slot: Slot },
+}
+
+/// A game's saves and their backups, found as the DELTASAVER app would find
+/// them.
+#[derive(Debug, Clone)]
+pub struct Vault {
+    store: SaveStore,
+}
+
+impl Vault {
+    /// The vault of the profile named `profile_name`, or DELTARUNE's if
+    /// `None`, honoring the user's `config.toml`.
+    pub fn open(profile_name: Option<&str>) -> io::Result<Self> {
+        let not_found = |message: String| io::Error::new(io::ErrorKind::NotFound, message);
+        let store = match profile_name {
+            Some(name) => profile::find_profile(name)?
+                .ok_or_else(|| not_found(format!("no profile named {}", name)))
+                .and_then(|profile| {
+                    SaveStore::for_profile(profile)
+                        .ok_or_else(|| not_found("no local data directory".to_string()))
+                })?,
+            None => SaveStore::at_default_location()
+                .ok_or_else(|| not_found("no DELTARUNE save directory".to_string()))?,
+        };
+        let config = config::load_config()?;
+        Ok(Self::from_store(
+            config
+                .apply_directories(&store)
+                .with_skip_identical(config.backups.skip_identical),
+        ))
+    }
+
+    pub fn from_store(store: SaveStore) -> Self {
+        Self { store }
+    }
+
+    /// This vault, able to read its backups if they are encrypted with
+    /// `passphrase`. Fails with `PermissionDenied` if it is wrong.
+    pub fn unlock(&self, passphrase: &str) -> io::Result<Self> {
+        Ok(Self::from_store(self.store.unlock(passphrase)?))
+    }
+
+    /// Every occupied game slot, then every backup, each in order of chapter
+    /// and slot, with backups oldest first.
+    pub fn list_saves(&self) -> io::Result<Vec<Save>> {
+        let saves = self
+            .store
+            .load()
+            .map_err(|error| io::Error::other(error.to_string()))?;
+        let mut game = saves
+            .game
+            .values()
+            .map(Save::from_save_file)
+            .collect::<Vec<_>>();
+        game.sort_by_key(|save| (save.chapter, save.slot));
+        let mut backups = saves
+            .local
+            .iter()
+            .map(Save::from_save_file)
+            .collect::<Vec<_>>();
+        backups.sort_by(|a, b| {
+            (a.chapter, a.slot, a.time, &a.backup_id).cmp(&(
+                b.chapter,
+                b.slot,
+                b.time,
+                &b.backup_id,
+            ))
+        });
+        game.extend(backups);
+        Ok(game)
+    }
+
+    /// The backup with the given ID, if there is one.
+    pub fn get_backup(&self, id: &str) -> io::Result<Option<Save>> {
+        Ok(self.find_backup(id)?.as_ref().map(Save::from_save_file))
+    }
+
+    /// The contents of the backup with the given ID.
+    pub fn read_backup(&self, id: &str) -> io::Result<Vec<u8>> {
+        self.store.read_backup(&self.require_backup(id)?.path)
+    }
+
+    /// Restores the backup with the given ID into the slot it was taken
+    /// from, checking that it arrived intact.
+    pub fn restore(&self, id: &str) -> io::Result<()> {
+        let backup = self.require_backup(id)?;
+        self.store
+            .restore(&backup.path, backup.chapter, backup.slot)
+    }
+
+    /// Watches the game's save directory, returning events as they happen.
+    /// Iterating blocks until the next one.
+    pub fn subscribe(&self) -> io::Result<Events> {
+        let watcher = SaveWatcher::new(self.store.profile().clone()).map_err(io::Error::other)?;
+        Ok(Events {
+            watcher,
+            pending: Vec::new(),
+        })
+    }
+
+    fn find_backup(&self, id: &str) -> io::Result<Option<SaveFile>> {
+        let saves = self
+            .store
+            .load()
+            .map_err(|error| io::Error::other(error.to_string()))?;
+        Ok(saves
+            .local
+            .into_iter()
+            .find(|save| save.id().and_then(backup_id) == Some(id)))
+    }
+
+    fn require_backup(&self, id: &str) -> io::Result<SaveFile> {
+        self.find_backup(id)?.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("no backup with id {}", id))
+        })
+    }
+}
+
+/// The events of a vault, from [`Vault::subscribe`]. Ends if watching stops.
+pub struct Events {
+    watcher: SaveWatcher,
+    pending: Vec<Event>,
+}
+
+impl Iterator for Events {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        if self.pending.is_empty() {
+            self.pending = self
+                .watcher
+                .next_changes()?
+                .into_iter()
+                .rev()
+                .map(|(chapter, slot)| Event::SlotWritten { chapter, slot })
+                .collect();
+        }
+        self.pending.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{SyntheticSave, store_in};
+    use std::fs;
+
+    #[test]
+    fn saves_are_listed_fetched_and_restored_by_id() {
+        let root = tempfile::tempdir().unwrap();
+        let store = store_in(root.path()).unwrap();
+        let original = SyntheticSave::default();
+        original.write_to(store.game_directory(), 0).unwrap();
+        store.backup(1, 0).unwrap();
+        SyntheticSave {
+            gold: 9,
+            ..Default::default()
+        }
+        .write_to(store.game_directory(), 0)
+        .unwrap();
+
+        let vault = Vault::from_store(store.clone());
+        let saves = vault.list_saves().unwrap();
+        assert_eq!(saves.len(), 2);
+        assert_eq!(saves[0].backup_id, None);
+        let id = saves[1].backup_id.clone().unwrap();
+        assert!(saves[1].time.is_some());
+
+        assert_eq!(vault.get_backup(&id).unwrap(), Some(saves[1].clone()));
+        assert_eq!(vault.get_backup("missing").unwrap(), None);
+        assert_eq!(vault.read_backup(&id).unwrap(), original.to_bytes());
+        vault.restore(&id).unwrap();
+        assert_eq!(
+            fs::read(store.game_directory().join("filech1_0")).unwrap(),
+            original.to_bytes()
+        );
+        assert_eq!(
+            vault.restore("missing").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
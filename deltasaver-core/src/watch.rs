@@ -0,0 +1,69 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use crate::profile::GameProfile;
+use crate::save::{Chapter, Slot};
+
+/// The game writes a save in several steps, so we wait for the directory to
+/// settle before reporting a change.
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Watches a game's save directory for slots being written.
+pub struct SaveWatcher {
+    // Dropping the watcher stops the events.
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    profile: GameProfile,
+}
+
+impl SaveWatcher {
+    pub fn new(profile: GameProfile) -> notify::Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        watcher.watch(&profile.save_directory, RecursiveMode::NonRecursive)?;
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            profile,
+        })
+    }
+
+    /// Blocks until at least one save slot has been written and the
+    /// directory has settled, then returns every slot written in the
+    /// meantime. Returns `None` once the watcher has shut down.
+    pub fn next_changes(&self) -> Option<BTreeSet<(Chapter, Slot)>> {
+        let mut changes = BTreeSet::new();
+        while changes.is_empty() {
+            changes.extend(self.changed_slots(self.events.recv().ok()?));
+        }
+        loop {
+            match self.events.recv_timeout(DEBOUNCE) {
+                Ok(event) => changes.extend(self.changed_slots(event)),
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => {
+                    return Some(changes);
+                }
+            }
+        }
+    }
+
+    fn changed_slots(&self, event: notify::Result<Event>) -> Vec<(Chapter, Slot)> {
+        let Ok(event) = event else {
+            return Vec::new();
+        };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            return Vec::new();
+        }
+        event
+            .paths
+            .iter()
+            .filter_map(|path| {
+                self.profile
+                    .parse_save_filename(path.file_name()?.to_str()?)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+use deltasaver_core::SaveStore;
+use deltasaver_core::config::RetentionPolicy;
+use deltasaver_core::retention;
+use deltasaver_core::watch::SaveWatcher;
+use std::io;
+
+/// Backs up every slot the game writes until the process is killed. The
+/// backups go into the same directory the app reads, so the app can stay
+/// open alongside the daemon and will pick them up on refresh. Old backups are
+/// pruned after each batch, following `retention`.
+pub fn run(store: SaveStore, retention: RetentionPolicy) -> io::Result<()> {
+    store.ensure_local_directory()?;
+
+    let watcher = SaveWatcher::new(store.profile().clone()).map_err(io::Error::other)?;
+    println!("Watching {}", store.game_directory().display());
+
+    while let Some(changes) = watcher.next_changes() {
+        for (chapter, slot) in changes {
+            match store.backup(chapter, slot) {
+                Ok(path) => println!(
+                    "Backed up Chapter {} Slot {} to {}",
+                    chapter,
+                    slot + 1,
+                    path.display()
+                ),
+                Err(error) => eprintln!(
+                    "error: could not back up Chapter {} Slot {}: {}",
+                    chapter,
+                    slot + 1,
+                    error
+                ),
+            }
+        }
+        match retention::prune(&store, &retention) {
+            Ok(pruned) => {
+                for save in pruned {
+                    println!("Pruned {}", save.path.display());
+                }
+            }
+            Err(error) => eprintln!("error: could not prune old backups: {}", error),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+use iced::Font;
+use iced::widget::{Text, text};
+use std::sync::LazyLock;
+
+pub const PRIMARY_BYTES: &[u8] = include_bytes!("../fonts/DTM-Mono.otf");
+
+pub const PRIMARY: Font = Font::with_name("Determination Mono");
+
+/// Used when [`PRIMARY`] lacks a glyph. This is the generic sans-serif family,
+/// which the text shaper resolves to a system font and from there falls back
+/// per script through the rest of the system's fonts.
+const FALLBACK: Font = Font::DEFAULT;
+
+static PRIMARY_FACE: LazyLock<Option<ttf_parser::Face<'static>>> =
+    LazyLock::new(|| ttf_parser::Face::parse(PRIMARY_BYTES, 0).ok());
+
+fn primary_covers(content: &str) -> bool {
+    PRIMARY_FACE.as_ref().is_some_and(|face| {
+        content
+            .chars()
+            .all(|c| c.is_whitespace() || face.glyph_index(c).is_some())
+    })
+}
+
+/// The font to draw `content` in. Determination Mono only covers Latin text,
+/// so things like non-Latin usernames in paths would otherwise render as
+/// nothing.
+pub fn font_for(content: &str) -> Font {
+    if primary_covers(content) {
+        PRIMARY
+    } else {
+        FALLBACK
+    }
+}
+
+/// Text that falls back to a system font when it contains glyphs the primary
+/// font lacks. Use this for anything that comes from outside the app.
+pub fn label<'a>(content: impl Into<String>) -> Text<'a> {
+    let content = content.into();
+    let font = font_for(&content);
+    text(content).font(font)
+}
@@ -0,0 +1,35 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+use iced::widget::{Svg, svg};
+use iced::{Color, Length};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Icon {
+    Backup,
+    Restore,
+    Delete,
+    Export,
+    Handoff,
+    Pin,
+}
+
+impl Icon {
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            Icon::Backup => include_bytes!("../icons/backup.svg"),
+            Icon::Restore => include_bytes!("../icons/restore.svg"),
+            Icon::Delete => include_bytes!("../icons/delete.svg"),
+            Icon::Export => include_bytes!("../icons/export.svg"),
+            Icon::Handoff => include_bytes!("../icons/handoff.svg"),
+            Icon::Pin => include_bytes!("../icons/pin.svg"),
+        }
+    }
+}
+
+/// A square icon `size` pixels wide, tinted `color`.
+pub fn icon<'a>(icon: Icon, size: f32, color: Color) -> Svg<'a> {
+    svg(svg::Handle::from_memory(icon.bytes()))
+        .width(Length::Fixed(size))
+        .height(Length::Fixed(size))
+        .style(move |_, _| svg::Style { color: Some(color) })
+}
@@ -1,32 +1,185 @@
 // Copyright (C) 2025 Ethan Uppal. All rights reserved.
 
+use iced::futures::{SinkExt, Stream};
 use iced::widget::{
-    button, column, container, horizontal_space, row, scrollable, text, vertical_space,
+    Button, Column, button, checkbox, column, container, horizontal_space, pick_list, progress_bar,
+    row, scrollable, slider, text, text_input, vertical_space,
 };
-use iced::{Background, Border, Center, Color, Element, Fill, Font, Length, Task, Theme};
+use iced::{Background, Border, Center, Color, Element, Fill, Length, Subscription, Task, Theme};
 
-use sha2::{Digest, Sha256};
-use std::collections::HashMap;
-use std::fs;
-use std::io;
-use std::path::PathBuf;
-use std::time::SystemTime;
+use clap::Parser;
+use deltasaver_core::branch::{self, Branches};
+use deltasaver_core::compare::{self, Change};
+use deltasaver_core::config::{
+    self, BackupOrder, BackupPolicy, Config, DirectoryArgs, ExportHook, RetentionPolicy,
+    SchedulePolicy,
+};
+use deltasaver_core::conflict::{self, Choice, Conflict, WatchedRestore};
+use deltasaver_core::dump;
+use deltasaver_core::duplicates::{self, DuplicateSet};
+use deltasaver_core::edit;
+use deltasaver_core::export::{self, ExportError};
+use deltasaver_core::format;
+use deltasaver_core::freeze::{self, Freezes};
+use deltasaver_core::history;
+use deltasaver_core::import::{self, ImportError, ImportSummary};
+use deltasaver_core::index;
+use deltasaver_core::json;
+use deltasaver_core::rooms::RoomNames;
+use deltasaver_core::schedule::{self, Scheduler};
+use deltasaver_core::search;
+use deltasaver_core::snapshot::{self, Snapshot};
+use deltasaver_core::store::Damaged;
+use deltasaver_core::summary::{Route, SaveSummary};
+use deltasaver_core::undo::{self, LastRestore};
+use deltasaver_core::watch::SaveWatcher;
+use deltasaver_core::{Chapter, GameUpdate, LoadError, Locale, SaveFile, SaveStore, Saves, Slot};
+use deltasaver_core::{GameProfile, hook, power, profile, retention, transfer};
+use deltasaver_core::{encryption, git};
+use icons::Icon;
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::fmt;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime};
+
+mod daemon;
+mod fonts;
+mod icons;
+mod tui;
+
+/// Manage DELTARUNE saves.
+#[derive(Parser)]
+#[command(version)]
+struct Args {
+    /// Run in the terminal instead of opening a window.
+    #[arg(long, conflicts_with = "daemon")]
+    tui: bool,
+
+    /// Run without a window, backing up every slot the game writes.
+    #[arg(long)]
+    daemon: bool,
+
+    /// Format dates and sizes for this locale, e.g. `en-GB`, instead of the
+    /// system one.
+    #[arg(long, value_name = "TAG")]
+    locale: Option<String>,
+
+    /// Manage another game's saves, e.g. `UNDERTALE` or one from
+    /// `profiles.json`, instead of DELTARUNE's.
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Pause background checks while running on battery, resuming once
+    /// plugged back in.
+    #[arg(long)]
+    low_power: bool,
 
-#[cfg(target_os = "linux")]
-compile_error!("Linux is not supported in this context.");
+    #[command(flatten)]
+    directories: DirectoryArgs,
+}
 
 pub fn main() -> iced::Result {
+    let args = Args::parse();
+    let mut config = match config::load_config() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("error: could not read config: {}", error);
+            process::exit(1);
+        }
+    };
+    let locale = args
+        .locale
+        .as_deref()
+        .or(config.locale.as_deref())
+        .map(Locale::from_tag)
+        .unwrap_or_else(Locale::current);
+
+    let store = open_store(args.profile.as_deref());
+    if let Err(error) = config::remember_directories(
+        &mut config,
+        &store.profile().name,
+        args.directories.saves_dir,
+        args.directories.backups_dir,
+    ) {
+        eprintln!("error: could not save config: {}", error);
+        process::exit(1);
+    }
+    let store = config
+        .apply_directories(&store)
+        .with_skip_identical(config.backups.skip_identical);
+    let store = match env::var(encryption::PASSPHRASE_VARIABLE) {
+        Ok(passphrase) if store.is_locked() => match store.unlock(&passphrase) {
+            Ok(store) => store,
+            Err(error) => {
+                eprintln!("error: could not unlock backups: {}", error);
+                process::exit(1);
+            }
+        },
+        _ => store,
+    };
+
+    if args.tui || args.daemon {
+        if store.is_locked() {
+            eprintln!(
+                "error: backups are encrypted; set {} to their passphrase",
+                encryption::PASSPHRASE_VARIABLE
+            );
+            process::exit(1);
+        }
+        let result = if args.tui {
+            tui::run(store, locale, config.retention)
+        } else {
+            daemon::run(store, config.retention)
+        };
+        if let Err(error) = result {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let theme = config
+        .theme
+        .as_deref()
+        .and_then(|name| Theme::ALL.iter().find(|theme| theme.to_string() == name))
+        .cloned()
+        .unwrap_or(Theme::Dark);
+    config.backups.pause_on_battery |= args.low_power;
+
     iced::application("DELTASAVER", Deltasaver::update, Deltasaver::view)
-        .theme(|_| Theme::Dark)
-        .font(include_bytes!("../fonts/DTM-Mono.otf").as_slice())
-        .default_font(Font::with_name("Determination Mono"))
-        .run_with(Deltasaver::new)
+        .subscription(Deltasaver::subscription)
+        .theme(move |_| theme.clone())
+        .font(fonts::PRIMARY_BYTES)
+        .default_font(fonts::PRIMARY)
+        .run_with(move || Deltasaver::new(store, locale, config))
 }
 
-// tricky tony better not pull a trick
-const CHAPTER_COUNT: Chapter = 7;
+fn open_store(profile_name: Option<&str>) -> SaveStore {
+    let Some(profile_name) = profile_name else {
+        return SaveStore::at_default_location()
+            .expect("You have no local storage directory. Are you sure you downloaded DELTARUNE?");
+    };
+    let profile = match profile::find_profile(profile_name) {
+        Ok(Some(profile)) => profile,
+        Ok(None) => {
+            eprintln!("error: no profile named {}", profile_name);
+            process::exit(1);
+        }
+        Err(error) => {
+            eprintln!("error: could not read profiles: {}", error);
+            process::exit(1);
+        }
+    };
+    SaveStore::for_profile(profile).expect("You have no local storage directory.")
+}
 
-const BUILTIN_SLOT_MAX_INDEX: Slot = 2;
+const GAME_UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const CONFLICT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const INTEGRITY_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
 
 const SPACING0_5: f32 = 0.5 * SPACING;
 const SPACING: f32 = 8.0;
@@ -36,149 +189,1996 @@ const SPACING2: f32 = 2.0 * SPACING;
 const TABLE_COLUMN_HEADER_SIZE: f32 = 24.0;
 const BUTTON_SIZE: f32 = 12.0;
 
+struct Deltasaver {
+    store: SaveStore,
+    game_saves: HashMap<(Chapter, Slot), SaveFile>,
+    /// Files the game keeps past its last slot, shown read-only.
+    extra_saves: HashMap<(Chapter, Slot), SaveFile>,
+    /// Chapters whose extra files are shown.
+    extra_files: BTreeSet<Chapter>,
+    local_saves: Vec<SaveFile>,
+    /// The names, notes, and tags of the local saves, by ID.
+    backup_entries: HashMap<String, index::Entry>,
+    /// Notes being typed, by local save path, until they are saved.
+    note_drafts: HashMap<PathBuf, String>,
+    /// Names being typed, by local save path, until they are saved.
+    name_drafts: HashMap<PathBuf, String>,
+    /// Whether the save editor is offered, see [`edit`].
+    save_editor: bool,
+    /// Edits being typed into the save editor, by local save path, as the
+    /// player name and dark dollars.
+    edit_drafts: HashMap<PathBuf, (String, String)>,
+    /// Tags being typed, by local save path, until they are added.
+    tag_drafts: HashMap<PathBuf, String>,
+    /// Only backups with this tag are listed, if one is picked.
+    tag_filter: TagFilter,
+    /// How the backups of each slot are listed.
+    backup_order: BackupOrder,
+    /// The note to attach to the next backup made from the game saves column.
+    backup_note: String,
+    /// Local saves ticked for exporting or deleting together.
+    selected_backups: BTreeSet<PathBuf>,
+    /// Local saves whose details, such as their party, are shown.
+    expanded_backups: BTreeSet<PathBuf>,
+    /// The comparison of the selected backups, once made.
+    comparison: Option<Comparison>,
+    /// The slot whose backups are shown in order, oldest first, and the
+    /// position of the one scrubbed to.
+    timeline: Option<(Chapter, Slot, usize)>,
+    flag_browser: Option<FlagBrowser>,
+    contents_viewer: Option<ContentsViewer>,
+    /// Whether the user has asked to delete the selected backups and is yet
+    /// to confirm.
+    confirming_bulk_delete: bool,
+    /// Files dropped onto the window whose slot the user has yet to say.
+    dropped: Vec<PathBuf>,
+    /// The chapter the first of them is for.
+    dropped_chapter: Chapter,
+    loading: bool,
+    status: Option<String>,
+    /// Filters the local saves, see [`search`].
+    search_query: String,
+    /// A share code being pasted in, see [`transfer::decode_share_code`].
+    share_code: String,
+    locale: Locale,
+    demos: Vec<Demo>,
+    /// When to snapshot on game updates, and whether to wait for AC power.
+    backups: BackupPolicy,
+    /// Which old backups to delete after each backup.
+    retention: RetentionPolicy,
+    /// Which slots to back up on schedule, and how often.
+    schedule: SchedulePolicy,
+    /// When the next scheduled backup is, if scheduling is on.
+    scheduler: Option<Scheduler>,
+    /// Where to send each snapshot once it is taken.
+    export_hook: Option<ExportHook>,
+    /// Every installation of the game found, to pick between.
+    installations: Vec<Installation>,
+    /// Game slots the user has frozen, which we only label; the store
+    /// enforces them.
+    freezes: Freezes,
+    /// The last restore, while a cloud sync could still overwrite it.
+    watched_restore: Option<WatchedRestore>,
+    /// A restore that was overwritten, awaiting the user's choice.
+    conflict: Option<Conflict>,
+    /// A restore into another chapter, awaiting the user's go-ahead.
+    warned_restore: Option<WarnedRestore>,
+    /// The last restore, which can be undone.
+    last_restore: Option<LastRestore>,
+    /// What is wrong with each backup the last integrity check found
+    /// damaged, by local save path.
+    damaged: HashMap<PathBuf, String>,
+    /// What each save records about the game, by path, for the saves that
+    /// could be read.
+    summaries: HashMap<PathBuf, SaveSummary>,
+    /// Names for the rooms in [`Self::summaries`].
+    rooms: RoomNames,
+    checking_integrity: bool,
+    /// The migrations made to the backup directory, shown on request.
+    vault_history: Vec<history::Entry>,
+    show_vault_history: bool,
+    /// Whether the game updated while snapshots were off, until the user
+    /// skips the snapshot.
+    unsnapshotted_update: bool,
+    /// Typed to unlock encrypted backups, or to encrypt them.
+    passphrase: String,
+    /// Whole-playthrough snapshots, newest first, shown on request.
+    snapshots: Vec<Snapshot>,
+    show_snapshots: bool,
+    snapshot_name: String,
+    /// Deleted backups, shown on request.
+    trash: Vec<SaveFile>,
+    show_trash: bool,
+    /// Sets of backups with the same contents, shown on request.
+    show_duplicates: bool,
+    /// Which playthrough each backup belongs to. Only the active one's
+    /// backups are listed.
+    branches: Branches,
+    show_branches: bool,
+    branch_name: String,
+}
+
+/// A save directory of the game, e.g. of its Steam or itch.io build.
+#[derive(Debug, Clone, PartialEq)]
+struct Installation(PathBuf);
+
+impl fmt::Display for Installation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.display())
+    }
+}
+
+/// Which backups the local saves column lists, by tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TagFilter {
+    All,
+    Tag(String),
+}
+
+impl TagFilter {
+    fn matches(&self, entry: Option<&index::Entry>) -> bool {
+        match self {
+            TagFilter::All => true,
+            TagFilter::Tag(tag) => entry.is_some_and(|entry| entry.tags.contains(tag)),
+        }
+    }
+}
+
+impl fmt::Display for TagFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagFilter::All => f.write_str("All tags"),
+            TagFilter::Tag(tag) => f.write_str(tag),
+        }
+    }
+}
+
+/// A slot to restore a backup into other than the one it was taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RestoreTarget {
+    chapter: Chapter,
+    slot: Slot,
+    /// Whether `chapter` is not the one the backup was taken in.
+    other_chapter: bool,
+}
+
+impl fmt::Display for RestoreTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.other_chapter {
+            write!(f, "Chapter {} Slot {}", self.chapter, self.slot + 1)
+        } else {
+            write!(f, "Slot {}", self.slot + 1)
+        }
+    }
+}
+
+/// A restore held until the user accepts the warning about it, as when the
+/// backup looks corrupted or is from another chapter.
 #[derive(Debug, Clone)]
-struct SaveFile {
-    path: PathBuf,
-    chapter: u8,
-    slot: u8,
-    hash: Option<String>,
-    modified: Option<SystemTime>,
-    is_local: bool,
-}
-
-impl SaveFile {
-    fn display_name(&self) -> String {
-        if self.is_local {
-            format!(
-                "Chapter {}, Slot {} ({})",
-                self.chapter,
-                self.slot + 1,
-                self.hash.as_ref().map(|h| &h[..8]).unwrap_or("local")
-            )
+struct WarnedRestore {
+    backup_path: PathBuf,
+    target: RestoreTarget,
+    warning: String,
+}
+
+/// What changed between two saves, see [`compare`].
+#[derive(Debug, Clone)]
+struct Comparison {
+    before: String,
+    after: String,
+    changes: Vec<Change>,
+}
+
+/// Every flag of a save, listed for those who need the raw values.
+#[derive(Debug, Clone)]
+struct FlagBrowser {
+    title: String,
+    flags: Vec<String>,
+    /// A flag index, or the start of one, or words of a flag's meaning.
+    query: String,
+}
+
+/// At most this many flags are listed at once, so the view stays quick.
+const MAX_LISTED_FLAGS: usize = 200;
+
+/// The raw contents of a save, shown a page at a time as lines or as hex.
+#[derive(Debug, Clone)]
+struct ContentsViewer {
+    title: String,
+    lines: Vec<String>,
+    hex: Vec<String>,
+    show_hex: bool,
+    page: usize,
+}
+
+impl ContentsViewer {
+    fn rows(&self) -> &[String] {
+        if self.show_hex {
+            &self.hex
         } else {
-            format!("Chapter {}, Slot {}", self.chapter, self.slot + 1)
+            &self.lines
         }
     }
+
+    fn page_count(&self) -> usize {
+        self.rows().len().div_ceil(CONTENTS_PAGE_ROWS).max(1)
+    }
 }
 
-type Chapter = u8;
-type Slot = u8;
+/// How many lines or hex rows the contents viewer shows per page.
+const CONTENTS_PAGE_ROWS: usize = 200;
 
-struct Deltasaver {
-    deltarune_saves_directory: PathBuf,
-    local_saves_directory: PathBuf,
-    game_saves: HashMap<(Chapter, Slot), SaveFile>,
-    local_saves: Vec<SaveFile>,
-    loading: bool,
+/// A DELTARUNE demo found alongside the full game, whose saves are shown in a
+/// section of their own and backed up into a directory of their own.
+struct Demo {
+    store: SaveStore,
+    saves: Saves,
 }
 
 #[derive(Debug, Clone)]
 enum Message {
-    SavesLoaded(Result<(HashMap<(Chapter, Slot), SaveFile>, Vec<SaveFile>), LoadError>),
+    SavesLoaded(Result<Saves, LoadError>),
     RefreshSaves,
     BackupSave(Chapter, Slot),
+    /// chapter, slot, why the save looked corrupted if it did
+    BackedUp(Chapter, Slot, Option<String>, Result<PathBuf, String>),
+    BackupAllSlots,
+    /// how many slots there were, and the failures
+    BackedUpAll(usize, Vec<String>),
+    BackupNoteChanged(String),
+    /// local save path, where to restore it
+    RestoreTo(PathBuf, RestoreTarget),
+    /// whether the user accepted the warning
+    WarnedRestoreConfirmed(bool),
+    CheckIntegrity,
+    IntegrityChecked(Result<Vec<Damaged>, String>),
     /// local save path, target chapter, slot
     RestoreSave(PathBuf, Chapter, Slot),
+    RestoreFinished(Result<(), String>),
+    UndoRestore,
+    RestoreUndone(Result<LastRestore, String>),
+    RestoreWatched(Result<WatchedRestore, String>),
+    CheckRestoreConflict,
+    RestoreConflictChecked(Result<Option<Conflict>, String>),
+    ConflictChoiceMade(Choice),
+    ConflictResolved(Choice, Result<(), String>),
     DeleteLocalSave(PathBuf),
-}
-
-#[derive(Debug, Clone)]
-enum LoadError {
-    IoError(()),
+    /// local save path, the note typed so far
+    NoteChanged(PathBuf, String),
+    /// local save path
+    SaveNote(PathBuf),
+    /// local save path, the name typed so far
+    NameChanged(PathBuf, String),
+    /// local save path
+    SaveName(PathBuf),
+    /// local save path, the tag typed so far
+    TagChanged(PathBuf, String),
+    /// local save path
+    AddTag(PathBuf),
+    /// local save path, tag
+    RemoveTag(PathBuf, String),
+    TagFilterSelected(TagFilter),
+    BackupOrderSelected(BackupOrder),
+    /// local save path, whether it is now pinned
+    PinToggled(PathBuf, bool),
+    DeleteFinished(Result<(), String>),
+    TrashToggled(bool),
+    DuplicatesToggled(bool),
+    /// the sets to keep one of each
+    CollapseDuplicates(Vec<DuplicateSet>),
+    /// how many backups were moved to the trash
+    DuplicatesCollapsed(Result<duplicates::Collapsed, String>),
+    BranchesToggled(bool),
+    BranchNameChanged(String),
+    /// makes the named branch active, starting it if it is new
+    SwitchBranch(String),
+    RestoreBranchTip(String),
+    /// what to tell the user
+    BranchFinished(Result<String, String>),
+    /// trashed backup path
+    RestoreFromTrash(PathBuf),
+    /// trashed backup path
+    PurgeFromTrash(PathBuf),
+    EmptyTrash,
+    /// what to tell the user
+    TrashFinished(Result<String, String>),
+    ExportChapterHistory(Chapter),
+    /// local save path, whether it is now selected
+    BackupSelected(PathBuf, bool),
+    /// local save path, whether its details are now shown
+    BackupExpanded(PathBuf, bool),
+    ClearSelection,
+    ExportSelected,
+    DeleteSelected,
+    /// Compares the two selected backups, or the one selected with the game
+    /// save of its slot.
+    CompareSelected,
+    Compared(Result<Comparison, String>),
+    CloseComparison,
+    ShowTimeline(Chapter, Slot),
+    /// position in the timeline
+    TimelineScrubbed(usize),
+    CloseTimeline,
+    /// local save path
+    BrowseFlags(PathBuf),
+    FlagsRead(Result<FlagBrowser, String>),
+    FlagQueryChanged(String),
+    CloseFlags,
+    /// a game save or local backup
+    ViewContents(SaveFile),
+    ContentsRead(Result<ContentsViewer, String>),
+    /// whether to show hex
+    ContentsHexToggled(bool),
+    /// page to show
+    ContentsPageChanged(usize),
+    CloseContents,
+    /// whether the user confirmed
+    DeleteSelectedConfirmed(bool),
+    /// what to tell the user
+    SelectedDeleted(Result<String, String>),
+    ArchiveExported(Result<Option<PathBuf>, ExportError>),
+    /// a game save or local backup
+    ExportFile(SaveFile),
+    /// a game save or local backup
+    ExportJson(SaveFile),
+    FileExported(Result<Option<PathBuf>, String>),
+    ImportArchive,
+    PushGitHistory,
+    /// the remote's URL
+    GitHistoryPushed(Result<String, String>),
+    /// a save file, directory of them, or archive dropped onto the window
+    FileDropped(PathBuf),
+    DropChapterSelected(Chapter),
+    /// imports the first dropped file into this slot of the selected chapter
+    ImportDropped(Slot),
+    SkipDropped,
+    ArchiveImported(Result<Option<ImportSummary>, ImportError>),
+    SearchQueryChanged(String),
+    ChapterHandoff(Chapter, Slot),
+    ChapterHandoffFinished(Result<PathBuf, String>),
+    CheckGameUpdate,
+    GameUpdateChecked(Result<Option<GameUpdate>, String>),
+    SkipGameUpdateSnapshot,
+    GameUpdateSnapshotSkipped(Result<(), String>),
+    SnapshotExported(Result<PathBuf, String>),
+    /// demo index, saves
+    DemoSavesLoaded(usize, Result<Saves, LoadError>),
+    /// demo index, chapter, slot
+    BackupDemoSave(usize, Chapter, Slot),
+    /// demo index, local save path, target chapter, slot
+    RestoreDemoSave(usize, PathBuf, Chapter, Slot),
+    ImportFromClipboard(Chapter),
+    ClipboardRead(Chapter, Option<String>),
+    Imported(Result<PathBuf, String>),
+    /// local save path, chapter, slot
+    CopyAsBase64(PathBuf, Chapter, Slot),
+    Base64Encoded(Result<String, String>),
+    /// local save path, chapter, slot
+    CopyShareCode(PathBuf, Chapter, Slot),
+    ShareCodeChanged(String),
+    ImportShareCode,
+    ShareCodeEncoded(Result<String, String>),
+    InstallationSelected(Installation),
+    AutoBackupToggled(bool),
+    ExtraFilesToggled(Chapter, bool),
+    SkipIdenticalToggled(bool),
+    SaveEditorToggled(bool),
+    /// local save path, player name
+    EditNameChanged(PathBuf, String),
+    /// local save path, dark dollars
+    EditGoldChanged(PathBuf, String),
+    /// Writes the edits to the local save at the path as a new backup.
+    SaveEdits(PathBuf),
+    Edited(Result<PathBuf, String>),
+    VaultHistoryToggled(bool),
+    SnapshotsToggled(bool),
+    SnapshotNameChanged(String),
+    SnapshotEverything,
+    RestoreSnapshot(Snapshot),
+    /// what to tell the user
+    SnapshotFinished(Result<String, String>),
+    PassphraseChanged(String),
+    UnlockBackups,
+    EncryptBackups,
+    /// the store, unlocked, or why it could not be
+    BackupsUnlocked(Result<SaveStore, String>),
+    /// the store, encrypted and unlocked, or why it could not be encrypted
+    BackupsEncrypted(Result<SaveStore, String>),
+    /// slots the game wrote while auto-backup was on
+    SlotsWritten(Vec<(Chapter, Slot)>),
+    AutoBackupFailed(String),
+    AutoBackedUp(Vec<Result<PathBuf, String>>),
+    /// deletes the backups the retention policy no longer keeps
+    PruneBackups,
+    Pruned(Result<usize, String>),
+    ScheduleTick,
+    ScheduledSlotToggled(Chapter, Slot, bool),
 }
 
 impl Deltasaver {
-    fn new() -> (Self, Task<Message>) {
-        let app_data_directory = dirs::data_local_dir()
-            .expect("You have no local storage directory. Are you sure you downloaded DELTARUNE?");
+    fn new(store: SaveStore, locale: Locale, config: Config) -> (Self, Task<Message>) {
+        let _ = store.ensure_local_directory();
+        let export_hook = config.hook_for(&store.profile().name).cloned();
 
-        let deltarune_saves_directory = if cfg!(target_os = "windows") {
-            app_data_directory.join("DELTARUNE")
-        } else if cfg!(target_os = "macos") {
-            app_data_directory.join("com.tobyfox.deltarune")
+        let demos = if store.profile().name == profile::DELTARUNE_PROFILE_NAME {
+            profile::detected_deltarune_demos()
+                .into_iter()
+                .filter_map(SaveStore::for_profile)
+                .map(|store| {
+                    let _ = store.ensure_local_directory();
+                    Demo {
+                        store,
+                        saves: Saves::default(),
+                    }
+                })
+                .collect()
         } else {
-            unreachable!("Unsupported OS");
+            Vec::new()
         };
 
-        let local_saves_directory = app_data_directory.join("DELTASAVER");
+        let installations = store
+            .profile()
+            .installations()
+            .into_iter()
+            .map(Installation)
+            .collect();
 
-        if !local_saves_directory.exists() {
-            let _ = fs::create_dir_all(&local_saves_directory);
-        }
+        let scheduler = config
+            .schedule
+            .interval()
+            .map(|interval| Scheduler::new(interval, SystemTime::now()));
 
         let app = Self {
-            deltarune_saves_directory: deltarune_saves_directory.clone(),
-            local_saves_directory: local_saves_directory.clone(),
+            store,
             game_saves: HashMap::new(),
+            extra_saves: HashMap::new(),
+            extra_files: config.extra_files,
             local_saves: Vec::new(),
+            backup_entries: HashMap::new(),
+            note_drafts: HashMap::new(),
+            name_drafts: HashMap::new(),
+            save_editor: config.save_editor,
+            edit_drafts: HashMap::new(),
+            backup_note: String::new(),
+            tag_drafts: HashMap::new(),
+            tag_filter: TagFilter::All,
+            backup_order: config.backup_order,
+            selected_backups: BTreeSet::new(),
+            expanded_backups: BTreeSet::new(),
+            comparison: None,
+            timeline: None,
+            flag_browser: None,
+            contents_viewer: None,
+            confirming_bulk_delete: false,
+            dropped: Vec::new(),
+            dropped_chapter: 1,
             loading: true,
+            status: None,
+            search_query: String::new(),
+            share_code: String::new(),
+            locale,
+            demos,
+            backups: config.backups,
+            retention: config.retention,
+            schedule: config.schedule,
+            scheduler,
+            export_hook,
+            installations,
+            freezes: Freezes::default(),
+            watched_restore: None,
+            conflict: None,
+            warned_restore: None,
+            damaged: HashMap::new(),
+            summaries: HashMap::new(),
+            rooms: RoomNames::load_default().unwrap_or_else(|_| RoomNames::builtin()),
+            checking_integrity: false,
+            last_restore: None,
+            vault_history: Vec::new(),
+            unsnapshotted_update: false,
+            show_vault_history: false,
+            passphrase: String::new(),
+            snapshots: Vec::new(),
+            show_snapshots: false,
+            snapshot_name: String::new(),
+            trash: Vec::new(),
+            show_trash: false,
+            show_duplicates: false,
+            branches: Branches::default(),
+            show_branches: false,
+            branch_name: String::new(),
         };
 
         (
             app,
-            Task::perform(
-                load_saves(deltarune_saves_directory, local_saves_directory),
-                Message::SavesLoaded,
-            ),
+            Task::batch([
+                Task::done(Message::CheckGameUpdate),
+                Task::done(Message::RefreshSaves),
+            ]),
         )
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        let mut subscriptions = vec![
+            iced::time::every(GAME_UPDATE_CHECK_INTERVAL).map(|_| Message::CheckGameUpdate),
+            iced::time::every(INTEGRITY_CHECK_INTERVAL).map(|_| Message::CheckIntegrity),
+        ];
+        if self.scheduler.is_some() {
+            subscriptions
+                .push(iced::time::every(SCHEDULE_CHECK_INTERVAL).map(|_| Message::ScheduleTick));
+        }
+        if self.watched_restore.is_some() {
+            subscriptions.push(
+                iced::time::every(CONFLICT_CHECK_INTERVAL).map(|_| Message::CheckRestoreConflict),
+            );
+        }
+        if self.backups.auto_backup {
+            // Keyed by directory, so picking another installation watches that
+            // one.
+            let profile = self.store.profile().clone();
+            subscriptions.push(Subscription::run_with_id(
+                profile.save_directory.clone(),
+                watch_saves(profile),
+            ));
+        }
+        subscriptions.push(iced::event::listen_with(|event, _, _| match event {
+            iced::Event::Window(iced::window::Event::FileDropped(path)) => {
+                Some(Message::FileDropped(path))
+            }
+            _ => None,
+        }));
+        Subscription::batch(subscriptions)
+    }
+
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::SavesLoaded(result) => {
                 self.loading = false;
                 match result {
-                    Ok((game_saves, local_saves)) => {
-                        self.game_saves = game_saves;
-                        self.local_saves = local_saves;
+                    Ok(saves) => {
+                        self.game_saves = saves.game;
+                        self.extra_saves = saves.extra;
+                        self.local_saves = saves.local;
+                        let local_saves = &self.local_saves;
+                        self.selected_backups
+                            .retain(|path| local_saves.iter().any(|save| &save.path == path));
+                        self.expanded_backups
+                            .retain(|path| local_saves.iter().any(|save| &save.path == path));
+                        // Backups never change, but game saves do.
+                        self.summaries
+                            .retain(|path, _| local_saves.iter().any(|save| &save.path == path));
+                        for save in self.game_saves.values().chain(&self.local_saves) {
+                            if save.is_local && self.summaries.contains_key(&save.path) {
+                                continue;
+                            }
+                            if let Some(summary) = self
+                                .store
+                                .read_save(save)
+                                .ok()
+                                .and_then(|contents| SaveSummary::parse(&contents))
+                            {
+                                self.summaries.insert(save.path.clone(), summary);
+                            }
+                        }
+                        self.backup_entries = self
+                            .store
+                            .index()
+                            .and_then(|index| index.entries())
+                            .unwrap_or_default();
+                        if !self.tag_filters().contains(&self.tag_filter) {
+                            self.tag_filter = TagFilter::All;
+                        }
+                        self.freezes =
+                            Freezes::load(self.store.local_directory()).unwrap_or_default();
+                        self.vault_history = self.store.history().unwrap_or_default();
+                        self.last_restore = undo::last_restore(&self.store).unwrap_or_default();
+                        self.snapshots = snapshot::list(&self.store).unwrap_or_default();
+                        self.trash = self.store.trash().unwrap_or_default();
+                        self.branches =
+                            Branches::load(self.store.local_directory()).unwrap_or_default();
                     }
-                    Err(_) => {
-                        // Handle error - maybe show a message to user
+                    Err(error) => {
+                        self.status = Some(format!("Could not load saves: {}", error));
                     }
                 }
                 Task::none()
             }
+            Message::PassphraseChanged(passphrase) => {
+                self.passphrase = passphrase;
+                Task::none()
+            }
+            Message::UnlockBackups => {
+                let store = self.store.clone();
+                let passphrase = std::mem::take(&mut self.passphrase);
+                Task::perform(
+                    async move { store.unlock(&passphrase).map_err(|error| error.to_string()) },
+                    Message::BackupsUnlocked,
+                )
+            }
+            Message::EncryptBackups => {
+                if self.passphrase.is_empty() {
+                    self.status = Some("Enter a passphrase to encrypt backups with".to_string());
+                    return Task::none();
+                }
+                let store = self.store.clone();
+                let passphrase = std::mem::take(&mut self.passphrase);
+                Task::perform(
+                    async move {
+                        store
+                            .encrypt(&passphrase)
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::BackupsEncrypted,
+                )
+            }
+            Message::BackupsEncrypted(result) => match result {
+                Ok(store) => {
+                    self.store = store;
+                    self.status = Some(
+                        "Backup contents are encrypted. Their names, chapters, slots, and times \
+                         are not."
+                            .to_string(),
+                    );
+                    Task::done(Message::RefreshSaves)
+                }
+                Err(error) => {
+                    self.status = Some(format!("Could not encrypt backups: {}", error));
+                    Task::none()
+                }
+            },
+            Message::BackupsUnlocked(result) => match result {
+                Ok(store) => {
+                    self.store = store;
+                    self.status = None;
+                    Task::done(Message::RefreshSaves)
+                }
+                Err(error) => {
+                    self.status = Some(format!("Could not unlock backups: {}", error));
+                    Task::none()
+                }
+            },
+            Message::SnapshotsToggled(shown) => {
+                self.show_snapshots = shown;
+                Task::none()
+            }
+            Message::SnapshotNameChanged(name) => {
+                self.snapshot_name = name;
+                Task::none()
+            }
+            Message::SnapshotEverything => {
+                let store = self.store.clone();
+                let name = std::mem::take(&mut self.snapshot_name);
+                Task::perform(
+                    async move {
+                        snapshot::take(&store, &name)
+                            .map(|taken| format!("Snapshotted everything as {}", taken.name))
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::SnapshotFinished,
+                )
+            }
+            Message::RestoreSnapshot(taken) => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        snapshot::restore(&store, &taken)
+                            .map(|before| {
+                                format!(
+                                    "Restored {}; what was there is snapshotted as {}",
+                                    taken.name, before.name
+                                )
+                            })
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::SnapshotFinished,
+                )
+            }
+            Message::SnapshotFinished(result) => {
+                self.status = Some(match result {
+                    Ok(message) => message,
+                    Err(error) => format!("Snapshot failed: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::VaultHistoryToggled(shown) => {
+                self.show_vault_history = shown;
+                Task::none()
+            }
+            Message::AutoBackupToggled(enabled) => {
+                self.backups.auto_backup = enabled;
+                if let Err(error) = config::update_config(|config| {
+                    config.backups.auto_backup = enabled;
+                }) {
+                    self.status = Some(format!("Could not save config: {}", error));
+                }
+                Task::none()
+            }
+            Message::ExtraFilesToggled(chapter, shown) => {
+                if shown {
+                    self.extra_files.insert(chapter);
+                } else {
+                    self.extra_files.remove(&chapter);
+                }
+                if let Err(error) = config::update_config(|config| {
+                    if shown {
+                        config.extra_files.insert(chapter);
+                    } else {
+                        config.extra_files.remove(&chapter);
+                    }
+                }) {
+                    self.status = Some(format!("Could not save config: {}", error));
+                }
+                Task::none()
+            }
+            Message::SkipIdenticalToggled(enabled) => {
+                self.backups.skip_identical = enabled;
+                self.store = self.store.with_skip_identical(enabled);
+                if let Err(error) = config::update_config(|config| {
+                    config.backups.skip_identical = enabled;
+                }) {
+                    self.status = Some(format!("Could not save config: {}", error));
+                }
+                Task::none()
+            }
+            Message::SlotsWritten(slots) => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        slots
+                            .into_iter()
+                            .map(|(chapter, slot)| {
+                                store
+                                    .backup(chapter, slot)
+                                    .map_err(|error| error.to_string())
+                            })
+                            .collect()
+                    },
+                    Message::AutoBackedUp,
+                )
+            }
+            Message::ScheduleTick => {
+                let now = SystemTime::now();
+                let Some(scheduler) = &mut self.scheduler else {
+                    return Task::none();
+                };
+                if !scheduler.is_due(now) {
+                    return Task::none();
+                }
+                scheduler.advance(now);
+                let slots =
+                    schedule::scheduled_slots(&self.store, &self.game_saves, &self.schedule);
+                Task::done(Message::SlotsWritten(slots))
+            }
+            Message::ScheduledSlotToggled(chapter, slot, enabled) => {
+                let profile_name = self.store.profile().name.clone();
+                let save_filename = self.store.profile().save_filename(chapter, slot);
+                self.schedule
+                    .set_enabled_for(&profile_name, &save_filename, enabled);
+                if let Err(error) = config::update_config(|config| {
+                    config
+                        .schedule
+                        .set_enabled_for(&profile_name, &save_filename, enabled);
+                }) {
+                    self.status = Some(format!("Could not save config: {}", error));
+                }
+                Task::none()
+            }
+            Message::AutoBackupFailed(error) => {
+                self.status = Some(format!("Auto-backup stopped: {}", error));
+                Task::none()
+            }
+            Message::AutoBackedUp(results) => {
+                let failures = results
+                    .into_iter()
+                    .filter_map(Result::err)
+                    .collect::<Vec<_>>();
+                if !failures.is_empty() {
+                    self.status = Some(format!("Auto-backup failed: {}", failures.join("; ")));
+                }
+                Task::done(Message::PruneBackups)
+            }
+            Message::PruneBackups => {
+                let store = self.store.clone();
+                let policy = self.retention.clone();
+                Task::perform(
+                    async move {
+                        retention::prune(&store, &policy)
+                            .map(|pruned| pruned.len())
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::Pruned,
+                )
+            }
+            Message::Pruned(result) => {
+                match result {
+                    Ok(0) => {}
+                    Ok(count) => self.status = Some(format!("Pruned {} old backups", count)),
+                    Err(error) => self.status = Some(format!("Could not prune backups: {}", error)),
+                }
+                Task::done(Message::RefreshSaves)
+            }
+            Message::InstallationSelected(Installation(game_directory)) => {
+                self.store = self.store.with_game_directory(game_directory);
+                self.game_saves.clear();
+                Task::done(Message::RefreshSaves)
+            }
             Message::RefreshSaves => {
                 self.loading = true;
-                Task::perform(
-                    load_saves(
-                        self.deltarune_saves_directory.clone(),
-                        self.local_saves_directory.clone(),
-                    ),
-                    Message::SavesLoaded,
+                // A mod may have added a chapter since we last looked.
+                self.store.detect_chapters();
+                let store = self.store.clone();
+                let demo_tasks = self.demos.iter().enumerate().map(|(index, demo)| {
+                    let store = demo.store.clone();
+                    Task::perform(async move { store.load() }, move |result| {
+                        Message::DemoSavesLoaded(index, result)
+                    })
+                });
+                Task::batch(
+                    std::iter::once(Task::perform(
+                        async move { store.load() },
+                        Message::SavesLoaded,
+                    ))
+                    .chain(demo_tasks),
                 )
             }
             Message::BackupSave(chapter, slot) => {
-                if let Some(save) = self.game_saves.get(&(chapter, slot)) {
+                if self.game_saves.contains_key(&(chapter, slot))
+                    || self.extra_saves.contains_key(&(chapter, slot))
+                {
+                    let problem = self
+                        .game_saves
+                        .get(&(chapter, slot))
+                        .and_then(|save| self.store.read_save(save).ok())
+                        .and_then(|contents| format::validate(&contents).err());
+                    let store = self.store.clone();
+                    let note = std::mem::take(&mut self.backup_note);
                     Task::perform(
-                        backup_save(
-                            save.path.clone(),
-                            self.local_saves_directory.clone(),
+                        async move {
+                            let note = Some(note.trim()).filter(|note| !note.is_empty());
+                            store
+                                .backup_with_note(chapter, slot, note)
+                                .map_err(|error| error.to_string())
+                        },
+                        move |result| Message::BackedUp(chapter, slot, problem.clone(), result),
+                    )
+                } else {
+                    Task::none()
+                }
+            }
+            Message::BackedUp(chapter, slot, problem, result) => match result {
+                Ok(_) => {
+                    if let Some(problem) = problem {
+                        self.status = Some(format!(
+                            "Warning: Chapter {} Slot {} looks corrupted: {}. Backed it up anyway.",
                             chapter,
-                            slot,
-                        ),
-                        |_| Message::RefreshSaves,
+                            slot + 1,
+                            problem
+                        ));
+                    }
+                    Task::done(Message::PruneBackups)
+                }
+                Err(error) => {
+                    self.status = Some(format!(
+                        "Could not back up Chapter {} Slot {}: {}",
+                        chapter,
+                        slot + 1,
+                        error
+                    ));
+                    Task::none()
+                }
+            },
+            Message::BackupAllSlots => {
+                let mut slots = self.game_saves.keys().copied().collect::<Vec<_>>();
+                slots.sort();
+                let store = self.store.clone();
+                let note = mem::take(&mut self.backup_note);
+                self.status = Some(format!("Backing up {} slots…", slots.len()));
+                Task::perform(
+                    async move {
+                        let note = Some(note.trim()).filter(|note| !note.is_empty());
+                        let failures = slots
+                            .iter()
+                            .filter_map(|&(chapter, slot)| {
+                                store
+                                    .backup_with_note(chapter, slot, note)
+                                    .err()
+                                    .map(|error| {
+                                        format!("Chapter {} Slot {}: {}", chapter, slot + 1, error)
+                                    })
+                            })
+                            .collect();
+                        (slots.len(), failures)
+                    },
+                    |(count, failures)| Message::BackedUpAll(count, failures),
+                )
+            }
+            Message::BackedUpAll(count, failures) => {
+                let succeeded = count - failures.len();
+                self.status = Some(if failures.is_empty() {
+                    format!("Backed up all {} slots", count)
+                } else {
+                    format!(
+                        "Backed up {} of {} slots; {}",
+                        succeeded,
+                        count,
+                        failures.join("; ")
                     )
+                });
+                Task::done(Message::PruneBackups)
+            }
+            Message::BackupNoteChanged(note) => {
+                self.backup_note = note;
+                Task::none()
+            }
+            Message::RestoreSave(local_path, chapter, slot) => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        conflict::restore_watched(&store, local_path, chapter, slot)
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::RestoreWatched,
+                )
+            }
+            Message::RestoreTo(backup_path, target) => {
+                match self.store.restore_warning(&backup_path, target.chapter) {
+                    Ok(Some(warning)) => {
+                        self.warned_restore = Some(WarnedRestore {
+                            backup_path,
+                            target,
+                            warning,
+                        });
+                        Task::none()
+                    }
+                    Ok(None) => Task::done(Message::RestoreSave(
+                        backup_path,
+                        target.chapter,
+                        target.slot,
+                    )),
+                    Err(error) => {
+                        self.status = Some(format!("Restore failed: {}", error));
+                        Task::none()
+                    }
+                }
+            }
+            Message::WarnedRestoreConfirmed(confirmed) => match self.warned_restore.take() {
+                Some(pending) if confirmed => Task::done(Message::RestoreSave(
+                    pending.backup_path,
+                    pending.target.chapter,
+                    pending.target.slot,
+                )),
+                _ => Task::none(),
+            },
+            Message::CheckIntegrity => {
+                if self.checking_integrity || self.store.is_locked() {
+                    return Task::none();
+                }
+                self.checking_integrity = true;
+                let store = self.store.clone();
+                Task::perform(
+                    async move { store.check_integrity().map_err(|error| error.to_string()) },
+                    Message::IntegrityChecked,
+                )
+            }
+            Message::IntegrityChecked(result) => {
+                self.checking_integrity = false;
+                match result {
+                    Ok(damaged) => {
+                        self.damaged = damaged
+                            .into_iter()
+                            .map(|damaged| (damaged.save.path, damaged.problem))
+                            .collect();
+                        self.status = Some(match self.damaged.len() {
+                            0 => "Checked every backup: none are damaged".to_string(),
+                            count => {
+                                format!("{} backups are damaged; see them marked below", count)
+                            }
+                        });
+                    }
+                    Err(error) => {
+                        self.status = Some(format!("Could not check the backups: {}", error))
+                    }
+                }
+                Task::none()
+            }
+            Message::RestoreWatched(result) => {
+                match result {
+                    Ok(watched) => {
+                        self.status = Some(format!(
+                            "Restored and verified Chapter {} Slot {}",
+                            watched.chapter,
+                            watched.slot + 1
+                        ));
+                        self.watched_restore = Some(watched);
+                    }
+                    Err(error) => self.status = Some(format!("Restore failed: {}", error)),
+                }
+                Task::done(Message::RefreshSaves)
+            }
+            Message::CheckRestoreConflict => {
+                let Some(watched) = self.watched_restore.clone() else {
+                    return Task::none();
+                };
+                if watched.is_expired(SystemTime::now()) {
+                    self.watched_restore = None;
+                    return Task::none();
+                }
+                let store = self.store.clone();
+                Task::perform(
+                    async move { conflict::check(&store, &watched).map_err(|error| error.to_string()) },
+                    Message::RestoreConflictChecked,
+                )
+            }
+            Message::RestoreConflictChecked(result) => {
+                match result {
+                    Ok(Some(found)) => {
+                        self.watched_restore = None;
+                        self.conflict = Some(found);
+                    }
+                    Ok(None) => {}
+                    Err(error) => {
+                        self.watched_restore = None;
+                        self.status = Some(format!("Could not check the restore: {}", error));
+                    }
+                }
+                Task::done(Message::RefreshSaves)
+            }
+            Message::ConflictChoiceMade(choice) => {
+                let Some(found) = self.conflict.clone() else {
+                    return Task::none();
+                };
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        conflict::resolve(&store, &found, choice).map_err(|error| error.to_string())
+                    },
+                    move |result| Message::ConflictResolved(choice, result),
+                )
+            }
+            Message::ConflictResolved(choice, result) => {
+                if let Some(found) = self.conflict.take() {
+                    self.status = Some(match result {
+                        Ok(()) => format!(
+                            "Kept {} in Chapter {} Slot {}",
+                            choice,
+                            found.chapter,
+                            found.slot + 1
+                        ),
+                        Err(error) => format!("Could not keep {}: {}", choice, error),
+                    });
+                }
+                Task::done(Message::RefreshSaves)
+            }
+            Message::RestoreFinished(result) => {
+                self.status = Some(match result {
+                    Ok(()) => "Restored and verified".to_string(),
+                    Err(error) => format!("Restore failed: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::UndoRestore => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move { undo::undo(&store).map_err(|error| error.to_string()) },
+                    Message::RestoreUndone,
+                )
+            }
+            Message::RestoreUndone(result) => {
+                self.status = Some(match result {
+                    Ok(last) => format!(
+                        "Put back what was in Chapter {} Slot {} before the restore",
+                        last.chapter,
+                        last.slot + 1
+                    ),
+                    Err(error) => format!("Could not undo the restore: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::DeleteLocalSave(path) => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move { store.delete(&path).map_err(|error| error.to_string()) },
+                    Message::DeleteFinished,
+                )
+            }
+            Message::DeleteFinished(result) => {
+                self.status = Some(match result {
+                    Ok(()) => "Moved the backup to the trash".to_string(),
+                    Err(error) => format!("Delete failed: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::NoteChanged(path, note) => {
+                self.note_drafts.insert(path, note);
+                Task::none()
+            }
+            Message::SaveNote(path) => {
+                let Some(note) = self.note_drafts.remove(&path) else {
+                    return Task::none();
+                };
+                let id = path.file_name().and_then(|name| name.to_str());
+                let result = self
+                    .store
+                    .index()
+                    .and_then(|index| index.set_note(id.unwrap_or_default(), &note));
+                self.status = Some(match result {
+                    Ok(true) => "Saved the note".to_string(),
+                    Ok(false) => "Could not save the note: the backup is gone".to_string(),
+                    Err(error) => format!("Could not save the note: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::SaveEditorToggled(enabled) => {
+                self.save_editor = enabled;
+                if let Err(error) = config::update_config(|config| {
+                    config.save_editor = enabled;
+                }) {
+                    self.status = Some(format!("Could not save config: {}", error));
+                }
+                Task::none()
+            }
+            Message::EditNameChanged(path, name) => {
+                self.edit_drafts.entry(path).or_default().0 = name;
+                Task::none()
+            }
+            Message::EditGoldChanged(path, gold) => {
+                self.edit_drafts.entry(path).or_default().1 = gold;
+                Task::none()
+            }
+            Message::SaveEdits(path) => {
+                let Some((name, gold)) = self.edit_drafts.remove(&path) else {
+                    return Task::none();
+                };
+                let Some(save) = self
+                    .local_saves
+                    .iter()
+                    .find(|save| save.path == path)
+                    .cloned()
+                else {
+                    return Task::none();
+                };
+                let gold = match gold.trim() {
+                    "" => None,
+                    gold => match gold.parse::<u32>() {
+                        Ok(gold) => Some(gold),
+                        Err(_) => {
+                            self.status = Some(format!("{} is not an amount of D$", gold));
+                            return Task::none();
+                        }
+                    },
+                };
+                let edits = edit::Edits {
+                    name: Some(name.trim().to_string()).filter(|name| !name.is_empty()),
+                    gold,
+                };
+                if edits.is_empty() {
+                    return Task::none();
+                }
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        edit::edit_backup(&store, &save, &edits).map_err(|error| error.to_string())
+                    },
+                    Message::Edited,
+                )
+            }
+            Message::Edited(result) => {
+                self.status = Some(match result {
+                    Ok(path) => format!("Saved the edits as a new backup, {}", path.display()),
+                    Err(error) => format!("Could not edit the backup: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::NameChanged(path, name) => {
+                self.name_drafts.insert(path, name);
+                Task::none()
+            }
+            Message::SaveName(path) => {
+                let Some(name) = self.name_drafts.remove(&path) else {
+                    return Task::none();
+                };
+                let id = path.file_name().and_then(|name| name.to_str());
+                let result = self
+                    .store
+                    .index()
+                    .and_then(|index| index.set_name(id.unwrap_or_default(), &name));
+                self.status = Some(match result {
+                    Ok(true) => "Renamed the backup".to_string(),
+                    Ok(false) => "Could not rename the backup: it is gone".to_string(),
+                    Err(error) => format!("Could not rename the backup: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::TagChanged(path, tag) => {
+                self.tag_drafts.insert(path, tag);
+                Task::none()
+            }
+            Message::AddTag(path) => {
+                let Some(tag) = self.tag_drafts.remove(&path) else {
+                    return Task::none();
+                };
+                if tag.trim().is_empty() {
+                    return Task::none();
+                }
+                let id = path.file_name().and_then(|name| name.to_str());
+                if let Err(error) = self
+                    .store
+                    .index()
+                    .and_then(|index| index.tag(id.unwrap_or_default(), &tag))
+                {
+                    self.status = Some(format!("Could not add the tag: {}", error));
+                }
+                Task::done(Message::RefreshSaves)
+            }
+            Message::RemoveTag(path, tag) => {
+                let id = path.file_name().and_then(|name| name.to_str());
+                if let Err(error) = self
+                    .store
+                    .index()
+                    .and_then(|index| index.untag(id.unwrap_or_default(), &tag))
+                {
+                    self.status = Some(format!("Could not remove the tag: {}", error));
+                }
+                Task::done(Message::RefreshSaves)
+            }
+            Message::PinToggled(path, pinned) => {
+                let id = path.file_name().and_then(|name| name.to_str());
+                if let Err(error) = self
+                    .store
+                    .index()
+                    .and_then(|index| index.set_pinned(id.unwrap_or_default(), pinned))
+                {
+                    self.status = Some(format!("Could not pin the backup: {}", error));
+                }
+                Task::done(Message::RefreshSaves)
+            }
+            Message::TagFilterSelected(filter) => {
+                self.tag_filter = filter;
+                Task::none()
+            }
+            Message::BackupOrderSelected(order) => {
+                self.backup_order = order;
+                if let Err(error) = config::update_config(|config| {
+                    config.backup_order = order;
+                }) {
+                    self.status = Some(format!("Could not save config: {}", error));
+                }
+                Task::none()
+            }
+            Message::TrashToggled(shown) => {
+                self.show_trash = shown;
+                Task::none()
+            }
+            Message::DuplicatesToggled(shown) => {
+                self.show_duplicates = shown;
+                Task::none()
+            }
+            Message::CollapseDuplicates(sets) => {
+                let store = self.store.clone();
+                let pinned = self
+                    .backup_entries
+                    .iter()
+                    .filter(|(_, entry)| entry.pinned)
+                    .map(|(id, _)| id.clone())
+                    .collect::<BTreeSet<_>>();
+                Task::perform(
+                    async move {
+                        let is_pinned =
+                            |save: &SaveFile| save.id().is_some_and(|id| pinned.contains(id));
+                        duplicates::collapse(&store, &sets, is_pinned)
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::DuplicatesCollapsed,
+                )
+            }
+            Message::DuplicatesCollapsed(result) => {
+                self.status = Some(match result {
+                    Ok(collapsed) => format!(
+                        "Moved {} duplicate backups to the trash; emptying it frees {}",
+                        collapsed.trashed.len(),
+                        self.locale.format_size(collapsed.reclaimable)
+                    ),
+                    Err(error) => format!("Could not remove duplicates: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::BranchesToggled(shown) => {
+                self.show_branches = shown;
+                Task::none()
+            }
+            Message::BranchNameChanged(name) => {
+                self.branch_name = name;
+                Task::none()
+            }
+            Message::SwitchBranch(name) => {
+                if name.trim().is_empty() {
+                    return Task::none();
+                }
+                self.branch_name.clear();
+                let mut branches = self.branches.clone();
+                branches.switch(&name);
+                let result = branches
+                    .save(self.store.local_directory())
+                    .map(|()| format!("New backups now go to {}", branches.active()))
+                    .map_err(|error| error.to_string());
+                Task::done(Message::BranchFinished(result))
+            }
+            Message::RestoreBranchTip(name) => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        branch::restore_tip(&store, &name)
+                            .map(|tips| {
+                                format!("Switched to {}, restoring {} slots", name, tips.len())
+                            })
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::BranchFinished,
+                )
+            }
+            Message::BranchFinished(result) => {
+                self.status = Some(match result {
+                    Ok(message) => message,
+                    Err(error) => format!("Playthrough failed: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::RestoreFromTrash(path) => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        store
+                            .restore_from_trash(&path)
+                            .map(|_| "Put the backup back".to_string())
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::TrashFinished,
+                )
+            }
+            Message::PurgeFromTrash(path) => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        store
+                            .purge(&path)
+                            .map(|()| "Deleted the backup for good".to_string())
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::TrashFinished,
+                )
+            }
+            Message::EmptyTrash => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        store
+                            .purge_trash_older_than(Duration::ZERO)
+                            .map(|purged| format!("Deleted {} backups for good", purged.len()))
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::TrashFinished,
+                )
+            }
+            Message::TrashFinished(result) => {
+                self.status = Some(match result {
+                    Ok(message) => message,
+                    Err(error) => format!("Trash failed: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::ExportChapterHistory(chapter) => Task::perform(
+                export_chapter_history(chapter, self.store.clone(), self.local_saves.clone()),
+                Message::ArchiveExported,
+            ),
+            Message::BackupSelected(path, selected) => {
+                if selected {
+                    self.selected_backups.insert(path);
                 } else {
+                    self.selected_backups.remove(&path);
+                }
+                Task::none()
+            }
+            Message::BackupExpanded(path, expanded) => {
+                if expanded {
+                    self.expanded_backups.insert(path);
+                } else {
+                    self.expanded_backups.remove(&path);
+                }
+                Task::none()
+            }
+            Message::ClearSelection => {
+                self.selected_backups.clear();
+                self.confirming_bulk_delete = false;
+                Task::none()
+            }
+            Message::ExportSelected => {
+                let saves = self
+                    .local_saves
+                    .iter()
+                    .filter(|save| self.selected_backups.contains(&save.path))
+                    .cloned()
+                    .collect();
+                Task::perform(
+                    export_backups(self.store.clone(), saves),
+                    Message::ArchiveExported,
+                )
+            }
+            Message::CompareSelected => {
+                let mut selected = self
+                    .local_saves
+                    .iter()
+                    .filter(|save| self.selected_backups.contains(&save.path))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                // Backup IDs sort by when they were made.
+                selected.sort_by(|a, b| a.id().cmp(&b.id()));
+                let pair = match selected.as_slice() {
+                    [before, after] => Some((before.clone(), after.clone())),
+                    [backup] => self
+                        .game_saves
+                        .get(&(backup.chapter, backup.slot))
+                        .map(|game_save| (backup.clone(), game_save.clone())),
+                    _ => None,
+                };
+                let Some((before, after)) = pair else {
+                    self.status = Some(
+                        "Select two backups, or one whose slot has a game save, to compare"
+                            .to_string(),
+                    );
+                    return Task::none();
+                };
+                let label = |save: &SaveFile| {
+                    let entry = save.id().and_then(|id| self.backup_entries.get(id));
+                    let name = save.display_name_with_label(entry.and_then(index::Entry::label));
+                    if save.is_local {
+                        name
+                    } else {
+                        format!("{} (game)", name)
+                    }
+                };
+                let (before_label, after_label) = (label(&before), label(&after));
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        let read = |save: &SaveFile| {
+                            store.read_save(save).map_err(|error| error.to_string())
+                        };
+                        let changes = compare::compare(&read(&before)?, &read(&after)?)
+                            .ok_or_else(|| "not both DELTARUNE saves".to_string())?;
+                        Ok(Comparison {
+                            before: before_label,
+                            after: after_label,
+                            changes,
+                        })
+                    },
+                    Message::Compared,
+                )
+            }
+            Message::Compared(result) => {
+                match result {
+                    Ok(comparison) => self.comparison = Some(comparison),
+                    Err(error) => self.status = Some(format!("Could not compare: {}", error)),
+                }
+                Task::none()
+            }
+            Message::ShowTimeline(chapter, slot) => {
+                let count = self.timeline_saves(chapter, slot).len();
+                self.timeline = Some((chapter, slot, count.saturating_sub(1)));
+                Task::none()
+            }
+            Message::TimelineScrubbed(position) => {
+                if let Some((_, _, current)) = &mut self.timeline {
+                    *current = position;
+                }
+                Task::none()
+            }
+            Message::BrowseFlags(path) => {
+                let Some(save) = self
+                    .local_saves
+                    .iter()
+                    .find(|save| save.path == path)
+                    .cloned()
+                else {
+                    return Task::none();
+                };
+                let entry = save.id().and_then(|id| self.backup_entries.get(id));
+                let title = save.display_name_with_label(entry.and_then(index::Entry::label));
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        let contents = store.read_save(&save).map_err(|error| error.to_string())?;
+                        let flags = format::flags(&contents)
+                            .ok_or_else(|| "not a full DELTARUNE save".to_string())?;
+                        Ok(FlagBrowser {
+                            title,
+                            flags: flags.into_iter().map(str::to_string).collect(),
+                            query: String::new(),
+                        })
+                    },
+                    Message::FlagsRead,
+                )
+            }
+            Message::FlagsRead(result) => {
+                match result {
+                    Ok(browser) => self.flag_browser = Some(browser),
+                    Err(error) => self.status = Some(format!("Could not read flags: {}", error)),
+                }
+                Task::none()
+            }
+            Message::FlagQueryChanged(query) => {
+                if let Some(browser) = &mut self.flag_browser {
+                    browser.query = query;
+                }
+                Task::none()
+            }
+            Message::CloseFlags => {
+                self.flag_browser = None;
+                Task::none()
+            }
+            Message::ViewContents(save) => {
+                let entry = save.id().and_then(|id| self.backup_entries.get(id));
+                let title = save.display_name_with_label(entry.and_then(index::Entry::label));
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        let contents = store.read_save(&save).map_err(|error| error.to_string())?;
+                        Ok(ContentsViewer {
+                            title,
+                            lines: dump::lines(&contents),
+                            hex: dump::hex(&contents),
+                            show_hex: false,
+                            page: 0,
+                        })
+                    },
+                    Message::ContentsRead,
+                )
+            }
+            Message::ContentsRead(result) => {
+                match result {
+                    Ok(viewer) => self.contents_viewer = Some(viewer),
+                    Err(error) => self.status = Some(format!("Could not read save: {}", error)),
+                }
+                Task::none()
+            }
+            Message::ContentsHexToggled(show_hex) => {
+                if let Some(viewer) = &mut self.contents_viewer {
+                    viewer.show_hex = show_hex;
+                    viewer.page = 0;
+                }
+                Task::none()
+            }
+            Message::ContentsPageChanged(page) => {
+                if let Some(viewer) = &mut self.contents_viewer {
+                    viewer.page = page.min(viewer.page_count() - 1);
+                }
+                Task::none()
+            }
+            Message::CloseContents => {
+                self.contents_viewer = None;
+                Task::none()
+            }
+            Message::CloseTimeline => {
+                self.timeline = None;
+                Task::none()
+            }
+            Message::CloseComparison => {
+                self.comparison = None;
+                Task::none()
+            }
+            Message::DeleteSelected => {
+                self.confirming_bulk_delete = true;
+                Task::none()
+            }
+            Message::DeleteSelectedConfirmed(confirmed) => {
+                self.confirming_bulk_delete = false;
+                if !confirmed {
+                    return Task::none();
+                }
+                let (pinned, paths): (Vec<_>, Vec<_>) = mem::take(&mut self.selected_backups)
+                    .into_iter()
+                    .partition(|path| {
+                        path.file_name()
+                            .and_then(|name| name.to_str())
+                            .and_then(|id| self.backup_entries.get(id))
+                            .is_some_and(|entry| entry.pinned)
+                    });
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        let failures = paths
+                            .iter()
+                            .filter_map(|path| store.delete(path).err())
+                            .map(|error| error.to_string())
+                            .collect::<Vec<_>>();
+                        let mut message = format!(
+                            "Moved {} backups to the trash",
+                            paths.len() - failures.len()
+                        );
+                        if !pinned.is_empty() {
+                            message.push_str(&format!(", kept {} pinned", pinned.len()));
+                        }
+                        if failures.is_empty() {
+                            Ok(message)
+                        } else {
+                            Err(format!("{}; {}", message, failures.join("; ")))
+                        }
+                    },
+                    Message::SelectedDeleted,
+                )
+            }
+            Message::SelectedDeleted(result) => {
+                self.status = Some(match result {
+                    Ok(message) => message,
+                    Err(error) => format!("Delete failed: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::ExportFile(save) => {
+                Task::perform(export_file(self.store.clone(), save), Message::FileExported)
+            }
+            Message::ExportJson(save) => Task::perform(
+                export_json(self.store.clone(), save, self.rooms.clone()),
+                Message::FileExported,
+            ),
+            Message::FileExported(result) => {
+                self.status = match result {
+                    Ok(Some(path)) => Some(format!("Exported to {}", path.display())),
+                    Ok(None) => None,
+                    Err(error) => Some(format!("Export failed: {}", error)),
+                };
+                Task::none()
+            }
+            Message::PushGitHistory => {
+                let Some(directory) = self.store.git_directory().map(Path::to_path_buf) else {
+                    return Task::none();
+                };
+                self.status = Some("Pushing git history…".to_string());
+                Task::perform(
+                    async move { git::push(&directory).map_err(|error| error.to_string()) },
+                    Message::GitHistoryPushed,
+                )
+            }
+            Message::GitHistoryPushed(result) => {
+                self.status = Some(match result {
+                    Ok(url) => format!("Pushed git history to {}", url),
+                    Err(error) => format!("Could not push git history: {}", error),
+                });
+                Task::none()
+            }
+            Message::ImportArchive => {
+                Task::perform(import_backups(self.store.clone()), Message::ArchiveImported)
+            }
+            Message::FileDropped(path) => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move { import::import_path(&path, &store).map(Some) },
+                    Message::ArchiveImported,
+                )
+            }
+            Message::DropChapterSelected(chapter) => {
+                self.dropped_chapter = chapter;
+                Task::none()
+            }
+            Message::ImportDropped(slot) => {
+                if self.dropped.is_empty() {
+                    return Task::none();
+                }
+                let path = self.dropped.remove(0);
+                let chapter = self.dropped_chapter;
+                let store = self.store.clone();
+                Task::perform(
+                    async move { import::import_file(&path, chapter, slot, &store).map(Some) },
+                    Message::ArchiveImported,
+                )
+            }
+            Message::SkipDropped => {
+                if !self.dropped.is_empty() {
+                    self.dropped.remove(0);
+                }
+                Task::none()
+            }
+            Message::ArchiveImported(result) => {
+                self.status = match result {
+                    Ok(Some(summary))
+                        if summary.imported.is_empty()
+                            && summary.duplicates == 0
+                            && summary.rejected.is_empty() =>
+                    {
+                        self.dropped.extend(summary.unrecognized);
+                        None
+                    }
+                    Ok(Some(summary)) => {
+                        self.dropped.extend(summary.unrecognized.iter().cloned());
+                        let mut status = format!(
+                            "Imported {} backups, {} already backed up",
+                            summary.imported.len(),
+                            summary.duplicates
+                        );
+                        if summary.unlabeled > 0 {
+                            status.push_str(&format!(
+                                "; dropped the names, notes, and tags of {} backups, which are not \
+                                 kept while backups are encrypted",
+                                summary.unlabeled
+                            ));
+                        }
+                        for (name, reason) in &summary.rejected {
+                            status.push_str(&format!("; skipped {}: {}", name, reason));
+                        }
+                        Some(status)
+                    }
+                    Ok(None) => None,
+                    Err(error) => Some(format!("Import failed: {}", error)),
+                };
+                Task::done(Message::RefreshSaves)
+            }
+            Message::ArchiveExported(result) => {
+                self.status = match result {
+                    Ok(Some(path)) => Some(format!("Exported to {}", path.display())),
+                    Ok(None) => None,
+                    Err(error) => Some(format!("Export failed: {}", error)),
+                };
+                Task::none()
+            }
+            Message::SearchQueryChanged(query) => {
+                self.search_query = query;
+                Task::none()
+            }
+            Message::ChapterHandoff(chapter, slot) => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        store
+                            .chapter_handoff(chapter, slot)
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::ChapterHandoffFinished,
+                )
+            }
+            Message::ChapterHandoffFinished(result) => {
+                self.status = Some(match result {
+                    Ok(path) => format!("Saved chapter handoff to {}", path.display()),
+                    Err(error) => format!("Chapter handoff failed: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::CheckGameUpdate => {
+                // The next tick after we are plugged back in catches up.
+                if !self.backups.snapshot_on_game_update
+                    || (self.backups.pause_on_battery && power::on_battery())
+                {
+                    return Task::none();
+                }
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        store
+                            .snapshot_on_game_update()
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::GameUpdateChecked,
+                )
+            }
+            Message::DemoSavesLoaded(index, result) => {
+                if let (Some(demo), Ok(saves)) = (self.demos.get_mut(index), result) {
+                    demo.saves = saves;
+                }
+                Task::none()
+            }
+            Message::BackupDemoSave(index, chapter, slot) => {
+                let Some(demo) = self.demos.get(index) else {
+                    return Task::none();
+                };
+                let store = demo.store.clone();
+                Task::perform(async move { store.backup(chapter, slot) }, |_| {
+                    Message::RefreshSaves
+                })
+            }
+            Message::RestoreDemoSave(index, local_path, chapter, slot) => {
+                let Some(demo) = self.demos.get(index) else {
+                    return Task::none();
+                };
+                let store = demo.store.clone();
+                Task::perform(
+                    async move {
+                        store
+                            .restore(&local_path, chapter, slot)
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::RestoreFinished,
+                )
+            }
+            Message::ImportFromClipboard(chapter) => iced::clipboard::read()
+                .map(move |contents| Message::ClipboardRead(chapter, contents)),
+            Message::ClipboardRead(chapter, contents) => {
+                let decoded = transfer::decode_save(
+                    self.store.profile(),
+                    contents.as_deref().unwrap_or_default(),
+                )
+                .map_err(|error| format!("Clipboard {}", error))
+                .and_then(|decoded| match decoded.origin {
+                    Some((origin_chapter, _)) if origin_chapter != chapter => Err(format!(
+                        "That save is from Chapter {}, not Chapter {}",
+                        origin_chapter, chapter
+                    )),
+                    origin => Ok((origin.map_or(0, |(_, slot)| slot), decoded.contents)),
+                });
+                match decoded {
+                    Ok((slot, contents)) => {
+                        let store = self.store.clone();
+                        Task::perform(
+                            async move {
+                                store
+                                    .import(chapter, slot, &contents)
+                                    .map_err(|error| error.to_string())
+                            },
+                            Message::Imported,
+                        )
+                    }
+                    Err(error) => Task::done(Message::Imported(Err(error))),
+                }
+            }
+            Message::Imported(result) => {
+                self.status = Some(match result {
+                    Ok(path) => format!("Imported {}", path.display()),
+                    Err(error) => format!("Import failed: {}", error),
+                });
+                Task::done(Message::RefreshSaves)
+            }
+            Message::CopyAsBase64(path, chapter, slot) => {
+                let filename = self.store.profile().save_filename(chapter, slot);
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        let contents = store
+                            .read_backup(&path)
+                            .map_err(|error| error.to_string())?;
+                        Ok(transfer::encode_save(&filename, &contents))
+                    },
+                    Message::Base64Encoded,
+                )
+            }
+            Message::Base64Encoded(result) => match result {
+                Ok(text) => {
+                    self.status = Some("Copied save to the clipboard as base64".to_string());
+                    iced::clipboard::write(text)
+                }
+                Err(error) => {
+                    self.status = Some(format!("Copy failed: {}", error));
                     Task::none()
                 }
+            },
+            Message::CopyShareCode(path, chapter, slot) => {
+                let filename = self.store.profile().save_filename(chapter, slot);
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        let contents = store
+                            .read_backup(&path)
+                            .map_err(|error| error.to_string())?;
+                        transfer::encode_share_code(&filename, &contents)
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::ShareCodeEncoded,
+                )
+            }
+            Message::ShareCodeChanged(code) => {
+                self.share_code = code;
+                Task::none()
+            }
+            Message::ImportShareCode => {
+                match transfer::decode_share_code(self.store.profile(), &self.share_code) {
+                    Ok(decoded) => {
+                        self.share_code.clear();
+                        let (chapter, slot) = decoded.origin.unwrap_or_default();
+                        let store = self.store.clone();
+                        Task::perform(
+                            async move {
+                                store
+                                    .import(chapter, slot, &decoded.contents)
+                                    .map_err(|error| error.to_string())
+                            },
+                            Message::Imported,
+                        )
+                    }
+                    Err(error) => Task::done(Message::Imported(Err(error.to_string()))),
+                }
+            }
+            Message::ShareCodeEncoded(result) => match result {
+                Ok(code) => {
+                    self.status = Some(format!(
+                        "Copied share code to the clipboard ({} characters)",
+                        code.len()
+                    ));
+                    iced::clipboard::write(code)
+                }
+                Err(error) => {
+                    self.status = Some(format!("Copy failed: {}", error));
+                    Task::none()
+                }
+            },
+            Message::GameUpdateChecked(result) => {
+                self.unsnapshotted_update = matches!(result, Ok(Some(GameUpdate::Unsnapshotted)));
+                match result {
+                    Ok(Some(GameUpdate::Snapshotted(path))) => {
+                        self.status = Some(format!(
+                            "Game update detected, saves snapshotted to {}",
+                            path.display()
+                        ));
+                        if let Some(export_hook) = self.export_hook.clone() {
+                            return Task::perform(
+                                async move {
+                                    hook::export_snapshot(&export_hook, &path)
+                                        .map_err(|error| error.to_string())
+                                },
+                                Message::SnapshotExported,
+                            );
+                        }
+                    }
+                    Ok(Some(GameUpdate::Unsnapshotted) | None) => {}
+                    Err(error) => {
+                        self.status = Some(format!("Could not snapshot saves: {}", error));
+                    }
+                }
+                Task::none()
+            }
+            Message::SkipGameUpdateSnapshot => {
+                let store = self.store.clone();
+                Task::perform(
+                    async move {
+                        store
+                            .skip_game_update_snapshot()
+                            .map_err(|error| error.to_string())
+                    },
+                    Message::GameUpdateSnapshotSkipped,
+                )
+            }
+            Message::GameUpdateSnapshotSkipped(result) => {
+                match result {
+                    Ok(()) => self.unsnapshotted_update = false,
+                    Err(error) => {
+                        self.status = Some(format!("Could not skip the snapshot: {}", error));
+                    }
+                }
+                Task::none()
             }
-            Message::RestoreSave(local_path, chapter, slot) => Task::perform(
-                restore_save(
-                    local_path,
-                    self.deltarune_saves_directory.clone(),
-                    chapter,
-                    slot,
-                ),
-                |_| Message::RefreshSaves,
-            ),
-            Message::DeleteLocalSave(path) => {
-                Task::perform(delete_local_save(path), |_| Message::RefreshSaves)
+            Message::SnapshotExported(result) => {
+                self.status = Some(match result {
+                    Ok(path) => format!("Exported snapshot to {}", path.display()),
+                    Err(error) => format!("Could not export snapshot: {}", error),
+                });
+                Task::none()
             }
         }
     }
 
-    fn view(&self) -> Element<Message> {
+    /// Games without chapters show their name in place of "Chapter 1".
+    fn chapter_title(&self, chapter: Chapter) -> String {
+        let profile = self.store.profile();
+        if profile.has_chapters() {
+            format!("Chapter {}", chapter)
+        } else {
+            profile.name.clone()
+        }
+    }
+
+    /// Every tag on a backup, to filter by, after [`TagFilter::All`].
+    fn tag_filters(&self) -> Vec<TagFilter> {
+        let tags = self
+            .backup_entries
+            .values()
+            .flat_map(|entry| &entry.tags)
+            .collect::<BTreeSet<_>>();
+        std::iter::once(TagFilter::All)
+            .chain(tags.into_iter().cloned().map(TagFilter::Tag))
+            .collect()
+    }
+
+    fn modified_label(&self, save: &SaveFile) -> String {
+        save.modified
+            .map(|time| self.locale.format_time(time))
+            .unwrap_or("Unknown".to_string())
+    }
+
+    fn view(&self) -> Element<'_, Message> {
         if self.loading {
             return container(text("Loading saves..."))
                 .center_x(Fill)
@@ -189,45 +2189,688 @@ impl Deltasaver {
         let game_saves_column = self.create_game_saves_column();
         let local_saves_column = self.create_local_saves_column();
 
-        container(
+        let columns = row![
+            game_saves_column,
+            vertical_space().width(SPACING2),
+            local_saves_column
+        ]
+        .height(Fill);
+
+        let mut content = column![
+            checkbox(
+                "Back up automatically when the game saves",
+                self.backups.auto_backup
+            )
+            .on_toggle(Message::AutoBackupToggled)
+            .size(BUTTON_SIZE)
+            .text_size(BUTTON_SIZE),
+            checkbox(
+                "Skip backups identical to the latest one",
+                self.backups.skip_identical
+            )
+            .on_toggle(Message::SkipIdenticalToggled)
+            .size(BUTTON_SIZE)
+            .text_size(BUTTON_SIZE),
+            checkbox("Edit backups in their details", self.save_editor)
+                .on_toggle(Message::SaveEditorToggled)
+                .size(BUTTON_SIZE)
+                .text_size(BUTTON_SIZE),
+            checkbox("Show vault history", self.show_vault_history)
+                .on_toggle(Message::VaultHistoryToggled)
+                .size(BUTTON_SIZE)
+                .text_size(BUTTON_SIZE),
+            checkbox("Show snapshots", self.show_snapshots)
+                .on_toggle(Message::SnapshotsToggled)
+                .size(BUTTON_SIZE)
+                .text_size(BUTTON_SIZE),
+            checkbox(
+                format!("Show trash ({})", self.trash.len()),
+                self.show_trash
+            )
+            .on_toggle(Message::TrashToggled)
+            .size(BUTTON_SIZE)
+            .text_size(BUTTON_SIZE),
+            checkbox("Show duplicates", self.show_duplicates)
+                .on_toggle(Message::DuplicatesToggled)
+                .size(BUTTON_SIZE)
+                .text_size(BUTTON_SIZE),
+            checkbox(
+                format!("Show playthroughs ({})", self.branches.active()),
+                self.show_branches
+            )
+            .on_toggle(Message::BranchesToggled)
+            .size(BUTTON_SIZE)
+            .text_size(BUTTON_SIZE)
+        ]
+        .spacing(SPACING);
+        if self.store.is_locked() {
+            content = content.push(
+                container(
+                    row![
+                        fonts::label("Backups are encrypted. Enter the passphrase to use them:")
+                            .size(BUTTON_SIZE),
+                        text_input("Passphrase", &self.passphrase)
+                            .secure(true)
+                            .on_input(Message::PassphraseChanged)
+                            .on_submit(Message::UnlockBackups)
+                            .size(BUTTON_SIZE),
+                        button(text("Unlock").size(BUTTON_SIZE)).on_press(Message::UnlockBackups),
+                    ]
+                    .spacing(SPACING)
+                    .align_y(Center),
+                )
+                .padding(SPACING)
+                .style(textbox_style),
+            );
+        } else if !self.store.is_encrypted() {
+            content = content.push(
+                row![
+                    text_input("Passphrase", &self.passphrase)
+                        .secure(true)
+                        .on_input(Message::PassphraseChanged)
+                        .size(BUTTON_SIZE),
+                    button(text("Encrypt backup contents").size(BUTTON_SIZE))
+                        .on_press(Message::EncryptBackups),
+                ]
+                .spacing(SPACING)
+                .align_y(Center),
+            );
+        }
+        if self.unsnapshotted_update {
+            content = content.push(
+                row![
+                    fonts::label(
+                        "The game updated, but its saves were not snapshotted first: \
+                         snapshots are off while backups are encrypted."
+                    )
+                    .size(BUTTON_SIZE),
+                    button(text("Continue without a snapshot").size(BUTTON_SIZE))
+                        .on_press(Message::SkipGameUpdateSnapshot),
+                ]
+                .spacing(SPACING)
+                .align_y(Center),
+            );
+        }
+        if self.store.is_encrypted() && self.store.keeps_unencrypted_copies() {
+            content = content.push(
+                fonts::label(
+                    "Backing up is off: the mirror and git would keep unencrypted copies of \
+                     encrypted backups. Remove them from config.toml.",
+                )
+                .size(BUTTON_SIZE),
+            );
+        }
+        if self.store.git_directory().is_some() {
+            content = content.push(
+                button(text("Push git history").size(BUTTON_SIZE))
+                    .on_press(Message::PushGitHistory),
+            );
+        }
+        if self.show_snapshots {
+            content = content.push(
+                container(self.create_snapshots_column())
+                    .padding(SPACING)
+                    .style(textbox_style),
+            );
+        }
+        if self.show_branches {
+            content = content.push(
+                container(self.create_branches_column())
+                    .padding(SPACING)
+                    .style(textbox_style),
+            );
+        }
+        if self.show_trash {
+            content = content.push(
+                container(self.create_trash_column())
+                    .padding(SPACING)
+                    .style(textbox_style),
+            );
+        }
+        if self.show_duplicates {
+            content = content.push(
+                container(self.create_duplicates_column())
+                    .padding(SPACING)
+                    .style(textbox_style),
+            );
+        }
+        if self.show_vault_history {
+            content = content.push(
+                container(self.create_vault_history_column())
+                    .padding(SPACING)
+                    .style(textbox_style),
+            );
+        }
+        if let Some(scheduler) = &self.scheduler {
+            let minutes = scheduler
+                .time_until_next(SystemTime::now())
+                .as_secs()
+                .div_ceil(60);
+            content = content.push(
+                fonts::label(format!("Next scheduled backup in {} minutes", minutes))
+                    .size(BUTTON_SIZE),
+            );
+        }
+        if self.installations.len() > 1 {
+            let selected = Installation(self.store.game_directory().to_path_buf());
+            content = content.push(
+                row![
+                    fonts::label("Installation").size(BUTTON_SIZE),
+                    pick_list(
+                        self.installations.as_slice(),
+                        Some(selected),
+                        Message::InstallationSelected
+                    )
+                    .text_size(BUTTON_SIZE)
+                ]
+                .spacing(SPACING)
+                .align_y(Center),
+            );
+        }
+        if let Some(status) = &self.status {
+            content = content.push(fonts::label(status).size(BUTTON_SIZE));
+        }
+        if let Some(last) = &self.last_restore {
+            content = content.push(
+                button(
+                    text(format!(
+                        "Undo last restore (Chapter {} Slot {})",
+                        last.chapter,
+                        last.slot + 1
+                    ))
+                    .size(BUTTON_SIZE),
+                )
+                .on_press(Message::UndoRestore),
+            );
+        }
+        if let Some(path) = self.dropped.first() {
+            let profile = self.store.profile();
+            let mut prompt = row![
+                fonts::label(format!(
+                    "Which slot is {}?",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ))
+                .size(BUTTON_SIZE),
+                pick_list(
+                    (1..=profile.chapter_count).collect::<Vec<_>>(),
+                    Some(self.dropped_chapter),
+                    Message::DropChapterSelected
+                )
+                .text_size(BUTTON_SIZE),
+            ]
+            .spacing(SPACING)
+            .align_y(Center);
+            for slot in 0..profile.slot_count {
+                prompt = prompt.push(
+                    button(text(format!("Slot {}", slot + 1)).size(BUTTON_SIZE))
+                        .on_press(Message::ImportDropped(slot)),
+                );
+            }
+            prompt =
+                prompt.push(button(text("Skip").size(BUTTON_SIZE)).on_press(Message::SkipDropped));
+            content = content.push(container(prompt).padding(SPACING).style(textbox_style));
+        }
+        if let Some(pending) = &self.warned_restore {
+            let warning = row![
+                fonts::label(format!(
+                    "Warning: restore into Chapter {} Slot {}? {}",
+                    pending.target.chapter,
+                    pending.target.slot + 1,
+                    pending.warning
+                ))
+                .size(BUTTON_SIZE),
+                button(text("Restore anyway").size(BUTTON_SIZE))
+                    .on_press(Message::WarnedRestoreConfirmed(true)),
+                button(text("Cancel").size(BUTTON_SIZE))
+                    .on_press(Message::WarnedRestoreConfirmed(false)),
+            ]
+            .spacing(SPACING)
+            .align_y(Center);
+            content = content.push(container(warning).padding(SPACING).style(textbox_style));
+        }
+        if let Some(found) = &self.conflict {
+            let mut choices = row![
+                fonts::label(format!(
+                    "Chapter {} Slot {} changed right after the restore, probably from Steam \
+                     Cloud. Keep:",
+                    found.chapter,
+                    found.slot + 1
+                ))
+                .size(BUTTON_SIZE),
+                button(text("Cloud version").size(BUTTON_SIZE))
+                    .on_press(Message::ConflictChoiceMade(Choice::Cloud)),
+                button(text("Restored backup").size(BUTTON_SIZE))
+                    .on_press(Message::ConflictChoiceMade(Choice::Restored)),
+            ]
+            .spacing(SPACING)
+            .align_y(Center);
+            if found.pre_restore.is_some() {
+                choices = choices.push(
+                    button(text("Before the restore").size(BUTTON_SIZE))
+                        .on_press(Message::ConflictChoiceMade(Choice::PreRestore)),
+                );
+            }
+            content = content.push(container(choices).padding(SPACING).style(textbox_style));
+        }
+        let content = content.push(columns);
+
+        container(content).padding(SPACING1_5).height(Fill).into()
+    }
+
+    /// Snapshotting the whole save directory, and putting a snapshot back.
+    fn create_snapshots_column(&self) -> Element<'_, Message> {
+        let mut snapshots = column![
             row![
-                game_saves_column,
-                vertical_space().width(SPACING2),
-                local_saves_column
+                text_input("Snapshot name", &self.snapshot_name)
+                    .on_input(Message::SnapshotNameChanged)
+                    .on_submit(Message::SnapshotEverything)
+                    .size(BUTTON_SIZE),
+                button(text("Snapshot everything").size(BUTTON_SIZE))
+                    .on_press(Message::SnapshotEverything),
             ]
-            .height(Fill),
-        )
-        .padding(SPACING1_5)
-        .height(Fill)
+            .spacing(SPACING)
+            .align_y(Center)
+        ]
+        .spacing(SPACING);
+        for taken in &self.snapshots {
+            snapshots = snapshots.push(
+                row![
+                    fonts::label(format!(
+                        "{}: {}",
+                        self.locale.format_time(taken.created),
+                        taken.name
+                    ))
+                    .size(BUTTON_SIZE),
+                    horizontal_space(),
+                    button(text("Restore").size(BUTTON_SIZE))
+                        .on_press(Message::RestoreSnapshot(taken.clone())),
+                ]
+                .spacing(SPACING)
+                .align_y(Center),
+            );
+        }
+        snapshots.into()
+    }
+
+    /// Each playthrough, to switch to or pick back up where it was left.
+    fn create_branches_column(&self) -> Element<'_, Message> {
+        let mut branches = column![
+            row![
+                text_input("New playthrough", &self.branch_name)
+                    .on_input(Message::BranchNameChanged)
+                    .on_submit(Message::SwitchBranch(self.branch_name.clone()))
+                    .size(BUTTON_SIZE),
+                button(text("Start playthrough").size(BUTTON_SIZE))
+                    .on_press(Message::SwitchBranch(self.branch_name.clone())),
+            ]
+            .spacing(SPACING)
+            .align_y(Center)
+        ]
+        .spacing(SPACING);
+        for name in self.branches.names() {
+            let count = self
+                .local_saves
+                .iter()
+                .filter(|save| self.branches.branch_of(save) == name)
+                .count();
+            let is_active = name == self.branches.active();
+            branches = branches.push(
+                row![
+                    fonts::label(format!(
+                        "{}{}: {} backups",
+                        name,
+                        if is_active { " (active)" } else { "" },
+                        count
+                    ))
+                    .size(BUTTON_SIZE),
+                    horizontal_space(),
+                    button(text("Switch").size(BUTTON_SIZE))
+                        .on_press_maybe((!is_active).then(|| Message::SwitchBranch(name.clone()))),
+                    button(text("Restore tip").size(BUTTON_SIZE))
+                        .on_press_maybe((count > 0).then_some(Message::RestoreBranchTip(name))),
+                ]
+                .spacing(SPACING)
+                .align_y(Center),
+            );
+        }
+        branches.into()
+    }
+
+    /// Deleted backups, which can be put back until they are purged.
+    fn create_trash_column(&self) -> Element<'_, Message> {
+        let mut trash = column![
+            row![
+                text(format!(
+                    "Trash, kept for {} days",
+                    self.retention
+                        .trash_days
+                        .unwrap_or(RetentionPolicy::DEFAULT_TRASH_DAYS)
+                ))
+                .size(BUTTON_SIZE),
+                horizontal_space(),
+                button(text("Empty trash").size(BUTTON_SIZE))
+                    .on_press_maybe((!self.trash.is_empty()).then_some(Message::EmptyTrash)),
+            ]
+            .spacing(SPACING)
+            .align_y(Center)
+        ]
+        .spacing(SPACING);
+        if self.trash.is_empty() {
+            trash = trash.push(fonts::label("The trash is empty.").size(BUTTON_SIZE));
+        }
+        for save in &self.trash {
+            trash = trash.push(
+                row![
+                    fonts::label(format!(
+                        "Chapter {} Slot {}, deleted {}",
+                        save.chapter,
+                        save.slot + 1,
+                        self.modified_label(save)
+                    ))
+                    .size(BUTTON_SIZE),
+                    horizontal_space(),
+                    button(text("Restore").size(BUTTON_SIZE))
+                        .on_press(Message::RestoreFromTrash(save.path.clone())),
+                    button(text("Delete forever").size(BUTTON_SIZE))
+                        .on_press(Message::PurgeFromTrash(save.path.clone())),
+                ]
+                .spacing(SPACING)
+                .align_y(Center),
+            );
+        }
+        trash.into()
+    }
+
+    /// Fields to change in a copy of `save`, saved as a new backup.
+    fn save_editor<'a>(
+        &'a self,
+        save: &'a SaveFile,
+        summary: &SaveSummary,
+    ) -> Element<'a, Message> {
+        let (name, gold) = self
+            .edit_drafts
+            .get(&save.path)
+            .map(|(name, gold)| (name.as_str(), gold.as_str()))
+            .unwrap_or_default();
+        column![
+            text("Edit a copy").size(8),
+            text_input(&summary.name, name)
+                .on_input(|name| Message::EditNameChanged(save.path.clone(), name))
+                .size(8),
+            text_input(&format!("D$ {}", summary.gold), gold)
+                .on_input(|gold| Message::EditGoldChanged(save.path.clone(), gold))
+                .on_submit(Message::SaveEdits(save.path.clone()))
+                .size(8),
+            button(text("Save as new backup").size(8))
+                .on_press_maybe(
+                    self.edit_drafts
+                        .contains_key(&save.path)
+                        .then(|| Message::SaveEdits(save.path.clone()))
+                )
+                .padding(2)
+        ]
+        .spacing(2)
         .into()
     }
 
-    fn create_game_saves_column(&self) -> Element<Message> {
-        let mut content = column![text("Game Saves").size(TABLE_COLUMN_HEADER_SIZE)].spacing(5);
+    /// Every backup of `slot` of `chapter`, oldest first.
+    fn timeline_saves(&self, chapter: Chapter, slot: Slot) -> Vec<&SaveFile> {
+        let mut saves = self
+            .local_saves
+            .iter()
+            .filter(|save| (save.chapter, save.slot) == (chapter, slot))
+            .collect::<Vec<_>>();
+        search::sort(&mut saves, BackupOrder::OldestFirst, |save| {
+            save.id().and_then(|id| self.backup_entries.get(id))
+        });
+        saves
+    }
+
+    /// The backups of a slot in the order they were taken, with where and how
+    /// far into the game each was, and a slider to scrub through them.
+    fn create_timeline(
+        &self,
+        chapter: Chapter,
+        slot: Slot,
+        position: usize,
+    ) -> Element<'_, Message> {
+        let saves = self.timeline_saves(chapter, slot);
+        let mut timeline = column![
+            row![
+                text(format!("Chapter {} Slot {} timeline", chapter, slot + 1)).size(10),
+                horizontal_space(),
+                button(text("Close").size(10)).on_press(Message::CloseTimeline)
+            ]
+            .spacing(SPACING)
+            .align_y(Center)
+        ]
+        .spacing(2);
+        let Some(last) = saves.len().checked_sub(1) else {
+            return timeline
+                .push(text("No backups of this slot").size(8))
+                .into();
+        };
+        let position = position.min(last);
+        if last > 0 {
+            timeline = timeline.push(
+                slider(0..=last as u32, position as u32, |position| {
+                    Message::TimelineScrubbed(position as usize)
+                })
+                .height(12),
+            );
+        }
+        for (index, save) in saves.iter().enumerate() {
+            let summary = self
+                .summaries
+                .get(&save.path)
+                .map(|summary| summary.describe(&self.rooms, chapter))
+                .unwrap_or_else(|| "unreadable".to_string());
+            let line = fonts::label(format!(
+                "{} {}: {}",
+                if index == position { "▶" } else { "  " },
+                self.modified_label(save),
+                summary
+            ))
+            .size(8);
+            timeline = timeline.push(if index == position {
+                line.color(Color::from_rgb(1.0, 1.0, 0.4))
+            } else {
+                line
+            });
+        }
+        let current = saves[position];
+        let entry = current.id().and_then(|id| self.backup_entries.get(id));
+        timeline
+            .push(
+                icon_button(
+                    Icon::Restore,
+                    format!(
+                        "Restore {}",
+                        current.display_name_with_label(entry.and_then(index::Entry::label))
+                    ),
+                    10.0,
+                )
+                .on_press(Message::RestoreTo(
+                    current.path.clone(),
+                    RestoreTarget {
+                        chapter,
+                        slot,
+                        other_chapter: false,
+                    },
+                )),
+            )
+            .into()
+    }
+
+    /// Each field that changed between two saves, as `before → after`.
+    fn create_comparison<'a>(&self, comparison: &'a Comparison) -> Element<'a, Message> {
+        let mut changes = column![
+            row![
+                text(format!("{} → {}", comparison.before, comparison.after)).size(10),
+                horizontal_space(),
+                button(text("Close").size(10)).on_press(Message::CloseComparison)
+            ]
+            .spacing(SPACING)
+            .align_y(Center)
+        ]
+        .spacing(2);
+        if comparison.changes.is_empty() {
+            changes = changes.push(text("No differences").size(8));
+        }
+        for change in &comparison.changes {
+            changes = changes.push(
+                fonts::label(format!(
+                    "{}: {} → {}",
+                    change.field, change.before, change.after
+                ))
+                .size(8),
+            );
+        }
+        changes.into()
+    }
+
+    /// Backups with the same contents, and removing all but one of each.
+    fn create_duplicates_column(&self) -> Element<'_, Message> {
+        let sets = duplicates::find(&self.local_saves);
+        let mut column = column![
+            row![
+                text("Duplicate backups").size(BUTTON_SIZE),
+                horizontal_space(),
+                button(text("Keep one of each").size(BUTTON_SIZE)).on_press_maybe(
+                    (!sets.is_empty()).then(|| Message::CollapseDuplicates(sets.clone()))
+                ),
+            ]
+            .spacing(SPACING)
+            .align_y(Center)
+        ]
+        .spacing(SPACING);
+        if sets.is_empty() {
+            column = column.push(fonts::label("No two backups are the same.").size(BUTTON_SIZE));
+        }
+        for set in sets {
+            let copies = set
+                .saves
+                .iter()
+                .map(|save| format!("Chapter {} Slot {}", save.chapter, save.slot + 1))
+                .collect::<Vec<_>>()
+                .join(", ");
+            column = column.push(
+                row![
+                    fonts::label(format!(
+                        "{} copies of {}: {}",
+                        set.saves.len(),
+                        set.saves[0].share_code().unwrap_or(set.hash.clone()),
+                        copies
+                    ))
+                    .size(BUTTON_SIZE),
+                    horizontal_space(),
+                    button(text("Keep one").size(BUTTON_SIZE))
+                        .on_press(Message::CollapseDuplicates(vec![set])),
+                ]
+                .spacing(SPACING)
+                .align_y(Center),
+            );
+        }
+        column.into()
+    }
+
+    /// What older versions' backups were migrated to, and when.
+    fn create_vault_history_column(&self) -> Element<'_, Message> {
+        let mut history = column![text("Vault history").size(BUTTON_SIZE)].spacing(SPACING);
+        if self.vault_history.is_empty() {
+            history = history.push(
+                fonts::label("The backup directory has never needed migrating.").size(BUTTON_SIZE),
+            );
+        }
+        for entry in self.vault_history.iter().rev() {
+            history = history.push(
+                fonts::label(format!(
+                    "{}: {} ({} files)",
+                    self.locale.format_time(entry.time()),
+                    entry.migration,
+                    entry.count
+                ))
+                .size(BUTTON_SIZE),
+            );
+        }
+        history.into()
+    }
+
+    fn create_game_saves_column(&self) -> Element<'_, Message> {
+        let mut content = column![
+            text("Game Saves").size(TABLE_COLUMN_HEADER_SIZE),
+            text_input("Note for the next backup", &self.backup_note)
+                .on_input(Message::BackupNoteChanged)
+                .size(BUTTON_SIZE),
+            icon_button(Icon::Backup, "Back up all occupied slots", BUTTON_SIZE)
+                .on_press_maybe((!self.game_saves.is_empty()).then_some(Message::BackupAllSlots))
+        ]
+        .spacing(5);
 
-        for chapter in 1..=CHAPTER_COUNT {
-            let chapter_title = text(format!("Chapter {}", chapter)).size(SPACING2);
+        for chapter in 1..=self.store.profile().chapter_count {
+            let chapter_title = text(self.chapter_title(chapter)).size(SPACING2);
             let mut slots_cell = column![].spacing(SPACING);
 
-            for slot in 0..=BUILTIN_SLOT_MAX_INDEX {
+            for slot in 0..self.store.profile().slot_count {
                 let slot_content = if let Some(save) = self.game_saves.get(&(chapter, slot)) {
-                    column![
-                        button(text(format!("Slot {}", slot + 1)).size(BUTTON_SIZE))
+                    let mut actions = row![
+                        icon_button(Icon::Backup, format!("Slot {}", slot + 1), BUTTON_SIZE)
                             .on_press(Message::BackupSave(chapter, slot))
-                            .width(Length::Fixed(80.0)),
-                        vertical_space().height(SPACING),
-                        text(format!(
-                            "Modified: {}",
-                            save.modified
-                                .map(|t| format!("{:?}", t))
-                                .unwrap_or("Unknown".to_string())
-                        ))
-                        .size(10)
+                            .width(Length::Fixed(96.0))
                     ]
+                    .spacing(SPACING);
+                    if self.store.profile().completion_slot_offset.is_some() {
+                        actions = actions.push(
+                            icon_button(Icon::Handoff, "Handoff", BUTTON_SIZE)
+                                .on_press(Message::ChapterHandoff(chapter, slot)),
+                        );
+                    }
+                    actions = actions.push(
+                        icon_button(Icon::Export, "Export file…", BUTTON_SIZE)
+                            .on_press(Message::ExportFile(save.clone())),
+                    );
+                    actions = actions.push(
+                        icon_button(Icon::Export, "Export as JSON…", BUTTON_SIZE)
+                            .on_press(Message::ExportJson(save.clone())),
+                    );
+                    actions = actions.push(
+                        button(text("View contents").size(10))
+                            .on_press(Message::ViewContents(save.clone())),
+                    );
+                    let mut details = column![actions, vertical_space().height(SPACING)];
+                    if let Some(summary) = self.summaries.get(&save.path) {
+                        details = details
+                            .push(text(summary.describe(&self.rooms, chapter)).size(10))
+                            .push(text(summary.progress()).size(10))
+                            .push(route_badge(summary.route, 10.0));
+                    }
+                    details = details
+                        .push(text(format!("Modified: {}", self.modified_label(save))).size(10));
+                    let save_filename = self.store.profile().save_filename(chapter, slot);
+                    if self.scheduler.is_some() {
+                        let enabled = self
+                            .schedule
+                            .is_enabled_for(&self.store.profile().name, &save_filename);
+                        details = details.push(
+                            checkbox("On schedule", enabled)
+                                .on_toggle(move |enabled| {
+                                    Message::ScheduledSlotToggled(chapter, slot, enabled)
+                                })
+                                .size(10)
+                                .text_size(10),
+                        );
+                    }
+                    if let Some(until) = self.freezes.frozen_until(&save_filename) {
+                        details = details.push(
+                            text(format!("Frozen until {}", freeze::format_local_time(until)))
+                                .size(10),
+                        );
+                    }
+                    details
                 } else {
                     column![
-                        button(text(format!("Slot {}", slot + 1)).size(BUTTON_SIZE))
-                            .width(Length::Fixed(80.0)),
+                        icon_button(Icon::Backup, format!("Slot {}", slot + 1), BUTTON_SIZE)
+                            .width(Length::Fixed(96.0)),
                         text("Empty").size(10)
                     ]
                 };
@@ -240,6 +2883,69 @@ impl Deltasaver {
             }
 
             content = content.push(chapter_title).push(slots_cell);
+
+            let mut extras = self
+                .extra_saves
+                .values()
+                .filter(|save| save.chapter == chapter)
+                .collect::<Vec<_>>();
+            extras.sort_by_key(|save| save.slot);
+            let shown = self.extra_files.contains(&chapter);
+            if !extras.is_empty() || shown {
+                content = content.push(
+                    checkbox("Show extra files", shown)
+                        .on_toggle(move |shown| Message::ExtraFilesToggled(chapter, shown))
+                        .size(10)
+                        .text_size(10),
+                );
+            }
+            if shown {
+                let mut extras_cell =
+                    column![text("Extra files (read-only)").size(12)].spacing(SPACING);
+                for save in extras {
+                    let filename = self.store.profile().save_filename(chapter, save.slot);
+                    extras_cell = extras_cell.push(
+                        container(
+                            column![
+                                icon_button(Icon::Backup, filename, BUTTON_SIZE)
+                                    .on_press(Message::BackupSave(chapter, save.slot)),
+                                text(format!("Modified: {}", self.modified_label(save))).size(10)
+                            ]
+                            .width(Length::Fill),
+                        )
+                        .padding(SPACING)
+                        .style(textbox_style),
+                    );
+                }
+                content = content.push(extras_cell);
+            }
+        }
+
+        for (index, demo) in self.demos.iter().enumerate() {
+            let mut slots_cell = column![].spacing(SPACING);
+            for (chapter, slot) in demo.store.profile().slots() {
+                let label = format!("Chapter {} Slot {}", chapter, slot + 1);
+                let slot_content = if let Some(save) = demo.saves.game.get(&(chapter, slot)) {
+                    column![
+                        icon_button(Icon::Backup, label, BUTTON_SIZE)
+                            .on_press(Message::BackupDemoSave(index, chapter, slot)),
+                        text(format!("Modified: {}", self.modified_label(save))).size(10)
+                    ]
+                } else {
+                    column![
+                        icon_button(Icon::Backup, label, BUTTON_SIZE),
+                        text("Empty").size(10)
+                    ]
+                };
+                slots_cell = slots_cell.push(
+                    container(slot_content.spacing(SPACING0_5).width(Length::Fill))
+                        .padding(SPACING)
+                        .style(textbox_style),
+                );
+            }
+            content = content
+                .push(text(demo.store.profile().name.as_str()).size(SPACING2))
+                .push(slots_cell);
         }
 
         container(
@@ -257,59 +2963,378 @@ impl Deltasaver {
         .into()
     }
 
-    fn create_local_saves_column(&self) -> Element<Message> {
-        let mut content =
-            column![text("Local Saves").size(TABLE_COLUMN_HEADER_SIZE)].spacing(SPACING);
+    fn create_local_saves_column(&self) -> Element<'_, Message> {
+        let mut content = column![
+            text("Local Saves").size(TABLE_COLUMN_HEADER_SIZE),
+            row![
+                text_input(
+                    "Search notes, tags, hashes, codes, chapter:2, date:2025-06",
+                    &self.search_query
+                )
+                .on_input(Message::SearchQueryChanged)
+                .size(BUTTON_SIZE),
+                pick_list(
+                    self.tag_filters(),
+                    Some(self.tag_filter.clone()),
+                    Message::TagFilterSelected
+                )
+                .text_size(BUTTON_SIZE),
+                pick_list(
+                    BackupOrder::ALL,
+                    Some(self.backup_order),
+                    Message::BackupOrderSelected
+                )
+                .text_size(BUTTON_SIZE)
+            ]
+            .spacing(SPACING)
+            .align_y(Center),
+            row![
+                text_input("Paste a share code", &self.share_code)
+                    .on_input(Message::ShareCodeChanged)
+                    .on_submit(Message::ImportShareCode)
+                    .size(BUTTON_SIZE),
+                icon_button(Icon::Restore, "Import share code", 10.0).on_press_maybe(
+                    (!self.share_code.trim().is_empty()).then_some(Message::ImportShareCode)
+                )
+            ]
+            .spacing(SPACING)
+            .align_y(Center)
+        ]
+        .spacing(SPACING);
+        content = content.push(
+            row![
+                icon_button(Icon::Restore, "Import archive…", 10.0)
+                    .on_press(Message::ImportArchive),
+                button(
+                    text(if self.checking_integrity {
+                        "Checking backups…"
+                    } else {
+                        "Check backups for damage"
+                    })
+                    .size(10)
+                )
+                .on_press_maybe((!self.checking_integrity).then_some(Message::CheckIntegrity)),
+            ]
+            .spacing(SPACING)
+            .align_y(Center),
+        );
+        if !self.selected_backups.is_empty() {
+            content = content.push(
+                row![
+                    icon_button(
+                        Icon::Export,
+                        format!("Export {} selected…", self.selected_backups.len()),
+                        10.0
+                    )
+                    .on_press(Message::ExportSelected),
+                    icon_button(
+                        Icon::Delete,
+                        format!("Delete {} selected", self.selected_backups.len()),
+                        10.0
+                    )
+                    .on_press(Message::DeleteSelected),
+                    button(text("Compare").size(10)).on_press_maybe(
+                        (self.selected_backups.len() <= 2).then_some(Message::CompareSelected)
+                    ),
+                    button(text("Clear selection").size(10)).on_press(Message::ClearSelection),
+                ]
+                .spacing(SPACING)
+                .align_y(Center),
+            );
+            if self.confirming_bulk_delete {
+                content = content.push(
+                    row![
+                        text(format!(
+                            "Move {} backups to the trash? Pinned ones are kept.",
+                            self.selected_backups.len()
+                        ))
+                        .size(10),
+                        button(text("Delete").size(10))
+                            .on_press(Message::DeleteSelectedConfirmed(true)),
+                        button(text("Cancel").size(10))
+                            .on_press(Message::DeleteSelectedConfirmed(false)),
+                    ]
+                    .spacing(SPACING)
+                    .align_y(Center),
+                );
+            }
+        }
+
+        if let Some((chapter, slot, position)) = self.timeline {
+            content = content.push(
+                container(self.create_timeline(chapter, slot, position))
+                    .padding(SPACING)
+                    .style(textbox_style),
+            );
+        }
+        if let Some(browser) = &self.flag_browser {
+            content = content.push(
+                container(create_flag_browser(browser))
+                    .padding(SPACING)
+                    .style(textbox_style),
+            );
+        }
+        if let Some(viewer) = &self.contents_viewer {
+            content = content.push(
+                container(create_contents_viewer(viewer))
+                    .padding(SPACING)
+                    .style(textbox_style),
+            );
+        }
+        if let Some(comparison) = &self.comparison {
+            content = content.push(
+                container(self.create_comparison(comparison))
+                    .padding(SPACING)
+                    .style(textbox_style),
+            );
+        }
+
+        let query = search::Query::parse(&self.search_query);
+        let active_branch = self.branches.active();
+        let matching_saves = self
+            .local_saves
+            .iter()
+            .filter(|save| self.branches.branch_of(save) == active_branch)
+            .filter(|save| {
+                let entry = save.id().and_then(|id| self.backup_entries.get(id));
+                self.tag_filter.matches(entry) && query.matches(save, entry)
+            });
 
         let mut saves_by_chapter: HashMap<Chapter, Vec<&SaveFile>> = HashMap::new();
-        for save in &self.local_saves {
-            saves_by_chapter
-                .entry(save.chapter)
-                .or_insert_with(Vec::new)
-                .push(save);
+        for save in matching_saves {
+            saves_by_chapter.entry(save.chapter).or_default().push(save);
         }
 
-        for chapter in 1..=CHAPTER_COUNT {
-            let chapter_title = text(format!("Chapter {}", chapter)).size(16);
+        for chapter in 1..=self.store.profile().chapter_count {
+            let chapter_title = text(self.chapter_title(chapter)).size(16);
 
             if let Some(saves) = saves_by_chapter.get(&chapter) {
                 let mut slots_by_slot: HashMap<Slot, Vec<&SaveFile>> = HashMap::new();
                 for save in saves {
-                    slots_by_slot
-                        .entry(save.slot)
-                        .or_insert_with(Vec::new)
-                        .push(save);
+                    slots_by_slot.entry(save.slot).or_default().push(save);
                 }
 
-                let mut chapter_content = column![chapter_title].spacing(SPACING);
+                let chapter_header = row![
+                    chapter_title,
+                    horizontal_space(),
+                    import_button(chapter),
+                    icon_button(Icon::Export, "Export history", 10.0)
+                        .on_press(Message::ExportChapterHistory(chapter))
+                ]
+                .spacing(SPACING)
+                .align_y(Center);
+                let mut chapter_content = column![chapter_header].spacing(SPACING);
 
-                for slot in 0..=BUILTIN_SLOT_MAX_INDEX {
-                    if let Some(slot_saves) = slots_by_slot.get(&slot) {
-                        let slot_title = text(format!("Slot {}", slot + 1)).size(14);
+                for slot in 0..self.store.profile().slot_count {
+                    if let Some(slot_saves) = slots_by_slot.get_mut(&slot) {
+                        search::sort(slot_saves, self.backup_order, |save| {
+                            save.id().and_then(|id| self.backup_entries.get(id))
+                        });
+                        let slot_title = row![
+                            text(format!("Slot {}", slot + 1)).size(14),
+                            button(text("Timeline").size(10))
+                                .on_press(Message::ShowTimeline(chapter, slot))
+                        ]
+                        .spacing(SPACING)
+                        .align_y(Center);
                         let mut slot_cell = column![].spacing(SPACING);
 
-                        for save in slot_saves {
-                            let save_content = column![
-                                button(text(save.display_name()).size(10))
-                                    .on_press(Message::RestoreSave(
+                        for save in slot_saves.iter() {
+                            let entry = save.id().and_then(|id| self.backup_entries.get(id));
+                            let note = match self.note_drafts.get(&save.path) {
+                                Some(draft) => draft.as_str(),
+                                None => entry
+                                    .and_then(|entry| entry.note.as_deref())
+                                    .unwrap_or_default(),
+                            };
+                            let name = match self.name_drafts.get(&save.path) {
+                                Some(draft) => draft.as_str(),
+                                None => entry
+                                    .and_then(|entry| entry.name.as_deref())
+                                    .unwrap_or_default(),
+                            };
+                            let pinned = entry.is_some_and(|entry| entry.pinned);
+                            let mut title = row![
+                                icon_button(
+                                    Icon::Restore,
+                                    save.display_name_with_label(
+                                        entry.and_then(index::Entry::label)
+                                    ),
+                                    10.0
+                                )
+                                .on_press(Message::RestoreTo(
+                                    save.path.clone(),
+                                    RestoreTarget {
+                                        chapter,
+                                        slot,
+                                        other_chapter: false,
+                                    }
+                                ))
+                                .width(Length::Fixed(120.0))
+                            ]
+                            .spacing(SPACING0_5)
+                            .align_y(Center);
+                            if pinned {
+                                title = title.push(icons::icon(Icon::Pin, 10.0, Color::WHITE));
+                            }
+                            let profile = self.store.profile();
+                            let targets = (1..=profile.chapter_count)
+                                .flat_map(|other| {
+                                    (0..profile.slot_count).map(move |slot| RestoreTarget {
+                                        chapter: other,
+                                        slot,
+                                        other_chapter: other != chapter,
+                                    })
+                                })
+                                .filter(|target| target.other_chapter || target.slot != slot)
+                                .collect::<Vec<_>>();
+                            let mut heading = column![title].spacing(2);
+                            if let Some(summary) = self.summaries.get(&save.path) {
+                                heading = heading
+                                    .push(
+                                        fonts::label(summary.describe(&self.rooms, chapter))
+                                            .size(8)
+                                            .width(Length::Fixed(120.0)),
+                                    )
+                                    .push(fonts::label(summary.progress()).size(8))
+                                    .push(route_badge(summary.route, 8.0))
+                                    .push(completion_bar(summary.completion(chapter)));
+                            }
+                            if let Some(problem) = self.damaged.get(&save.path) {
+                                heading = heading.push(
+                                    fonts::label(format!("Damaged: {}", problem))
+                                        .size(8)
+                                        .color(Color::from_rgb(1.0, 0.4, 0.4))
+                                        .width(Length::Fixed(120.0)),
+                                );
+                            }
+                            let mut save_content = column![
+                                heading,
+                                pick_list(targets, None::<RestoreTarget>, |target| {
+                                    Message::RestoreTo(save.path.clone(), target)
+                                })
+                                .placeholder("Restore to…")
+                                .text_size(10)
+                                .width(Length::Fixed(120.0)),
+                                icon_button(Icon::Delete, "Delete", 10.0)
+                                    .on_press(Message::DeleteLocalSave(save.path.clone()))
+                                    .width(Length::Fixed(120.0)),
+                                icon_button(Icon::Export, "Copy as base64", 10.0)
+                                    .on_press(Message::CopyAsBase64(
                                         save.path.clone(),
                                         chapter,
                                         slot
                                     ))
                                     .width(Length::Fixed(120.0)),
-                                button(text("Delete").size(10))
-                                    .on_press(Message::DeleteLocalSave(save.path.clone()))
+                                icon_button(Icon::Export, "Copy share code", 10.0)
+                                    .on_press(Message::CopyShareCode(
+                                        save.path.clone(),
+                                        chapter,
+                                        slot
+                                    ))
+                                    .width(Length::Fixed(120.0)),
+                                icon_button(Icon::Export, "Export file…", 10.0)
+                                    .on_press(Message::ExportFile((*save).clone()))
                                     .width(Length::Fixed(120.0)),
+                                icon_button(Icon::Export, "Export as JSON…", 10.0)
+                                    .on_press(Message::ExportJson((*save).clone()))
+                                    .width(Length::Fixed(120.0)),
+                                checkbox("Select", self.selected_backups.contains(&save.path))
+                                    .on_toggle(|selected| Message::BackupSelected(
+                                        save.path.clone(),
+                                        selected
+                                    ))
+                                    .size(10)
+                                    .text_size(10),
+                                checkbox("Pinned", pinned)
+                                    .on_toggle(|pinned| Message::PinToggled(
+                                        save.path.clone(),
+                                        pinned
+                                    ))
+                                    .size(10)
+                                    .text_size(10),
+                                checkbox("Details", self.expanded_backups.contains(&save.path))
+                                    .on_toggle(|expanded| Message::BackupExpanded(
+                                        save.path.clone(),
+                                        expanded
+                                    ))
+                                    .size(10)
+                                    .text_size(10),
                                 vertical_space().height(SPACING),
                                 text(format!(
-                                    "Modified: {}",
-                                    save.modified
-                                        .map(|t| format!("{:?}", t))
+                                    "Code: {}",
+                                    save.share_code().unwrap_or("Unknown".to_string())
+                                ))
+                                .size(8),
+                                text(format!("Modified: {}", self.modified_label(save))).size(8),
+                                text(format!(
+                                    "Size: {}",
+                                    save.size
+                                        .map(|size| self.locale.format_size(size))
                                         .unwrap_or("Unknown".to_string())
                                 ))
-                                .size(8)
+                                .size(8),
+                                text_input("Name", name)
+                                    .on_input(|name| Message::NameChanged(save.path.clone(), name))
+                                    .on_submit(Message::SaveName(save.path.clone()))
+                                    .size(8),
+                                text_input("Note", note)
+                                    .on_input(|note| Message::NoteChanged(save.path.clone(), note))
+                                    .on_submit(Message::SaveNote(save.path.clone()))
+                                    .size(8)
                             ]
                             .spacing(2);
+                            let mut tags = row![].spacing(SPACING0_5).align_y(Center);
+                            for tag in entry.iter().flat_map(|entry| &entry.tags) {
+                                tags = tags.push(
+                                    button(text(format!("{} ×", tag)).size(8))
+                                        .on_press(Message::RemoveTag(
+                                            save.path.clone(),
+                                            tag.clone(),
+                                        ))
+                                        .padding(2),
+                                );
+                            }
+                            let tag_draft = self
+                                .tag_drafts
+                                .get(&save.path)
+                                .map(String::as_str)
+                                .unwrap_or_default();
+                            save_content = save_content.push(
+                                tags.push(
+                                    text_input("Add tag", tag_draft)
+                                        .on_input(|tag| Message::TagChanged(save.path.clone(), tag))
+                                        .on_submit(Message::AddTag(save.path.clone()))
+                                        .size(8)
+                                        .width(Length::Fixed(80.0)),
+                                ),
+                            );
+                            if self.expanded_backups.contains(&save.path) {
+                                save_content =
+                                    save_content.push(match self.summaries.get(&save.path) {
+                                        Some(summary) => save_details(summary, chapter),
+                                        None => {
+                                            column![text("No details: unreadable save").size(8)]
+                                        }
+                                    });
+                                save_content = save_content.push(
+                                    row![
+                                        button(text("Browse flags").size(8))
+                                            .on_press(Message::BrowseFlags(save.path.clone()))
+                                            .padding(2),
+                                        button(text("View contents").size(8))
+                                            .on_press(Message::ViewContents((*save).clone()))
+                                            .padding(2)
+                                    ]
+                                    .spacing(SPACING0_5),
+                                );
+                                if let Some(summary) =
+                                    self.summaries.get(&save.path).filter(|_| self.save_editor)
+                                {
+                                    save_content =
+                                        save_content.push(self.save_editor(save, summary));
+                                }
+                            }
 
                             slot_cell = slot_cell.push(
                                 container(save_content.width(Length::Fill))
@@ -322,9 +3347,77 @@ impl Deltasaver {
                     }
                 }
 
+                // Backups of extra files can't be restored, only deleted.
+                let mut extra_backups = slots_by_slot
+                    .iter()
+                    .filter(|(slot, _)| self.store.profile().is_extra_slot(**slot))
+                    .flat_map(|(_, saves)| saves)
+                    .collect::<Vec<_>>();
+                extra_backups.sort_by_key(|save| (save.slot, save.id()));
+                if self.extra_files.contains(&chapter) && !extra_backups.is_empty() {
+                    let mut extras_cell = column![].spacing(SPACING);
+                    for save in extra_backups {
+                        extras_cell = extras_cell.push(
+                            container(
+                                column![
+                                    text(self.store.profile().save_filename(chapter, save.slot))
+                                        .size(10),
+                                    icon_button(Icon::Delete, "Delete", 10.0)
+                                        .on_press(Message::DeleteLocalSave(save.path.clone()))
+                                        .width(Length::Fixed(120.0)),
+                                    text(format!("Modified: {}", self.modified_label(save)))
+                                        .size(8)
+                                ]
+                                .spacing(2)
+                                .width(Length::Fill),
+                            )
+                            .padding(SPACING)
+                            .style(textbox_style),
+                        );
+                    }
+                    chapter_content = chapter_content
+                        .push(text("Extra files (read-only)").size(14))
+                        .push(extras_cell);
+                }
+
                 content = content.push(chapter_content);
             } else {
-                content = content.push(chapter_title).push(text("No saves").size(12));
+                content = content
+                    .push(
+                        row![chapter_title, horizontal_space(), import_button(chapter)]
+                            .align_y(Center),
+                    )
+                    .push(text("No saves").size(12));
+            }
+        }
+
+        for (index, demo) in self.demos.iter().enumerate() {
+            content = content.push(text(demo.store.profile().name.as_str()).size(16));
+            if demo.saves.local.is_empty() {
+                content = content.push(text("No saves").size(12));
+            }
+            for save in &demo.saves.local {
+                let save_content = column![
+                    icon_button(Icon::Restore, save.display_name(), 10.0)
+                        .on_press(Message::RestoreDemoSave(
+                            index,
+                            save.path.clone(),
+                            save.chapter,
+                            save.slot
+                        ))
+                        .width(Length::Fixed(120.0)),
+                    icon_button(Icon::Delete, "Delete", 10.0)
+                        .on_press(Message::DeleteLocalSave(save.path.clone()))
+                        .width(Length::Fixed(120.0)),
+                    vertical_space().height(SPACING),
+                    text(format!("Modified: {}", self.modified_label(save))).size(8)
+                ]
+                .spacing(2);
+                content = content.push(
+                    container(save_content.width(Length::Fill))
+                        .padding(SPACING)
+                        .style(textbox_style),
+                );
             }
         }
 
@@ -344,15 +3437,162 @@ impl Deltasaver {
     }
 }
 
-fn container_style(_theme: &Theme) -> container::Style {
-    container::Style {
-        background: Some(Background::Color(Color::from_rgb(0.1, 0.1, 0.1))),
+fn import_button<'a>(chapter: Chapter) -> Button<'a, Message> {
+    icon_button(Icon::Restore, "Import from clipboard", 10.0)
+        .on_press(Message::ImportFromClipboard(chapter))
+}
+
+/// Marks which route a save is on, standing out if it is the weird route, so
+/// it is not restored by mistake.
+fn route_badge<'a>(route: Route, size: f32) -> Element<'a, Message> {
+    let color = match route {
+        Route::Normal => Color::WHITE,
+        Route::Weird => Color::from_rgb(1.0, 0.4, 0.4),
+    };
+    container(
+        fonts::label(format!("{} route", route))
+            .size(size)
+            .color(color),
+    )
+    .padding([0.0, SPACING0_5])
+    .style(move |_theme| container::Style {
         border: Border {
-            radius: SPACING0_5.into(),
-            ..Default::default()
+            color,
+            width: 1.0,
+            radius: 4.0.into(),
         },
         ..Default::default()
+    })
+    .into()
+}
+
+/// The flags of a save matching the browser's query, see
+/// [`format::flag_matches`].
+fn create_flag_browser(browser: &FlagBrowser) -> Element<'_, Message> {
+    let matching = browser
+        .flags
+        .iter()
+        .enumerate()
+        .filter(|(flag, value)| format::flag_matches(*flag, value, &browser.query))
+        .map(|(flag, value)| (flag, value, format::flag_meaning(flag)))
+        .collect::<Vec<_>>();
+    let mut flags = column![
+        row![
+            text(format!("Flags of {}", browser.title)).size(10),
+            horizontal_space(),
+            button(text("Close").size(10)).on_press(Message::CloseFlags)
+        ]
+        .spacing(SPACING)
+        .align_y(Center),
+        text_input(
+            "Flag index or meaning; empty lists set and known flags",
+            &browser.query
+        )
+        .on_input(Message::FlagQueryChanged)
+        .size(8)
+    ]
+    .spacing(2);
+    for (flag, value, meaning) in matching.iter().take(MAX_LISTED_FLAGS) {
+        flags = flags.push(
+            fonts::label(match meaning {
+                Some(meaning) => format!("{} = {}  ({})", flag, value, meaning),
+                None => format!("{} = {}", flag, value),
+            })
+            .size(8),
+        );
     }
+    if matching.len() > MAX_LISTED_FLAGS {
+        flags = flags.push(
+            text(format!(
+                "…and {} more; narrow the search",
+                matching.len() - MAX_LISTED_FLAGS
+            ))
+            .size(8),
+        );
+    }
+    flags.into()
+}
+
+/// The page of a save's lines or hex rows the viewer is on.
+fn create_contents_viewer(viewer: &ContentsViewer) -> Element<'_, Message> {
+    let rows = viewer.rows();
+    let start = viewer.page * CONTENTS_PAGE_ROWS;
+    let page_count = viewer.page_count();
+    let mut navigation = row![
+        checkbox("Hex", viewer.show_hex)
+            .on_toggle(Message::ContentsHexToggled)
+            .size(10)
+            .text_size(10),
+        horizontal_space(),
+    ]
+    .spacing(SPACING)
+    .align_y(Center);
+    let mut previous = button(text("Previous").size(10));
+    if viewer.page > 0 {
+        previous = previous.on_press(Message::ContentsPageChanged(viewer.page - 1));
+    }
+    let mut next = button(text("Next").size(10));
+    if viewer.page + 1 < page_count {
+        next = next.on_press(Message::ContentsPageChanged(viewer.page + 1));
+    }
+    navigation = navigation
+        .push(previous)
+        .push(text(format!("Page {} of {}", viewer.page + 1, page_count)).size(10))
+        .push(next);
+
+    let mut contents = column![
+        row![
+            text(format!("Contents of {}", viewer.title)).size(10),
+            horizontal_space(),
+            button(text("Close").size(10)).on_press(Message::CloseContents)
+        ]
+        .spacing(SPACING)
+        .align_y(Center),
+        navigation
+    ]
+    .spacing(2);
+    for line in rows.iter().skip(start).take(CONTENTS_PAGE_ROWS) {
+        contents = contents.push(fonts::label(line.as_str()).size(8));
+    }
+    contents.into()
+}
+
+/// How much of its chapter a save has done, as a bar and a percentage, see
+/// [`SaveSummary::completion`].
+fn completion_bar<'a>(completion: f32) -> Element<'a, Message> {
+    row![
+        progress_bar(0.0..=1.0, completion)
+            .width(Length::Fixed(80.0))
+            .height(Length::Fixed(4.0)),
+        text(format!("{:.0}%", completion * 100.0)).size(8)
+    ]
+    .spacing(SPACING0_5)
+    .align_y(Center)
+    .into()
+}
+
+/// What `summary` says beyond its one-line form, for a save's details.
+fn save_details<'a>(summary: &SaveSummary, chapter: Chapter) -> Column<'a, Message> {
+    let mut details = column![].spacing(2);
+    if let Some((recruited, total)) = summary.recruits(chapter) {
+        details = details.push(text(format!("Recruits: {}/{}", recruited, total)).size(8));
+    }
+    details = details.push(text("Party").size(8));
+    for member in &summary.party {
+        details = details.push(fonts::label(member.to_string()).size(8));
+    }
+    details
+}
+
+fn icon_button<'a>(icon: Icon, label: impl Into<String>, size: f32) -> Button<'a, Message> {
+    button(
+        row![
+            icons::icon(icon, size, Color::WHITE),
+            fonts::label(label).size(size)
+        ]
+        .spacing(SPACING0_5)
+        .align_y(Center),
+    )
 }
 
 fn textbox_style(_theme: &Theme) -> container::Style {
@@ -378,139 +3618,142 @@ fn column_style(_theme: &Theme) -> container::Style {
     }
 }
 
-async fn load_saves(
-    deltarune_directory: PathBuf,
-    local_directory: PathBuf,
-) -> Result<(HashMap<(Chapter, Slot), SaveFile>, Vec<SaveFile>), LoadError> {
-    let mut game_saves = HashMap::new();
-    let mut local_saves = Vec::new();
-
-    if deltarune_directory.exists() {
-        let entries = fs::read_dir(&deltarune_directory).map_err(|e| LoadError::IoError(()))?;
-
-        for entry in entries {
-            let entry = entry.map_err(|e| LoadError::IoError(()))?;
-            let path = entry.path();
-
-            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                println!("Found game file: {}", filename);
-                if let Some((chapter, slot)) = parse_save_filename(filename) {
-                    println!("Parsed as chapter {} slot {}", chapter, slot);
-                    let modified = fs::metadata(&path).ok().and_then(|m| m.modified().ok());
-                    let save = SaveFile {
-                        path: path.clone(),
-                        chapter,
-                        slot,
-                        hash: None,
-                        modified,
-                        is_local: false,
-                    };
-                    game_saves.insert((chapter, slot), save);
-                } else {
-                    println!("Could not parse filename: {}", filename);
-                }
+/// Reports every batch of slots the game writes to `profile`'s save
+/// directory, for as long as the subscription is kept.
+fn watch_saves(profile: GameProfile) -> impl Stream<Item = Message> {
+    iced::stream::channel(1, move |mut output| async move {
+        let mut watcher = match SaveWatcher::new(profile) {
+            Ok(watcher) => watcher,
+            Err(error) => {
+                let _ = output
+                    .send(Message::AutoBackupFailed(error.to_string()))
+                    .await;
+                return;
+            }
+        };
+        loop {
+            // The watcher blocks, so it waits on a thread of its own.
+            let Ok((returned, changes)) = tokio::task::spawn_blocking(move || {
+                let changes = watcher.next_changes();
+                (watcher, changes)
+            })
+            .await
+            else {
+                return;
+            };
+            watcher = returned;
+            let Some(changes) = changes else {
+                return;
+            };
+            let message = Message::SlotsWritten(changes.into_iter().collect());
+            if output.send(message).await.is_err() {
+                return;
             }
         }
-    }
+    })
+}
 
-    // Load local saves
-    if local_directory.exists() {
-        let entries = fs::read_dir(&local_directory).map_err(|e| LoadError::IoError(()))?;
+/// Asks the user where to write `save`, named as the game names it so it can
+/// be dropped straight into a save directory, then writes it there. Returns
+/// `None` if the dialog was cancelled.
+async fn export_file(store: SaveStore, save: SaveFile) -> Result<Option<PathBuf>, String> {
+    let Some(handle) = rfd::AsyncFileDialog::new()
+        .set_file_name(store.profile().save_filename(save.chapter, save.slot))
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
 
-        for entry in entries {
-            let entry = entry.map_err(|e| LoadError::IoError(()))?;
-            let path = entry.path();
+    let contents = store.read_save(&save).map_err(|error| error.to_string())?;
+    let path = handle.path().to_path_buf();
+    std::fs::write(&path, contents).map_err(|error| error.to_string())?;
+    Ok(Some(path))
+}
 
-            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                if let Some((chapter, slot, hash)) = parse_local_save_filename(filename) {
-                    let modified = fs::metadata(&path).ok().and_then(|m| m.modified().ok());
-                    let save = SaveFile {
-                        path: path.clone(),
-                        chapter,
-                        slot,
-                        hash: Some(hash),
-                        modified,
-                        is_local: true,
-                    };
-                    local_saves.push(save);
-                }
-            }
-        }
-    }
+/// Asks the user where to write the parsed fields and flags of `save` as
+/// JSON, see [`json::save_json`], then writes them there. Returns `None` if
+/// the dialog was cancelled.
+async fn export_json(
+    store: SaveStore,
+    save: SaveFile,
+    rooms: RoomNames,
+) -> Result<Option<PathBuf>, String> {
+    let Some(handle) = rfd::AsyncFileDialog::new()
+        .set_file_name(format!(
+            "{}.json",
+            store.profile().save_filename(save.chapter, save.slot)
+        ))
+        .add_filter("JSON", &["json"])
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
 
-    Ok((game_saves, local_saves))
+    let contents = store.read_save(&save).map_err(|error| error.to_string())?;
+    let json = json::save_json(&contents, save.chapter, &rooms)
+        .ok_or_else(|| "not a full DELTARUNE save".to_string())?;
+    let path = handle.path().to_path_buf();
+    std::fs::write(&path, json).map_err(|error| error.to_string())?;
+    Ok(Some(path))
 }
 
-fn parse_save_filename(filename: &str) -> Option<(Chapter, Slot)> {
-    println!("Parsing filename: {}", filename);
-    if filename.starts_with("filech") {
-        let parts: Vec<&str> = filename[6..].split('_').collect();
-        println!("Parts: {:?}", parts);
-        if parts.len() == 2 {
-            if let (Ok(chapter), Ok(slot)) = (parts[0].parse::<u8>(), parts[1].parse::<u8>()) {
-                if slot <= 2 {
-                    println!("Successfully parsed: chapter {}, slot {}", chapter, slot);
-                    return Some((chapter, slot));
-                } else {
-                    println!("Ignoring slot {} (only 0-2 are save slots)", slot);
-                }
-            }
-        }
-    }
-    println!("Failed to parse filename: {}", filename);
-    None
+/// Asks the user for an archive, then imports the backups in it. Returns
+/// `None` if the dialog was cancelled.
+async fn import_backups(store: SaveStore) -> Result<Option<ImportSummary>, ImportError> {
+    let Some(handle) = rfd::AsyncFileDialog::new()
+        .add_filter("DELTASAVER archive", &[export::ARCHIVE_EXTENSION, "zip"])
+        .pick_file()
+        .await
+    else {
+        return Ok(None);
+    };
+
+    import::import_archive(handle.path(), &store).map(Some)
 }
 
-fn parse_local_save_filename(filename: &str) -> Option<(Chapter, Slot, String)> {
-    if filename.starts_with("filech") {
-        let parts: Vec<&str> = filename[6..].split('_').collect();
-        if parts.len() >= 3 {
-            if let (Ok(chapter), Ok(slot)) = (parts[0].parse::<u8>(), parts[1].parse::<u8>()) {
-                if slot <= 2 {
-                    return Some((chapter, slot, parts[2].to_string()));
-                }
-            }
-        }
-    }
-    None
+/// Asks the user where to put the archive, then writes `saves` into it.
+/// Returns `None` if the dialog was cancelled.
+async fn export_backups(
+    store: SaveStore,
+    saves: Vec<SaveFile>,
+) -> Result<Option<PathBuf>, ExportError> {
+    let Some(handle) = rfd::AsyncFileDialog::new()
+        .set_file_name(format!("deltarune-backups.{}", export::ARCHIVE_EXTENSION))
+        .add_filter("DELTASAVER archive", &[export::ARCHIVE_EXTENSION])
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+
+    let path = handle.path().to_path_buf();
+    export::write_backup_archive(&path, &store, &saves)?;
+    Ok(Some(path))
 }
 
-async fn backup_save(
-    source_path: PathBuf,
-    local_directory: PathBuf,
+/// Asks the user where to put the archive, then writes every local save of
+/// `chapter` into it. Returns `None` if the dialog was cancelled.
+async fn export_chapter_history(
     chapter: Chapter,
-    slot: Slot,
-) -> Result<(), io::Error> {
-    let contents = fs::read(&source_path)?;
-    let hash = format!("{:x}", Sha256::digest(&contents));
-    let now = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap();
-    let filename = format!(
-        "filech{}_{}_{}_{}_{}",
-        chapter,
-        slot,
-        hash,
-        now.as_secs(),
-        now.subsec_nanos()
-    );
-    let dest_path = local_directory.join(filename);
-    fs::write(dest_path, contents)?;
-    Ok(())
-}
-
-async fn restore_save(
-    local_path: PathBuf,
-    deltarune_directory: PathBuf,
-    chapter: Chapter,
-    slot: Slot,
-) -> Result<(), io::Error> {
-    let contents = fs::read(&local_path)?;
-    let dest_path = deltarune_directory.join(format!("filech{}_{}", chapter, slot));
-    fs::write(dest_path, contents)?;
-    Ok(())
-}
+    store: SaveStore,
+    saves: Vec<SaveFile>,
+) -> Result<Option<PathBuf>, ExportError> {
+    let Some(handle) = rfd::AsyncFileDialog::new()
+        .set_file_name(format!(
+            "deltarune-chapter{}-history.{}",
+            chapter,
+            export::ARCHIVE_EXTENSION
+        ))
+        .add_filter("DELTASAVER archive", &[export::ARCHIVE_EXTENSION])
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
 
-async fn delete_local_save(path: PathBuf) -> Result<(), io::Error> {
-    fs::remove_file(path)
+    let path = handle.path().to_path_buf();
+    export::write_chapter_archive(&path, chapter, &store, &saves)?;
+    Ok(Some(path))
 }
@@ -0,0 +1,238 @@
+// Copyright (C) 2025 Ethan Uppal. All rights reserved.
+
+use deltasaver_core::config::RetentionPolicy;
+use deltasaver_core::index;
+use deltasaver_core::{Chapter, Locale, SaveFile, SaveStore, Saves, Slot, retention};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::io;
+
+const HELP: &str =
+    "tab: switch column  ↑/↓: move  b: backup  r: restore  d: delete  R: refresh  q: quit";
+
+#[derive(PartialEq, Eq)]
+enum Focus {
+    GameSaves,
+    LocalSaves,
+}
+
+/// The terminal counterpart of the windowed app, with the same two columns.
+struct Tui {
+    store: SaveStore,
+    saves: Saves,
+    game_slots: Vec<(Chapter, Slot)>,
+    local_saves: Vec<SaveFile>,
+    /// The notes and tags of the local saves, by ID.
+    backup_entries: HashMap<String, index::Entry>,
+    focus: Focus,
+    game_state: ListState,
+    local_state: ListState,
+    status: String,
+    locale: Locale,
+    /// Which old backups to delete after each backup.
+    retention: RetentionPolicy,
+}
+
+pub fn run(store: SaveStore, locale: Locale, retention: RetentionPolicy) -> io::Result<()> {
+    store.ensure_local_directory()?;
+
+    let game_slots = store.profile().slots().collect();
+    let mut tui = Tui {
+        store,
+        saves: Saves::default(),
+        game_slots,
+        local_saves: Vec::new(),
+        backup_entries: HashMap::new(),
+        focus: Focus::GameSaves,
+        game_state: ListState::default().with_selected(Some(0)),
+        local_state: ListState::default(),
+        status: HELP.to_string(),
+        locale,
+        retention,
+    };
+    tui.refresh();
+
+    let mut terminal = ratatui::init();
+    let result = tui.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl Tui {
+    fn refresh(&mut self) {
+        match self.store.load() {
+            Ok(saves) => {
+                let mut local_saves = saves.local.clone();
+                local_saves
+                    .sort_by(|a, b| (a.chapter, a.slot, a.id()).cmp(&(b.chapter, b.slot, b.id())));
+                self.local_saves = local_saves;
+                self.saves = saves;
+                self.backup_entries = self
+                    .store
+                    .index()
+                    .and_then(|index| index.entries())
+                    .unwrap_or_default();
+            }
+            Err(error) => self.status = format!("Could not load saves: {}", error),
+        }
+
+        if self.local_saves.is_empty() {
+            self.local_state.select(None);
+        } else if self
+            .local_state
+            .selected()
+            .is_none_or(|index| index >= self.local_saves.len())
+        {
+            self.local_state.select(Some(0));
+        }
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Tab | KeyCode::Left | KeyCode::Right => {
+                    self.focus = match self.focus {
+                        Focus::GameSaves => Focus::LocalSaves,
+                        Focus::LocalSaves => Focus::GameSaves,
+                    };
+                }
+                KeyCode::Up | KeyCode::Char('k') => self.focused_state().select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.focused_state().select_next(),
+                KeyCode::Char('b') => self.backup_selected(),
+                KeyCode::Char('r') => self.restore_selected(),
+                KeyCode::Char('d') => self.delete_selected(),
+                KeyCode::Char('R') => {
+                    self.refresh();
+                    self.status = "Refreshed".to_string();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn focused_state(&mut self) -> &mut ListState {
+        match self.focus {
+            Focus::GameSaves => &mut self.game_state,
+            Focus::LocalSaves => &mut self.local_state,
+        }
+    }
+
+    fn selected_game_slot(&self) -> Option<(Chapter, Slot)> {
+        self.game_slots.get(self.game_state.selected()?).copied()
+    }
+
+    fn selected_local_save(&self) -> Option<&SaveFile> {
+        self.local_saves.get(self.local_state.selected()?)
+    }
+
+    fn backup_selected(&mut self) {
+        let Some((chapter, slot)) = self.selected_game_slot() else {
+            return;
+        };
+        if !self.saves.game.contains_key(&(chapter, slot)) {
+            self.status = format!("Chapter {} Slot {} is empty", chapter, slot + 1);
+            return;
+        }
+        self.status = match self.store.backup(chapter, slot) {
+            Ok(_) => match retention::prune(&self.store, &self.retention) {
+                Ok(pruned) if !pruned.is_empty() => format!(
+                    "Backed up Chapter {} Slot {} and pruned {} old backups",
+                    chapter,
+                    slot + 1,
+                    pruned.len()
+                ),
+                Ok(_) => format!("Backed up Chapter {} Slot {}", chapter, slot + 1),
+                Err(error) => format!("Backed up, but pruning failed: {}", error),
+            },
+            Err(error) => format!("Backup failed: {}", error),
+        };
+        self.refresh();
+    }
+
+    fn restore_selected(&mut self) {
+        let Some(save) = self.selected_local_save().cloned() else {
+            return;
+        };
+        self.status = match self.store.restore(&save.path, save.chapter, save.slot) {
+            Ok(()) => format!("Restored {}", save.display_name()),
+            Err(error) => format!("Restore failed: {}", error),
+        };
+        self.refresh();
+    }
+
+    fn delete_selected(&mut self) {
+        let Some(save) = self.selected_local_save().cloned() else {
+            return;
+        };
+        self.status = match self.store.delete(&save.path) {
+            Ok(()) => format!("Moved {} to the trash", save.display_name()),
+            Err(error) => format!("Delete failed: {}", error),
+        };
+        self.refresh();
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [columns_area, status_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        let [game_area, local_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(columns_area);
+
+        let game_items = self.game_slots.iter().map(|(chapter, slot)| {
+            let state = if self.saves.game.contains_key(&(*chapter, *slot)) {
+                "saved"
+            } else {
+                "empty"
+            };
+            ListItem::new(format!("Chapter {}, Slot {}  {}", chapter, slot + 1, state))
+        });
+        let game_list = List::new(game_items)
+            .block(self.column_block("Game Saves", Focus::GameSaves))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(game_list, game_area, &mut self.game_state);
+
+        let local_items = self.local_saves.iter().map(|save| {
+            let label = save
+                .id()
+                .and_then(|id| self.backup_entries.get(id))
+                .and_then(index::Entry::label);
+            ListItem::new(format!(
+                "{}  {}  {}",
+                save.display_name_with_label(label),
+                save.share_code().unwrap_or_default(),
+                save.modified
+                    .map(|time| self.locale.format_time(time))
+                    .unwrap_or_default()
+            ))
+        });
+        let local_list = List::new(local_items)
+            .block(self.column_block("Local Saves", Focus::LocalSaves))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(local_list, local_area, &mut self.local_state);
+
+        frame.render_widget(Paragraph::new(self.status.as_str()), status_area);
+    }
+
+    fn column_block(&self, title: &'static str, focus: Focus) -> Block<'static> {
+        let block = Block::bordered().title(title);
+        if self.focus == focus {
+            block.border_style(Style::new().add_modifier(Modifier::BOLD))
+        } else {
+            block
+        }
+    }
+}